@@ -0,0 +1,48 @@
+use core::fmt;
+
+/// Everything that can go wrong when playing moves on a board.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Error {
+    RowOutOfBounds,
+    ColumnOutOfBounds,
+    Occupied,
+    Blocked,
+    NotASide,
+    MustMove,
+    MustPlace,
+    NotYourPiece,
+    NotAdjacent,
+    NoMoves,
+    GameOver,
+    NothingToUndo,
+    InvalidNotation(&'static str),
+}
+impl Error {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::RowOutOfBounds    => "Row index out of bounds.",
+            Self::ColumnOutOfBounds => "Column index out of bounds.",
+            Self::Occupied          => "Already occupied tile.",
+            Self::Blocked           => "Blocked tile, nobody can play there.",
+            Self::NotASide          => "Only crosses and noughts make moves.",
+            Self::MustMove          => "All your pieces are on the board, move one of them: FROM to TO.",
+            Self::MustPlace         => "Place all your pieces before moving them.",
+            Self::NotYourPiece      => "There is no piece of yours to move from there.",
+            Self::NotAdjacent       => "Pieces only move to a tile next to them.",
+            Self::NoMoves           => "No empty tile to move to.",
+            Self::GameOver          => "The game is already over.",
+            Self::NothingToUndo     => "Nothing to undo.",
+            Self::InvalidNotation(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl core::error::Error for Error {}
+
+pub type Result<T> = core::result::Result<T, Error>;
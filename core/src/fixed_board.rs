@@ -0,0 +1,106 @@
+use crate::grid::{Grid, Piece, Shape, Tile};
+use crate::search;
+use crate::{Error, Result};
+use alloc::vec::Vec;
+
+/// A board of `N`x`N` tiles kept in an array, so it is `Copy` and searching it never touches the
+/// heap. It shares the `Grid` interface with the growable `Board` of `tick-tack-toe`, which
+/// searches 3x3 positions in one.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FixedBoard<const N: usize, T = Tile> {
+    tiles: [[T; N]; N],
+    win_row_length: usize,
+}
+impl<const N: usize> FixedBoard<N> {
+    pub fn new(win_row_length: usize) -> Self {
+        Self::empty(win_row_length)
+    }
+
+    pub fn perfect_move(&mut self, side: Tile) -> Option<(usize, usize)> {
+        search::best(self.move_values(side))
+    }
+
+    /// As `search::move_values`.
+    pub fn move_values(&mut self, side: Tile) -> Vec<((usize, usize), i8)> {
+        search::move_values(self, side).0
+    }
+}
+
+impl<const N: usize, T: Piece> FixedBoard<N, T> {
+    pub fn empty(win_row_length: usize) -> Self {
+        Self { tiles: [[T::EMPTY; N]; N], win_row_length }
+    }
+
+    /// The same position, when `grid` is `N` square tiles across.
+    pub fn from_grid(grid: &impl Grid<T>) -> Option<Self> {
+        if grid.length() != N || grid.shape() != Shape::Square {
+            return None;
+        }
+
+        let mut fixed = Self::empty(grid.win_row_length());
+        for row in 0..N {
+            for col in 0..N {
+                fixed.tiles[row][col] = grid.tile(row, col);
+            }
+        }
+        Some(fixed)
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<T> {
+        self.tiles.get(row)?.get(col).copied()
+    }
+
+    pub fn set(&mut self, tile: T, row: usize, col: usize) -> Result<()> {
+        let slot = self.tiles
+            .get_mut(row).ok_or(Error::RowOutOfBounds)?
+            .get_mut(col).ok_or(Error::ColumnOutOfBounds)?;
+
+        if *slot != T::EMPTY {
+            return Err(if slot.is_player() { Error::Occupied } else { Error::Blocked });
+        }
+
+        *slot = tile;
+        Ok(())
+    }
+}
+
+impl<const N: usize, T: Piece> Grid<T> for FixedBoard<N, T> {
+    fn length(&self) -> usize {
+        N
+    }
+
+    fn win_row_length(&self) -> usize {
+        self.win_row_length
+    }
+
+    fn tile(&self, row: usize, col: usize) -> T {
+        self.tiles[row][col]
+    }
+
+    fn put(&mut self, tile: T, row: usize, col: usize) {
+        self.tiles[row][col] = tile;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FixedBoard;
+    use crate::grid::{BoardStatus, Grid, Tile::*};
+
+    #[test]
+    fn fixed_board() {
+        let mut fixed = FixedBoard::<3>::new(3);
+        for (tile, row, col) in [(Cross, 0, 0), (Nought, 0, 1), (Cross, 1, 1), (Nought, 2, 0)] {
+            fixed.set(tile, row, col).unwrap();
+        }
+        assert_eq!(FixedBoard::from_grid(&fixed), Some(fixed));
+        assert_eq!(FixedBoard::<4>::from_grid(&fixed), None);
+
+        assert_eq!(fixed.perfect_move(Cross), Some((2, 2)));
+        assert_eq!(fixed.board_status(), BoardStatus::Continue);
+        fixed.set(Cross, 2, 2).unwrap();
+        assert_eq!(fixed.board_status(), BoardStatus::Winner(Cross));
+        assert!(fixed.set(Nought, 2, 2).is_err());
+        assert!(fixed.set(Nought, 3, 0).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+//! The pieces, and what the status check needs to know about a board.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+/// What a board is filled with. `Tile` is the usual piece, variants with more symbols, or more
+/// players, bring their own and get the same board.
+pub trait Piece: Copy + PartialEq + Debug {
+    /// The piece of a tile nobody has played on.
+    const EMPTY: Self;
+
+    fn char(&self) -> &'static str;
+
+    fn from_char(c: char) -> Option<Self>;
+
+    /// Whether the piece is a player's, which rows can be made of. Pieces that aren't, such as
+    /// blocked tiles, break every row they are on.
+    fn is_player(&self) -> bool {
+        *self != Self::EMPTY
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Tile {
+    Empty,
+    Cross,
+    Nought,
+    /// A tile nobody can play on, which breaks every row through it.
+    Blocked,
+}
+impl Tile {
+    pub fn char(&self) -> &'static str {
+        match self {
+            Self::Empty   => " ",
+            Self::Cross  => "X",
+            Self::Nought => "O",
+            Self::Blocked => "#",
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Tile> {
+        match c.to_ascii_uppercase() {
+            ' ' | '.' => Some(Self::Empty),
+            'X' => Some(Self::Cross),
+            'O' => Some(Self::Nought),
+            '#' => Some(Self::Blocked),
+            _ => None,
+        }
+    }
+
+    /// Whether the tile holds a mark of one of the sides.
+    pub fn is_player(&self) -> bool {
+        matches!(self, Self::Cross | Self::Nought)
+    }
+
+    pub fn opposite(&self) -> Option<Tile> {
+        match &self {
+            Self::Cross => Some(Self::Nought),
+            Self::Nought => Some(Self::Cross),
+            Self::Empty | Self::Blocked => None,
+        }
+    }
+}
+impl Piece for Tile {
+    const EMPTY: Self = Self::Empty;
+
+    fn char(&self) -> &'static str {
+        Tile::char(self)
+    }
+
+    fn from_char(c: char) -> Option<Self> {
+        Tile::from_char(c)
+    }
+
+    fn is_player(&self) -> bool {
+        Tile::is_player(self)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BoardStatus<T = Tile> {
+    Winner(T),
+    Tie,
+    Continue,
+}
+
+/// The ways a row can run from its first tile: across, down and down both diagonals.
+pub const DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+/// The ways a row can run on a hex board: along the columns, along the rows and down to the left,
+/// the three axes of its hexagons.
+pub const HEX_DIRECTIONS: [(i32, i32); 3] = [(0, 1), (1, 0), (1, -1)];
+
+/// How the tiles of a board touch, which decides the ways a row can run on it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Shape {
+    /// Squares, touching the eight around them.
+    #[default]
+    Square,
+    /// Hexagons in a rhombus, in axial coordinates: the row is r and the column q, and each row
+    /// sits half a tile further right than the one above, so a tile touches the two next to it
+    /// in its row, and two in the rows above and below.
+    Hex,
+}
+impl Shape {
+    pub fn directions(&self) -> &'static [(i32, i32)] {
+        match self {
+            Self::Square => &DIRECTIONS,
+            Self::Hex    => &HEX_DIRECTIONS,
+        }
+    }
+}
+
+/// Every row of `win_row_length` tiles that fits on a board of `length` and `shape`, each one
+/// once, as its first tile and the step to the next one.
+pub fn lines(shape: Shape, length: usize, win_row_length: usize) -> impl Iterator<Item = ((usize, usize), (i32, i32))> {
+    let (length, win) = (length as i32, win_row_length as i32);
+    let on_board = move |i| (0..length).contains(&i);
+
+    (0..length)
+        .flat_map(move |row| (0..length).map(move |col| (row, col)))
+        .flat_map(move |start| shape.directions().iter().map(move |&step| (start, step)))
+        .filter(move |&((row, col), (row_step, col_step))| {
+            win > 0 && on_board(row + (win - 1) * row_step) && on_board(col + (win - 1) * col_step)
+        })
+        .map(|((row, col), step)| ((row as usize, col as usize), step))
+}
+
+/// The tile `i` steps along the row from `start`.
+fn along((row, col): (usize, usize), (row_step, col_step): (i32, i32), i: usize) -> (usize, usize) {
+    ((row as i32 + i as i32 * row_step) as usize, (col as i32 + i as i32 * col_step) as usize)
+}
+
+/// The tiles of a board as the status check and the search see them, so they work the same on
+/// the growable `Board` and the array backed `FixedBoard`.
+pub trait Grid<T: Piece> {
+    fn length(&self) -> usize;
+
+    fn win_row_length(&self) -> usize;
+
+    /// Square unless the board says otherwise.
+    fn shape(&self) -> Shape {
+        Shape::Square
+    }
+
+    /// The piece on a tile, which must be on the board.
+    fn tile(&self, row: usize, col: usize) -> T;
+
+    /// Puts `tile` on the board without any checks, `T::EMPTY` takes a piece back.
+    fn put(&mut self, tile: T, row: usize, col: usize);
+
+    fn board_status(&self) -> BoardStatus<T> {
+        if let Some(line) = self.winning_line() {
+            let (row, col) = line[0];
+            return BoardStatus::Winner(self.tile(row, col));
+        }
+
+        // Once every row holds pieces of two sides, or a piece of no side, nobody can win any more,
+        // even with tiles left.
+        let win = self.win_row_length();
+        let blocked = |start, step| {
+            let mut pieces = (0..win)
+                .map(|i| along(start, step, i))
+                .map(|(row, col)| self.tile(row, col))
+                .filter(|t| *t != T::EMPTY);
+            pieces.next().is_some_and(|first| !first.is_player() || pieces.any(|t| t != first))
+        };
+        if lines(self.shape(), self.length(), win).all(|(start, step)| blocked(start, step)) {
+            return BoardStatus::Tie;
+        }
+
+        BoardStatus::Continue
+    }
+
+    /// The tiles of a completed row of one side, `None` while nobody has won.
+    fn winning_line(&self) -> Option<Vec<(usize, usize)>> {
+        let win = self.win_row_length();
+        lines(self.shape(), self.length(), win)
+            .find(|&(start, step)| self.complete(start, step))
+            .map(|(start, step)| (0..win).map(|i| along(start, step, i)).collect())
+    }
+
+    /// Every completed row of one side, a run longer than the win length once for each row of
+    /// the win length in it.
+    fn completed_rows(&self) -> Vec<Vec<(usize, usize)>> {
+        let win = self.win_row_length();
+        lines(self.shape(), self.length(), win)
+            .filter(|&(start, step)| self.complete(start, step))
+            .map(|(start, step)| (0..win).map(|i| along(start, step, i)).collect())
+            .collect()
+    }
+
+    /// Whether the row from `start` along `step` holds pieces of one side only.
+    fn complete(&self, start: (usize, usize), step: (i32, i32)) -> bool {
+        let (row, col) = along(start, step, 0);
+        let side = self.tile(row, col);
+        side.is_player() && (1..self.win_row_length()).all(|i| {
+            let (row, col) = along(start, step, i);
+            self.tile(row, col) == side
+        })
+    }
+
+    fn empty_tiles(&self) -> Vec<(usize, usize)> {
+        (0..self.length())
+            .flat_map(|row| (0..self.length()).map(move |col| (row, col)))
+            .filter(|(row, col)| self.tile(*row, *col) == T::EMPTY)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lines, Shape::*};
+
+    #[test]
+    fn count_lines() {
+        assert_eq!(lines(Square, 3, 3).count(), 8);
+        // Two per row and column, and 2 by 2 starts down each diagonal.
+        assert_eq!(lines(Square, 4, 3).count(), 24);
+        assert_eq!(lines(Square, 3, 4).count(), 0);
+        assert_eq!(lines(Square, 1, 1).count(), 4);
+        assert_eq!(lines(Square, 3, 0).count(), 0);
+        // Rows, columns and the one diagonal down to the left.
+        assert_eq!(lines(Hex, 3, 3).count(), 7);
+        assert_eq!(lines(Hex, 4, 3).count(), 20);
+    }
+}
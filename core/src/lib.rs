@@ -0,0 +1,14 @@
+//! The game logic of `tick-tack-toe` without I/O, threads or the standard library, only `alloc`,
+//! so the engine runs on microcontrollers too: the pieces, the status check, the search and an
+//! array backed board.
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod error;
+pub mod fixed_board;
+pub mod grid;
+pub mod search;
+
+pub use error::{Error, Result};
@@ -0,0 +1,84 @@
+//! A full minimax search, for any `Grid` of `Tile`s.
+
+use crate::grid::{BoardStatus, Grid, Tile};
+use alloc::vec::Vec;
+
+/// Every empty tile with its value.
+pub type MoveValues = Vec<((usize, usize), i8)>;
+
+/// The tile with the highest value.
+pub fn best(values: MoveValues) -> Option<(usize, usize)> {
+    values.into_iter()
+        .max_by_key(|(_, value)| *value)
+        .map(|(pos, _)| pos)
+}
+
+/// The minimax value of every empty tile for `side`, 1 is a forced win and -1 a forced loss,
+/// and the number of positions searched.
+pub fn move_values(board: &mut impl Grid<Tile>, side: Tile) -> (MoveValues, u64) {
+    if side == Tile::Empty {
+        return (Vec::new(), 0);
+    }
+
+    let mut positions = 0;
+    let values = board.empty_tiles()
+        .into_iter()
+        .map(|(row, col)| ((row, col), value_of_move(board, side, row, col, &mut positions)))
+        .collect();
+
+    (values, positions)
+}
+
+// //Private function where row and col always should be correct.
+fn value_of_move(board: &mut impl Grid<Tile>, side: Tile, move_row: usize, move_col: usize, positions: &mut u64) -> i8 {
+    const WIN_VALUE: i8 = 1;
+    const DRAW_VALUE: i8 = 0;
+    const LOOSE_VALUE: i8 = -1;
+
+    debug_assert_eq!(board.tile(move_row, move_col), Tile::Empty);
+    let Some(opponent) = side.opposite() else { return DRAW_VALUE };
+
+    board.put(side, move_row, move_col);
+    *positions += 1;
+
+    let value = match board.board_status() {
+        BoardStatus::Winner(tile) => {
+            if tile == side {WIN_VALUE}
+            else {LOOSE_VALUE}
+        },
+        BoardStatus::Tie => DRAW_VALUE,
+        BoardStatus::Continue => {
+            let opponent_move = board.empty_tiles()
+                .into_iter()
+                .map(|(row, col)| value_of_move(board, opponent, row, col, positions))
+                .max()
+                .unwrap_or(DRAW_VALUE);
+
+            -opponent_move
+        }
+    };
+
+    board.put(Tile::Empty, move_row, move_col);
+    value
+}
+
+/// The number of positions `depth` moves from `board` with `side` to move, not counting games that
+/// end sooner, like perft in chess engines, to check how moves are generated. A game ends on a win
+/// or a full board here, a tie found early still has moves to count.
+pub fn perft(board: &mut impl Grid<Tile>, side: Tile, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let Some(opponent) = side.opposite() else { return 0 };
+    if let BoardStatus::Winner(_) = board.board_status() {
+        return 0;
+    }
+
+    let mut count = 0;
+    for (row, col) in board.empty_tiles() {
+        board.put(side, row, col);
+        count += perft(board, opponent, depth - 1);
+        board.put(Tile::Empty, row, col);
+    }
+    count
+}
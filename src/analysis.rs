@@ -0,0 +1,196 @@
+//! Whole game analysis for studying recorded games: every move evaluated by the search, mistakes
+//! marked and the best line shown where the result slipped.
+
+use crate::board::{Board, BoardStatus, Tile};
+use crate::coords;
+use crate::game::Move;
+use crate::openings;
+use crate::record::Record;
+use crate::Result;
+
+/// What the search thinks of one move.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MoveAnalysis {
+    pub m: Move,
+    /// The value of the position for the side that moved, before and after the move: 1 is a forced
+    /// win, 0 a draw and -1 a forced loss.
+    pub before: i8,
+    pub after: i8,
+    /// The best move in the position, the played one when it was as good as any.
+    pub best: (usize, usize),
+    /// Best play for both sides from `best` on, for moves that gave away a better result.
+    pub line: Vec<Move>,
+}
+impl MoveAnalysis {
+    /// `?` for a move that gave away half the result, from a win to a draw or a draw to a loss,
+    /// `??` for one that turned a win into a loss.
+    pub fn glyph(&self) -> Option<&'static str> {
+        match self.before - self.after {
+            0 => None,
+            1 => Some("?"),
+            _ => Some("??"),
+        }
+    }
+}
+
+/// Evaluates every move of `record`. Every position is searched to the end, so this is only quick
+/// on small boards.
+pub fn analyze(record: &Record) -> Result<Vec<MoveAnalysis>> {
+    // Checks the moves, so every one of them is on an empty tile below.
+    record.to_game()?;
+    let mut board = record.start.clone();
+    let mut analysis = Vec::new();
+
+    for &m in &record.moves {
+        let values = board.move_values(m.tile);
+        let (best, before) = values.iter().copied().max_by_key(|(_, value)| *value).unwrap_or(((m.row, m.col), 0));
+        let after = values.iter().find(|(pos, _)| *pos == (m.row, m.col)).map_or(before, |(_, value)| *value);
+
+        analysis.push(match after < before {
+            true  => MoveAnalysis { m, before, after, best, line: best_line(&board, m.tile, best) },
+            false => MoveAnalysis { m, before, after, best: (m.row, m.col), line: Vec::new() },
+        });
+        let _ = board.play(&m);
+    }
+    Ok(analysis)
+}
+
+/// The game from `board` on when `side` plays `first` and then both sides play the best moves.
+fn best_line(board: &Board, side: Tile, first: (usize, usize)) -> Vec<Move> {
+    let mut board = board.clone();
+    let mut line = vec![Move { tile: side, row: first.0, col: first.1, from: None }];
+    let _ = board.set(side, first.0, first.1);
+
+    let mut to_move = side;
+    while board.board_status() == BoardStatus::Continue {
+        to_move = to_move.opposite().unwrap_or(to_move);
+        let Some(m) = board.best_move(to_move) else { break };
+        let _ = board.play(&m);
+        line.push(m);
+    }
+    line
+}
+
+/// The result a value for `side` stands for, e.g. `Cross wins`.
+fn outcome(side: Tile, value: i8) -> String {
+    match value {
+        1  => format!("{:?} wins", side),
+        -1 => format!("{:?} wins", side.opposite().unwrap_or(Tile::Empty)),
+        _  => "draw".to_string(),
+    }
+}
+
+fn notation(m: &Move) -> String {
+    m.notation()
+}
+
+fn line_notation(line: &[Move]) -> String {
+    line.iter().map(notation).collect::<Vec<_>>().join(" ")
+}
+
+/// What went wrong with a move, e.g. `best was c1 (draw)`, `None` for good moves.
+fn note(a: &MoveAnalysis) -> Option<String> {
+    a.glyph().map(|_| format!("best was {} ({})", coords::notation(a.best.0, a.best.1), outcome(a.m.tile, a.before)))
+}
+
+/// The board, the players and the opening as the heading of a report.
+fn heading(record: &Record) -> String {
+    let board = &record.start;
+    let mut heading = format!("{}x{} board, {} in a row to win", board.length(), board.length(), board.win_row_length());
+    for side in ["Cross", "Nought"] {
+        if let Some(player) = record.tag(side) {
+            heading.push_str(&format!(", {}: {}", side, player));
+        }
+    }
+    if let Some(opening) = openings::classify(board, &record.moves) {
+        heading.push_str(&format!(", {}", opening.name));
+    }
+    heading
+}
+
+/// How many mistakes and blunders each side made, e.g. `Cross: 0 mistakes, 1 blunder`.
+fn tally(analysis: &[MoveAnalysis]) -> String {
+    let plural = |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+    [Tile::Cross, Tile::Nought]
+        .iter()
+        .map(|&side| {
+            let count = |glyph| analysis.iter().filter(|a| a.m.tile == side && a.glyph() == Some(glyph)).count();
+            format!("{:?}: {}, {}", side, plural(count("?"), "mistake"), plural(count("??"), "blunder"))
+        })
+        .collect::<Vec<_>>()
+        .join(". ")
+}
+
+/// The analysis as plain text, one line per move with the evaluation after it and, for mistakes,
+/// the best line from the position before.
+pub fn text(record: &Record, analysis: &[MoveAnalysis]) -> String {
+    let mut out = format!("Analysis of a game on a {}\n", heading(record));
+    out.push_str(&format!("Start: {}\n\n", outcome(Tile::Cross, start_value(record, analysis))));
+
+    for (i, a) in analysis.iter().enumerate() {
+        let played = format!("{}{}", notation(&a.m), a.glyph().unwrap_or(""));
+        out.push_str(&format!("{:>3}. {:<6} {}", i + 1, played, outcome(a.m.tile, a.after)));
+        if let Some(note) = note(a) {
+            out.push_str(&format!(", {}\n     Best line: {}", note, line_notation(&a.line)));
+        }
+        out.push('\n');
+    }
+
+    out.push_str(&format!("\n{}.\n", tally(analysis)));
+    out
+}
+
+/// The analysis as Markdown: a table of the moves, then the best lines at the mistakes.
+pub fn markdown(record: &Record, analysis: &[MoveAnalysis]) -> String {
+    let mut out = format!("# Game analysis\n\n{}.\n\nAt the start: {}.\n\n", heading(record), outcome(Tile::Cross, start_value(record, analysis)));
+    out.push_str("| # | Move | Evaluation | Note |\n|--:|---|---|---|\n");
+    for (i, a) in analysis.iter().enumerate() {
+        let note = note(a).map(|note| format!("**{}** {}", a.glyph().unwrap_or(""), note)).unwrap_or_default();
+        out.push_str(&format!("| {} | {} | {} | {} |\n", i + 1, notation(&a.m), outcome(a.m.tile, a.after), note));
+    }
+
+    let key_moments: Vec<(usize, &MoveAnalysis)> = analysis.iter().enumerate().filter(|(_, a)| !a.line.is_empty()).collect();
+    if !key_moments.is_empty() {
+        out.push_str("\n## Key moments\n\n");
+        for (i, a) in key_moments {
+            out.push_str(&format!("- Move {}, {} instead of {}: {}\n", i + 1, notation(&a.line[0]), notation(&a.m), line_notation(&a.line)));
+        }
+    }
+
+    out.push_str(&format!("\n{}.\n", tally(analysis)));
+    out
+}
+
+/// The value of the starting position for Cross.
+fn start_value(record: &Record, analysis: &[MoveAnalysis]) -> i8 {
+    match analysis.first() {
+        Some(a) if a.m.tile == Tile::Cross => a.before,
+        Some(a) => -a.before,
+        None => record.start.move_values(Tile::Cross).iter().map(|(_, value)| *value).max().unwrap_or(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyze, markdown, text};
+    use crate::record::Record;
+
+    #[test]
+    fn report() {
+        let record = Record::parse("[Size \"3\"]\n[Cross \"human\"]\n1. Xb2 Ob1 2. Xa1 Oc3 3. Xa3 Oa2 4. Xc1").unwrap();
+        let analysis = analyze(&record).unwrap();
+        let glyphs: Vec<_> = analysis.iter().map(|a| a.glyph()).collect();
+        assert_eq!(glyphs, [None, Some("?"), None, None, None, None, None]);
+        assert_eq!(analysis[1].best, (2, 2));
+        assert_eq!(analysis[1].line.len(), 8);
+
+        let text = text(&record, &analysis);
+        assert!(text.starts_with("Analysis of a game on a 3x3 board, 3 in a row to win, Cross: human, Center opening, edge reply\nStart: draw\n\n"), "{}", text);
+        assert!(text.contains("  2. Ob1?   Cross wins, best was c3 (draw)\n     Best line: Oc3 "), "{}", text);
+        assert!(text.ends_with("Cross: 0 mistakes, 0 blunders. Nought: 1 mistake, 0 blunders.\n"), "{}", text);
+
+        let markdown = markdown(&record, &analysis);
+        assert!(markdown.contains("| 2 | Ob1 | Cross wins | **?** best was c3 (draw) |\n"), "{}", markdown);
+        assert!(markdown.contains("## Key moments\n\n- Move 2, Oc3 instead of Ob1: Oc3 "), "{}", markdown);
+    }
+}
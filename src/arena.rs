@@ -0,0 +1,91 @@
+//! Competitions between strategies listed in a file, one entrant per line with its name, the
+//! algorithm and its settings:
+//!
+//! ```text
+//! # name     algorithm   settings
+//! cautious   minimax     depth=1
+//! deep       minimax     depth=4 seed=3
+//! gambler    montecarlo  rollouts=50 seed=7
+//! oracle     perfect
+//! visitor    plugin      ./libvisitor.so
+//! remote     engine      10.0.0.2:7878
+//! ```
+//!
+//! `plugin` entrants name a shared library, see the `plugin` module, and `engine` entrants the
+//! address of a `serve` server, see `engine`.
+
+use crate::{engine, plugin};
+use crate::strategy::{self, Algorithm, Config};
+use std::path::Path;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entry {
+    pub name: String,
+    pub algorithm: Algorithm,
+    pub config: Config,
+}
+
+/// The entries of an arena file, skipping blank lines and `#` comments.
+pub fn parse(text: &str) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&name, rest)) = words.split_first() else { continue };
+
+        let entry = rest.split_first()
+            .ok_or("an entrant needs an algorithm: random, perfect, minimax, montecarlo, plugin or engine.".to_string())
+            .and_then(|(&algorithm, settings)| {
+                let (algorithm, settings) = match (algorithm, settings) {
+                    ("plugin", [library, settings @ ..]) => (Algorithm::Plugin(plugin::load(Path::new(library))?), settings),
+                    ("plugin", []) => return Err("plugin entrants need the path of their library.".to_string()),
+                    ("engine", [address, settings @ ..]) => (Algorithm::Engine(engine::connect(address)?), settings),
+                    ("engine", []) => return Err("engine entrants need the address of the server.".to_string()),
+                    _ => (Algorithm::parse(algorithm)
+                        .ok_or(format!("'{}' isn't an algorithm, use random, perfect, minimax, montecarlo, plugin or engine.", algorithm))?, settings),
+                };
+                Ok(Entry { name: name.to_string(), algorithm, config: Config::parse(settings)? })
+            })
+            .map_err(|err| format!("Line {}: {}", number + 1, err))?;
+        entries.push(entry);
+    }
+
+    if entries.len() < 2 {
+        return Err("An arena needs at least two entrants.".to_string());
+    }
+    Ok(entries)
+}
+
+/// Registers every entry as a strategy, returning their names as player kinds.
+pub fn register(entries: &[Entry]) -> Result<Vec<&'static str>, String> {
+    entries.iter()
+        .map(|e| strategy::register(&e.name, e.algorithm, e.config).map(|s| s.name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, register, Entry};
+    use crate::player::Player;
+    use crate::strategy::{Algorithm, Config};
+    use tick_tack_toe::board::Tile::*;
+
+    #[test]
+    fn parse_entries() {
+        let entries = parse("# The usual suspects\narena-cautious minimax depth=1\n\narena-gambler montecarlo rollouts=5 seed=7  # lucky\n").unwrap();
+        assert_eq!(entries[1], Entry {
+            name: "arena-gambler".to_string(),
+            algorithm: Algorithm::MonteCarlo,
+            config: Config { rollouts: Some(5), seed: Some(7), ..Config::default() },
+        });
+        assert_eq!(register(&entries), Ok(vec!["arena-cautious", "arena-gambler"]));
+        assert_eq!(Player::from_kind("arena-cautious", Nought).map(|p| p.kind()), Some("arena-cautious"));
+
+        assert_eq!(parse("a minimax\nb\n"), Err("Line 2: an entrant needs an algorithm: random, perfect, minimax, montecarlo, plugin or engine.".to_string()));
+        assert!(parse("a minimax\nb plugin\n").is_err());
+        assert!(parse("a minimax\nb plugin /nonexistent/libb.so\n").unwrap_err().starts_with("Line 2: Couldn't load the plugin"));
+        assert!(parse("a minimax\nb alphago\n").is_err());
+        assert!(parse("a minimax depth=deep\nb random\n").is_err());
+        assert!(parse("a minimax\n").is_err());
+    }
+}
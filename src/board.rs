@@ -1,262 +1,836 @@
-
-use itertools::Itertools;
-
-
-#[derive(Copy, Clone, PartialEq, Debug)]
-pub enum Tile {
-    Empty,
-    Cross,
-    Nought,
+use crate::game::Move;
+use crate::log;
+use crate::render::{self, Layout, Theme};
+use crate::variant;
+use crate::{Error, Result};
+use tick_tack_toe_core::fixed_board::FixedBoard;
+use tick_tack_toe_core::search::{self, best};
+
+pub use tick_tack_toe_core::grid::{BoardStatus, Grid, Piece, Shape, Tile};
+
+/// The tiles that matter for the side to move, the tactics beginners learn first.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Tactics {
+    /// Win the game at once.
+    pub wins: Vec<(usize, usize)>,
+    /// Where the opponent wins next move unless it's taken.
+    pub blocks: Vec<(usize, usize)>,
+    /// Make two winning threats at once, which the opponent can't both block.
+    pub forks: Vec<(usize, usize)>,
 }
-impl Tile {
-    pub fn char(&self) -> &'static str {
-        match self {
-            Self::Empty   => " ",
-            Self::Cross  => "X",
-            Self::Nought => "O",
-        }
+impl Tactics {
+    pub fn is_empty(&self) -> bool {
+        self.wins.is_empty() && self.blocks.is_empty() && self.forks.is_empty()
     }
+}
 
-    pub fn opposite(&self) -> Option<Tile> {
-        match &self {
-            Self::Cross => Some(Self::Nought),
-            Self::Nought => Some(Self::Cross),
-            Self::Empty => None,
-        }
-    }
+#[derive(Clone, PartialEq, Debug)]
+pub struct Board<T = Tile> {
+    /// Row by row, the tile at `(row, col)` is at `row * length + col`.
+    tiles: Vec<T>,
+    length: usize,
+    win_row_length: usize,
+    shape: Shape,
+    /// Under decay, a side's oldest mark goes once it has more than this many on the board.
+    decay: Option<usize>,
+    /// Where the marks are stored in the order they were played, oldest first, kept for decay.
+    placed: Vec<usize>,
+    /// In Morris each side has this many pieces to place, and moves them once they are all on
+    /// the board.
+    pieces: Option<usize>,
+    /// Rows score a point each instead of ending the game, which goes on until the board is
+    /// full.
+    scoring: bool,
 }
 
+/// How a game scored by rows stands, with the points each side has so far.
 #[derive(Copy, Clone, PartialEq, Debug)]
-pub enum BoardStatus {
-    Winner(Tile),
-    Tie,
-    Continue,
+pub struct Scored {
+    pub status: BoardStatus,
+    /// The rows completed by crosses and by noughts.
+    pub points: [usize; 2],
 }
-
-pub struct Board {
-    tiles: Vec<Vec<Tile>>,
-    length: usize,
-    win_row_length: usize,
+impl Scored {
+    pub fn of(&self, side: Tile) -> usize {
+        match side {
+            Tile::Cross  => self.points[0],
+            Tile::Nought => self.points[1],
+            _ => 0,
+        }
+    }
 }
+
+/// Rendering and the search only know crosses and noughts, the rest works for any `Piece`.
 impl Board {
     pub fn new(length: usize, win_row_length: usize) -> Self {
-        Self {
-            tiles: vec![vec![Tile::Empty; length]; length],
-            length: length,
-            win_row_length: win_row_length,
-        }
+        Self::empty(length, win_row_length)
+    }
+
+    pub fn from_notation(notation: &str, win_row_length: usize) -> Result<Self> {
+        Self::parse_notation(notation, win_row_length)
+    }
+
+    pub fn render(&self, theme: &Theme) -> String {
+        self.render_highlighted(theme, &[])
     }
 
-    pub fn print(&self) {
-        const HORIZONTAL: char = '=';
-        const VERTICAL: char   = '|';
+    /// Renders the board in the colors of the theme's palette, with the `highlight` tiles
+    /// highlighted, e.g. the winning line.
+    pub fn render_highlighted(&self, theme: &Theme, highlight: &[(usize, usize)]) -> String {
+        self.render_marked(theme, highlight, &[])
+    }
+
+    /// Renders the board with the `highlight` tiles highlighted and each of the `marks` drawn
+    /// on its empty tile.
+    pub fn render_marked(&self, theme: &Theme, highlight: &[(usize, usize)], marks: &[((usize, usize), char)]) -> String {
+        if self.shape == Shape::Hex {
+            return render::hex(self, theme, highlight, marks);
+        }
+        let layout = theme.layout.for_length(self.length);
+        if layout != Layout::Grid {
+            return render::condensed(self, theme, layout, highlight, marks);
+        }
+        let (width, _) = theme.tile_size();
+        let mut out = String::new();
 
-        for row in &self.tiles {
+        for (r, row) in self.rows().enumerate() {
             for _ in row {
-                print!("{}{}", HORIZONTAL, HORIZONTAL);
+                out.extend(std::iter::repeat_n(theme.horizontal, width + 1));
+            }
+            out.push(theme.horizontal);
+            out.push('\n');
+
+            let tiles: Vec<Vec<String>> = row.iter().enumerate().map(|(c, t)| {
+                let glyph = match marks.iter().find(|(tile, _)| *tile == (r, c)) {
+                    Some(&(_, mark)) if *t == Tile::Empty => mark,
+                    _ => theme.glyph(*t),
+                };
+                theme.tile_rows(glyph, *t).iter()
+                    .map(|line| theme.palette.paint(line, *t, highlight.contains(&(r, c)), theme.colors))
+                    .collect()
+            }).collect();
+            for line in 0..tiles.first().map_or(0, Vec::len) {
+                for tile in &tiles {
+                    out.push(theme.vertical);
+                    out.push_str(&tile[line]);
+                }
+                out.push(theme.vertical);
+                out.push('\n');
             }
-            println!("{}", HORIZONTAL);
+        }
 
-            for t in row {
-                print!("{}{}", VERTICAL, t.char());
+        out
+    }
+
+    pub fn to_svg(&self, theme: &Theme) -> String {
+        self.to_svg_highlighted(theme, &[])
+    }
+
+    /// The board as a standalone SVG image, with the `highlight` tiles shaded, e.g. the last move or
+    /// the winning line. Marks are drawn as text in the theme's glyphs.
+    pub fn to_svg_highlighted(&self, theme: &Theme, highlight: &[(usize, usize)]) -> String {
+        const CELL: usize = 60;
+        const MARGIN: usize = 10;
+        let side = self.length * CELL + 2 * MARGIN;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n\
+             <rect width=\"{0}\" height=\"{0}\" fill=\"white\"/>\n",
+            side,
+        );
+        for &(row, col) in highlight {
+            svg.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                MARGIN + col * CELL, MARGIN + row * CELL, CELL, CELL, theme.palette.css_highlight));
+        }
+        for i in 1..self.length {
+            let at = MARGIN + i * CELL;
+            svg.push_str(&format!("<line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"black\" stroke-width=\"2\"/>\n", at, MARGIN, side - MARGIN));
+            svg.push_str(&format!("<line x1=\"{1}\" y1=\"{0}\" x2=\"{2}\" y2=\"{0}\" stroke=\"black\" stroke-width=\"2\"/>\n", at, MARGIN, side - MARGIN));
+        }
+        for (i, &tile) in self.tiles.iter().enumerate() {
+            if tile == Tile::Empty {
+                continue;
             }
-            println!("{}", VERTICAL);
+            let (row, col) = self.position(i);
+            let glyph = match theme.glyph(tile) {
+                '&' => "&amp;".to_string(),
+                '<' => "&lt;".to_string(),
+                glyph => glyph.to_string(),
+            };
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\">{}</text>\n",
+                MARGIN + col * CELL + CELL / 2, MARGIN + row * CELL + CELL / 2, CELL * 2 / 3, theme.palette.css_color(tile), glyph,
+            ));
         }
+        svg.push_str("</svg>\n");
+        svg
+    }
 
-        println!();
+    /// The tiles nobody can play on.
+    pub fn blocked_tiles(&self) -> Vec<(usize, usize)> {
+        (0..self.tiles.len())
+            .filter(|&i| self.tiles[i] == Tile::Blocked)
+            .map(|i| self.position(i))
+            .collect()
     }
 
-    fn get<T: TryInto<usize>>(&self, row: T, col: T) -> Option<Tile> {
-        let tile = self.tiles
-            .get(row.try_into().ok()?)?
-            .get(col.try_into().ok()?)?;
+    pub fn random_move(&self) -> Option<(usize, usize)> {
+        use rand::seq::SliceRandom;
 
-        Some(*tile)
+        self.empty_tiles()
+            .choose(&mut rand::thread_rng())
+            .copied()
     }
 
-    pub fn set(&mut self, tile: Tile, row: usize, col: usize) -> Result<(), &'static str> {
-        let slot = self.tiles
-                .get_mut(row).ok_or("Row index out of bounds.")?
-                .get_mut(col).ok_or("Column index out of bounds.")?;
+    /// The status with the points of both sides in a game scored by rows, `None` otherwise.
+    pub fn scored(&self) -> Option<Scored> {
+        self.scoring.then(|| Scored { status: self.board_status(), points: [Tile::Cross, Tile::Nought].map(|side| self.points(side)) })
+    }
 
-        if *slot != Tile::Empty {
-            return Err("Already occupied tile.");
+    /// The best move for `side`, found on a copy so the board can be shared while it's searched.
+    pub fn best_move(&self, side: Tile) -> Option<Move> {
+        if self.decay.is_some() || self.pieces.is_some() || self.scoring {
+            return variant::horizon_move(self, side);
         }
+        best(self.move_values(side)).map(|(row, col)| Move { tile: side, row, col, from: None })
+    }
 
-        *slot = tile;
-        Ok(())
+    /// Plays `m` as `Game::play` does, without checking whose turn or which phase it is.
+    pub fn play(&mut self, m: &Move) -> Result<()> {
+        let Some(from) = m.from else { return self.set(m.tile, m.row, m.col) };
+        if self.get(from.0, from.1) != Some(m.tile) {
+            return Err(Error::NotYourPiece);
+        }
+        if !self.neighbours(from).contains(&(m.row, m.col)) {
+            return Err(Error::NotAdjacent);
+        }
+        self.clear(from.0, from.1);
+        self.set(m.tile, m.row, m.col).inspect_err(|_| {
+            let _ = self.set(m.tile, from.0, from.1);
+        })
     }
 
-    pub fn board_status(&self) -> BoardStatus {
-        for row in 0..self.length {
-            for col in 0..self.length {
-                let lines = [
-                    get_line(self, (row, col), ( 1,  0)),
-                    get_line(self, (row, col), (-1,  0)),
-                    get_line(self, (row, col), ( 0,  1)),
-                    get_line(self, (row, col), ( 0, -1)),
-
-                    get_line(self, (row, col), ( 1,  1)),
-                    get_line(self, (row, col), ( 1, -1)),
-                    get_line(self, (row, col), (-1,  1)),
-                    get_line(self, (row, col), (-1, -1)),
-                ];
-
-                for l in lines {
-                    if l.iter().all(|t| *t == Some(Tile::Cross )) {
-                        return BoardStatus::Winner(Tile::Cross);
-                    }
-                    if l.iter().all(|t| *t == Some(Tile::Nought)) {
-                        return BoardStatus::Winner(Tile::Nought);
-                    }
-                }
-            }
+    /// Whether a mark of `side` on `(row, col)` would complete a row.
+    pub fn completes_row(&self, side: Tile, row: usize, col: usize) -> bool {
+        let (row, col) = (row as i64, col as i64);
+        self.shape.directions().iter().any(|&(row_step, col_step)| {
+            let run = |sign: i64| (1..)
+                .take_while(|&i| self.get(row + sign * i * i64::from(row_step), col + sign * i * i64::from(col_step)) == Some(side))
+                .count();
+            1 + run(1) + run(-1) >= self.win_row_length
+        })
+    }
+
+    /// Where `side` to move wins, has to block and can fork.
+    pub fn tactics(&self, side: Tile) -> Tactics {
+        let Some(other) = side.opposite() else { return Tactics::default() };
+        let wins_for = |board: &Board, side| -> Vec<(usize, usize)> {
+            board.empty_tiles().into_iter().filter(|&(row, col)| board.completes_row(side, row, col)).collect()
+        };
+
+        let wins = wins_for(self, side);
+        let forks = self.empty_tiles()
+            .into_iter()
+            .filter(|tile| !wins.contains(tile))
+            .filter(|&(row, col)| {
+                let mut board = self.clone();
+                let _ = board.set(side, row, col);
+                wins_for(&board, side).len() >= 2
+            })
+            .collect();
+        Tactics { wins, blocks: wins_for(self, other), forks }
+    }
+
+    /// The minimax value of every empty tile for `side`, 1 is a forced win and -1 a forced loss.
+    pub fn move_values(&self, side: Tile) -> Vec<((usize, usize), i8)> {
+        // Games under decay and in Morris have no end to search to, and the end of a game
+        // scored by rows is too far off on the boards it is played on.
+        if self.decay.is_some() || self.pieces.is_some() || self.scoring {
+            return variant::horizon_values(self, side);
+        }
+        // The usual board is searched in an array, away from the heap.
+        match FixedBoard::<3>::from_grid(self) {
+            Some(mut fixed) => move_values(&mut fixed, side),
+            None => move_values(&mut self.clone(), side),
         }
+    }
 
-        let is_tie = !self.tiles
-            .iter()
-            .flatten()
-            .any(|tile| *tile == Tile::Empty);
-        
-        if is_tie {
-            return BoardStatus::Tie;
+    /// The number of positions `depth` moves from here with `side` to move, see `search::perft`.
+    pub fn perft(&self, side: Tile, depth: u32) -> u64 {
+        match FixedBoard::<3>::from_grid(self) {
+            Some(mut fixed) => search::perft(&mut fixed, side, depth),
+            None => search::perft(&mut self.clone(), side, depth),
         }
+    }
+}
 
-        return BoardStatus::Continue;
-        
-        fn get_line(
-            self_board: &Board,
-            (start_row, start_col): (usize, usize),
-            (row_change, col_change): (i32, i32),
-        ) -> Vec<Option<Tile>>
-        {
-            let length = self_board.win_row_length;
-            (0..length).map(|i| {
-                let row = start_row as i32 + i as i32 * row_change;
-                let col = start_col as i32 + i as i32 * col_change;
-                self_board.get(row, col)
-            }).collect()
+impl<T: Piece> Board<T> {
+    /// An empty board of any piece, `Board::new` makes one of `Tile`s.
+    pub fn empty(length: usize, win_row_length: usize) -> Self {
+        Self {
+            tiles: vec![T::EMPTY; length * length],
+            length,
+            win_row_length,
+            shape: Shape::Square,
+            decay: None,
+            placed: Vec::new(),
+            pieces: None,
+            scoring: false,
         }
     }
 
-    pub fn make_random_move(&mut self, side: Tile) {
-        use rand::seq::SliceRandom;
+    /// A copy of any other board, e.g. a `FixedBoard`.
+    pub fn from_grid(grid: &impl Grid<T>) -> Self {
+        let length = grid.length();
+        let tiles = (0..length * length).map(|i| grid.tile(i / length, i % length)).collect();
+        Self { tiles, length, win_row_length: grid.win_row_length(), shape: grid.shape(), decay: None, placed: Vec::new(), pieces: None, scoring: false }
+    }
 
-        let mut empty_tiles: Vec<&mut Tile> = self.tiles
-            .iter_mut()
-            .flatten()
-            .filter(|t| **t == Tile::Empty)
-            .collect();
+    /// The same tiles on a board of `shape`.
+    pub fn with_shape(self, shape: Shape) -> Self {
+        Self { shape, ..self }
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    /// The same tiles with marks decaying after `decay` of a side, or never with `None`. The marks
+    /// already on the board count as played row by row, so the first of them decays first.
+    pub fn with_decay(self, decay: Option<usize>) -> Self {
+        let placed = match decay {
+            Some(_) => (0..self.tiles.len()).filter(|&i| self.tiles[i].is_player()).collect(),
+            None => Vec::new(),
+        };
+        Self { decay, placed, ..self }
+    }
+
+    pub fn decay(&self) -> Option<usize> {
+        self.decay
+    }
+
+    /// The same tiles with `pieces` for each side to place before they move, or no limit with
+    /// `None`.
+    pub fn with_pieces(self, pieces: Option<usize>) -> Self {
+        Self { pieces, ..self }
+    }
+
+    pub fn pieces(&self) -> Option<usize> {
+        self.pieces
+    }
+
+    /// The same tiles with rows scoring points until the board is full, or ending the game.
+    pub fn with_scoring(self, scoring: bool) -> Self {
+        Self { scoring, ..self }
+    }
 
-        **empty_tiles
-            .choose_mut(&mut rand::thread_rng())
-            .unwrap()
-            = side;
-    }
-    
-    pub fn make_perfect_move(&mut self, side: Tile) {
-        let move_at = (0..self.length).cartesian_product(0..self.length)
-            .filter(|(row, col)| self.get(*row, *col).unwrap() == Tile::Empty)
-            .collect::<Vec<(usize, usize)>>()
-            .iter()
-            .max_by(|pos1, pos2| {
-                self.value_of_move(side, pos1.0, pos1.1)
-                    .cmp(&self.value_of_move(side, pos2.0, pos2.1))
+    pub fn scoring(&self) -> bool {
+        self.scoring
+    }
+
+    /// The rows `side` has completed, what it scores when rows score.
+    pub fn points(&self, side: T) -> usize {
+        self.completed_rows().iter().filter(|row| self.tile(row[0].0, row[0].1) == side).count()
+    }
+
+    /// Whether `side` has placed all its pieces in Morris, and moves them from now on.
+    pub fn must_move(&self, side: T) -> bool {
+        self.pieces.is_some_and(|pieces| self.tiles.iter().filter(|&&t| t == side).count() >= pieces)
+    }
+
+    /// The tiles next to `(row, col)`, along the ways rows run on the board.
+    pub fn neighbours(&self, (row, col): (usize, usize)) -> Vec<(usize, usize)> {
+        self.shape.directions().iter()
+            .flat_map(|&(row_step, col_step)| [(row_step, col_step), (-row_step, -col_step)])
+            .filter_map(|(row_step, col_step)| {
+                let (row, col) = (row.checked_add_signed(row_step as isize)?, col.checked_add_signed(col_step as isize)?);
+                self.index(row, col).map(|_| (row, col))
             })
-            .unwrap()
-            .clone();
-
-        self.set(side, move_at.0, move_at.1).unwrap();
-    }
-
-    // //Private function where row and col always should be correct.
-    fn value_of_move(&mut self, side: Tile, move_row: usize, move_col: usize) -> i8 {
-        const WIN_VALUE: i8 = 1;
-        const DRAW_VALUE: i8 = 0;
-        const LOOSE_VALUE: i8 = -1;
-
-        assert_eq!(self.get(move_row, move_col).unwrap(), Tile::Empty);
-        self.set(side, move_row, move_col).unwrap();
-
-        let value = match self.board_status() {
-            BoardStatus::Winner(tile) => {
-                if tile == side {WIN_VALUE}
-                else {LOOSE_VALUE}
-            },
-            BoardStatus::Tie => DRAW_VALUE,
-            BoardStatus::Continue => {
-                let opponent_move = (0..self.length).cartesian_product(0..self.length)
-                    .filter(|(row, col)| self.get(*row, *col).unwrap() == Tile::Empty)
-                    .collect::<Vec<(usize, usize)>>()
-                    .iter()
-                    .map(|(row, col)| self.value_of_move(side.opposite().unwrap(), *row, *col))
-                    .max()
-                    .unwrap();
-                
-                -opponent_move
-            }
+            .collect()
+    }
+
+    /// The mark of `side` that goes when it plays next under decay, if it has that many marks.
+    pub fn next_to_decay(&self, side: T) -> Option<(usize, usize)> {
+        let mut own = self.placed.iter().filter(|&&i| self.tiles[i] == side);
+        match (self.decay, own.clone().count()) {
+            (Some(marks), count) if count >= marks => own.next().map(|&i| self.position(i)),
+            _ => None,
+        }
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn win_row_length(&self) -> usize {
+        self.win_row_length
+    }
+
+    /// Where the tile at `(row, col)` is stored, `None` off the board.
+    pub fn index(&self, row: usize, col: usize) -> Option<usize> {
+        (row < self.length && col < self.length).then_some(row * self.length + col)
+    }
+
+    /// The `(row, col)` of the tile stored at `index`, the inverse of `index`.
+    pub fn position(&self, index: usize) -> (usize, usize) {
+        (index / self.length, index % self.length)
+    }
+
+    /// Where `(row, col)` ends up when the board is turned or mirrored by `symmetry`, one of the
+    /// eight from 0 to 7: bit 4 swaps rows and columns, then bit 2 flips the rows and bit 1 the
+    /// columns. 0 leaves every tile where it is. A hex board only has the four that keep its
+    /// diagonal rows running down to the left, 0, 3, 4 and 7, and the others stand for those.
+    pub fn transform(&self, symmetry: u8, (row, col): (usize, usize)) -> (usize, usize) {
+        let symmetry = match self.shape {
+            Shape::Square => symmetry,
+            Shape::Hex    => (symmetry & 6) | ((symmetry >> 1) & 1),
         };
+        let last = self.length.saturating_sub(1);
+        let (row, col) = if symmetry & 4 != 0 { (col, row) } else { (row, col) };
+        let row = if symmetry & 2 != 0 { last - row } else { row };
+        let col = if symmetry & 1 != 0 { last - col } else { col };
+        (row, col)
+    }
+
+    /// The board turned or mirrored by `symmetry`, see `transform`.
+    pub fn transformed(&self, symmetry: u8) -> Self {
+        let mut tiles = self.tiles.clone();
+        for (i, &tile) in self.tiles.iter().enumerate() {
+            let (row, col) = self.transform(symmetry, self.position(i));
+            tiles[row * self.length + col] = tile;
+        }
+        let placed = self.placed.iter().map(|&i| {
+            let (row, col) = self.transform(symmetry, self.position(i));
+            row * self.length + col
+        }).collect();
+        Self { tiles, placed, ..self.clone() }
+    }
 
-        self.tiles[move_row][move_col] = Tile::Empty;
-        value
+    /// The board in all eight ways it can be turned and mirrored, in the order of `transform`,
+    /// the board itself first. Positions that are symmetric themselves come up more than once.
+    pub fn symmetries(&self) -> [Self; 8] {
+        std::array::from_fn(|symmetry| self.transformed(symmetry as u8))
+    }
+
+    /// The one of the `symmetries` with the notation first in order, the same for all eight, so
+    /// positions that only differ by turning the board can be told apart from different ones.
+    pub fn canonical_form(&self) -> Self {
+        self.symmetries().into_iter().min_by_key(Self::notation).unwrap_or_else(|| self.clone())
+    }
+
+    fn rows(&self) -> std::slice::Chunks<'_, T> {
+        // `max` keeps a board of size 0 from panicking, it has no rows either way.
+        self.tiles.chunks(self.length.max(1))
+    }
+
+    /// Compact position notation, rows separated by '/' with '.' for empty tiles, e.g. "X.O/.X./...".
+    pub fn notation(&self) -> String {
+        self.rows()
+            .map(|row| row.iter().map(|t| if *t == T::EMPTY { "." } else { t.char() }).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+
+    /// Reads `notation`, `Board::from_notation` reads boards of `Tile`s.
+    pub fn parse_notation(notation: &str, win_row_length: usize) -> Result<Self> {
+        let rows = notation.trim()
+            .split('/')
+            .map(|row| row.chars().map(T::from_char).collect::<Option<Vec<T>>>())
+            .collect::<Option<Vec<Vec<T>>>>()
+            .ok_or(Error::InvalidNotation("Positions only contain 'X', 'O' and '.'."))?;
+
+        let length = rows.len();
+        if rows.iter().any(|row| row.len() != length) {
+            return Err(Error::InvalidNotation("Positions must be square."));
+        }
+        if win_row_length == 0 || win_row_length > length {
+            return Err(Error::InvalidNotation("The win length must fit on the board."));
+        }
+
+        Ok(Self { tiles: rows.concat(), length, win_row_length, shape: Shape::Square, decay: None, placed: Vec::new(), pieces: None, scoring: false })
+    }
+
+    pub fn get<I: TryInto<usize>>(&self, row: I, col: I) -> Option<T> {
+        let index = self.index(row.try_into().ok()?, col.try_into().ok()?)?;
+        Some(self.tiles[index])
+    }
+
+    pub fn set(&mut self, tile: T, row: usize, col: usize) -> Result<()> {
+        if row >= self.length {
+            return Err(Error::RowOutOfBounds);
+        }
+        let slot = self.index(row, col)
+            .map(|i| &mut self.tiles[i])
+            .ok_or(Error::ColumnOutOfBounds)?;
+
+        if *slot != T::EMPTY {
+            return Err(if slot.is_player() { Error::Occupied } else { Error::Blocked });
+        }
+
+        *slot = tile;
+        if let Some(marks) = self.decay.filter(|_| tile.is_player()) {
+            let index = row * self.length + col;
+            self.placed.push(index);
+            let own: Vec<usize> = self.placed.iter().copied().filter(|&i| self.tiles[i] == tile).collect();
+            if own.len() > marks {
+                self.tiles[own[0]] = T::EMPTY;
+                self.placed.retain(|&i| i != own[0]);
+            }
+        }
+        Ok(())
+    }
+
+    /// As `Grid::board_status`, but under decay and in Morris rows that are broken now open up
+    /// again as marks go or move, so the board never ties.
+    pub fn board_status(&self) -> BoardStatus<T> {
+        if self.scoring {
+            return self.scored_status();
+        }
+        match (self.decay.is_some() || self.pieces.is_some(), self.winning_line()) {
+            (true, Some(line)) => BoardStatus::Winner(self.tile(line[0].0, line[0].1)),
+            (true, None) => BoardStatus::Continue,
+            (false, _) => Grid::board_status(self),
+        }
+    }
+
+    /// Once the board is full, the side that completed the most rows wins.
+    fn scored_status(&self) -> BoardStatus<T> {
+        if self.tiles.contains(&T::EMPTY) {
+            return BoardStatus::Continue;
+        }
+        let mut points: Vec<(T, usize)> = Vec::new();
+        for row in self.completed_rows() {
+            let side = self.tile(row[0].0, row[0].1);
+            match points.iter_mut().find(|(s, _)| *s == side) {
+                Some((_, count)) => *count += 1,
+                None => points.push((side, 1)),
+            }
+        }
+        points.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        match points.as_slice() {
+            [(side, _)] => BoardStatus::Winner(*side),
+            [(side, most), (_, next), ..] if most > next => BoardStatus::Winner(*side),
+            _ => BoardStatus::Tie,
+        }
+    }
+
+    /// The tiles of a completed row of one side, `None` while nobody has won.
+    pub fn winning_line(&self) -> Option<Vec<(usize, usize)>> {
+        Grid::winning_line(self)
+    }
+
+    /// Empties a tile again, used to take back moves.
+    pub fn clear(&mut self, row: usize, col: usize) {
+        self.tiles[row * self.length + col] = T::EMPTY;
+        self.placed.retain(|&i| i != row * self.length + col);
+    }
+
+    pub fn empty_tiles(&self) -> Vec<(usize, usize)> {
+        Grid::empty_tiles(self)
     }
-    // fn foo(cord: (usize, usize), b: &mut Board) {
-    //     b.value_of_move(side, move_row, move_col)
-    // }
+}
+
+impl<T: Piece> Grid<T> for Board<T> {
+    fn length(&self) -> usize {
+        self.length
+    }
+
+    fn win_row_length(&self) -> usize {
+        self.win_row_length
+    }
+
+    fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    fn tile(&self, row: usize, col: usize) -> T {
+        self.tiles[row * self.length + col]
+    }
+
+    fn put(&mut self, tile: T, row: usize, col: usize) {
+        self.tiles[row * self.length + col] = tile;
+    }
+}
+
+/// The minimax value of every empty tile for `side`, as `search::move_values`, and logs the
+/// search statistics.
+pub fn move_values(board: &mut impl Grid<Tile>, side: Tile) -> Vec<((usize, usize), i8)> {
+    let start = std::time::Instant::now();
+    let (values, positions) = search::move_values(board, side);
+
+    log::debug!("searched {} positions for {:?} in {:?}", positions, side, start.elapsed());
+    values
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Tile::*, BoardStatus::*, Board};
+    use super::{move_values, Tile::*, BoardStatus::*, Board, Error, FixedBoard, Layout, Move, Piece, Shape, Tactics, Theme};
+    use crate::render::Palette;
 
     #[test]
     fn board_status() {
         let mut b = Board::new(3, 3);
 
-        b.tiles = vec![
+        b.tiles = [
             vec![Cross, Nought, Cross],
             vec![Nought, Cross, Empty],
             vec![Nought, Empty, Cross],
-        ];
+        ].concat();
         assert_eq!(b.board_status(), Winner(Cross));
 
-        b.tiles = vec![
+        b.tiles = [
             vec![Cross, Nought, Cross],
             vec![Cross, Nought, Empty],
             vec![Nought, Cross, Cross],
-        ];
+        ].concat();
         assert_eq!(b.board_status(), Continue);
 
-        b.tiles = vec![
+        b.tiles = [
             vec![ Cross, Nought,  Cross],
             vec![Nought, Nought, Nought],
             vec![ Cross,  Cross,  Empty],
-        ];
+        ].concat();
         assert_eq!(b.board_status(), Winner(Nought));
 
-        b.tiles = vec![
+        b.tiles = [
             vec![ Cross, Nought,  Cross],
             vec![Nought,  Cross, Nought],
             vec![Nought,  Cross, Nought],
-        ];
+        ].concat();
         assert_eq!(b.board_status(), Tie);
+        assert_eq!(Board::from_notation("XOX/XOO/OX.", 3).unwrap().board_status(), Tie);
+        assert_eq!(Board::from_notation("XOX/XOO/.X.", 3).unwrap().board_status(), Continue);
 
 
         let mut b2 = Board::new(4, 2);
-        b2.tiles = vec![
+        b2.tiles = [
             vec![ Cross, Empty,  Cross, Empty],
             vec![Nought,  Empty, Nought, Empty],
             vec![ Empty,  Empty,  Empty, Cross],
             vec![ Nought,  Cross, Empty, Nought],
-        ];
+        ].concat();
         assert_eq!(b2.board_status(), Continue);
 
-        b2.tiles = vec![
+        b2.tiles = [
             vec![ Cross, Empty,  Cross, Empty],
             vec![Nought,  Empty, Nought, Empty],
             vec![ Cross,  Empty,  Empty, Empty],
             vec![ Nought,  Cross, Empty, Cross],
-        ];
+        ].concat();
         assert_eq!(b2.board_status(), Winner(Cross));
+        assert_eq!(b2.winning_line(), Some(vec![(2, 0), (3, 1)]));
+        assert_eq!(Board::new(3, 3).winning_line(), None);
+        assert_eq!(b2.index(2, 1), Some(9));
+        assert_eq!(b2.position(9), (2, 1));
+        assert_eq!(b2.index(1, 4), None);
+    }
+
+    #[test]
+    fn other_pieces() {
+        // Three players, as in a multiplayer variant.
+        #[derive(Copy, Clone, PartialEq, Debug)]
+        enum Mark { Empty, A, B, C }
+        impl Piece for Mark {
+            const EMPTY: Self = Mark::Empty;
+
+            fn char(&self) -> &'static str {
+                [" ", "A", "B", "C"][*self as usize]
+            }
+
+            fn from_char(c: char) -> Option<Self> {
+                match c {
+                    ' ' | '.' => Some(Mark::Empty),
+                    'A' => Some(Mark::A),
+                    'B' => Some(Mark::B),
+                    'C' => Some(Mark::C),
+                    _ => None,
+                }
+            }
+        }
+
+        let mut b = Board::<Mark>::empty(4, 3);
+        for (mark, row, col) in [(Mark::A, 0, 0), (Mark::B, 0, 1), (Mark::C, 1, 1), (Mark::C, 2, 1)] {
+            b.set(mark, row, col).unwrap();
+        }
+        assert_eq!(b.board_status(), Continue);
+        b.set(Mark::C, 3, 1).unwrap();
+        assert_eq!(b.board_status(), Winner(Mark::C));
+        assert_eq!(b.notation(), "AB../.C../.C../.C..");
+        assert_eq!(Board::parse_notation(&b.notation(), 3), Ok(b));
+    }
+
+    #[test]
+    fn fixed_board() {
+        let board = Board::from_notation("XO./.X./O..", 3).unwrap();
+        let fixed = FixedBoard::<3>::from_grid(&board).unwrap();
+        assert_eq!(Board::from_grid(&fixed), board);
+        assert_eq!(board.move_values(Nought), move_values(&mut board.clone(), Nought));
+        assert_eq!(board.best_move(Cross), Some(Move { tile: Cross, row: 2, col: 2, from: None }));
+    }
+
+    #[test]
+    fn notation() {
+        let mut b = Board::new(3, 3);
+        b.set(Cross, 0, 0).unwrap();
+        b.set(Nought, 1, 2).unwrap();
+
+        assert_eq!(b.notation(), "X../..O/...");
+        assert_eq!(Board::from_notation("X../..O/...", 3), Ok(b));
+        assert!(Board::from_notation("X../..O", 3).is_err());
+        assert!(Board::from_notation("X../..Q/...", 3).is_err());
+    }
+
+    #[test]
+    fn symmetries() {
+        let board = Board::from_notation("XO./.../...", 3).unwrap();
+        let notations: Vec<String> = board.symmetries().iter().map(Board::notation).collect();
+        assert_eq!(notations, ["XO./.../...", ".OX/.../...", ".../.../XO.", ".../.../.OX", "X../O../...", "..X/..O/...", ".../O../X..", ".../..O/..X"]);
+        assert_eq!(board.transform(3, (0, 1)), (2, 1));
+        for symmetric in board.symmetries() {
+            assert_eq!(symmetric.canonical_form().notation(), ".../.../.OX");
+        }
+        assert_eq!(Board::new(3, 3).canonical_form(), Board::new(3, 3));
+    }
+
+    #[test]
+    fn perft() {
+        // The positions after each number of moves in every 3x3 game, ties found early included.
+        let counts: Vec<u64> = (0..=9).map(|depth| Board::new(3, 3).perft(Cross, depth)).collect();
+        assert_eq!(counts, [1, 9, 72, 504, 3024, 15120, 54720, 148176, 200448, 127872]);
+        assert_eq!(Board::new(4, 3).perft(Cross, 2), 240);
+        // Nought wins on c2 at once, the other four moves have four replies each.
+        assert_eq!(Board::from_notation("XX./OO./...", 3).unwrap().perft(Nought, 2), 4 * 4);
+    }
+
+    #[test]
+    fn tactics() {
+        let board = Board::from_notation("XX./O../...", 3).unwrap();
+        assert_eq!(board.tactics(Nought), Tactics { wins: vec![], blocks: vec![(0, 2)], forks: vec![] });
+        assert_eq!(board.tactics(Cross), Tactics { wins: vec![(0, 2)], blocks: vec![], forks: vec![(1, 1), (2, 1), (2, 2)] });
+
+        // Crosses in opposite corners fork on the third one.
+        let board = Board::from_notation("X../.O./..X", 3).unwrap();
+        assert_eq!(board.tactics(Cross).forks, [(0, 2), (2, 0)]);
+        assert!(board.tactics(Nought).is_empty());
+        let board = Board::from_notation("XXX../...../...../...../.....", 4).unwrap();
+        assert!(board.completes_row(Cross, 0, 3) && !board.completes_row(Cross, 0, 4));
+    }
+
+    #[test]
+    fn render() {
+        let mut b = Board::new(2, 2);
+        b.set(Cross, 0, 1).unwrap();
+        b.set(Nought, 1, 0).unwrap();
+
+        assert_eq!(b.render(&Theme::ASCII), "=====\n| |X|\n=====\n|O| |\n");
+        assert_eq!(b.render_highlighted(&Theme::ASCII, &[(0, 1)]), "=====\n| |\x1b[7mX\x1b[0m|\n=====\n|O| |\n");
+
+        let svg = b.to_svg_highlighted(&Theme::ASCII, &[(0, 1)]);
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<line ").count(), 2);
+        assert!(svg.contains("<rect x=\"70\" y=\"10\" width=\"60\" height=\"60\" fill=\"#ffe680\"/>"));
+        assert!(svg.contains(">X</text>") && svg.contains(">O</text>"));
+
+        let colorblind = Theme { palette: Palette::COLORBLIND, ..Theme::ASCII };
+        assert_eq!(b.render_highlighted(&colorblind, &[(0, 1)]), "=====\n| |\x1b[7mX\x1b[0m|\n=====\n|\x1b[38;5;178mO\x1b[0m| |\n");
+        let svg = b.to_svg(&colorblind);
+        assert!(svg.contains("fill=\"#0072b2\">X</text>") && svg.contains("fill=\"#e69f00\">O</text>"));
+        assert_eq!(Palette::named("high-contrast"), Some(Palette::HIGH_CONTRAST));
+        assert_eq!(Palette::named("rainbow"), None);
+        let boxed = Theme { palette: Palette::COLORBLIND.without_escapes(), ..Theme::BOX };
+        assert_eq!(b.render_highlighted(&boxed, &[(0, 1)]), "─────\n│ │X│\n─────\n│O│ │\n");
+        assert!(!boxed.is_ascii() && Theme::named("ascii").is_some_and(|theme| theme.is_ascii()));
+        // Emoji take two columns, and the grid and the narrow marks are widened to match.
+        let emoji = Theme { palette: Palette::PLAIN, ..Theme::EMOJI };
+        assert_eq!(emoji.tile_size(), (2, 1));
+        assert_eq!(b.render_marked(&emoji, &[], &[((0, 0), 'w')]), "───────\n│w │❌│\n───────\n│⭕│⬜│\n");
+
+        let large = b.render(&Theme { scale: 2, ..Theme::ASCII });
+        assert_eq!(large, "=============\n\
+                           |     |X   X|\n\
+                           |     |  X  |\n\
+                           |     |X   X|\n\
+                           =============\n\
+                           | OOO |     |\n\
+                           |O   O|     |\n\
+                           | OOO |     |\n");
+    }
+
+    #[test]
+    fn render_condensed() {
+        let mut b = Board::new(16, 5);
+        b.set(Cross, 0, 0).unwrap();
+        b.set(Nought, 1, 0).unwrap();
+        b.set(Cross, 5, 15).unwrap();
+        let compact = b.render_marked(&Theme::ASCII, &[], &[((0, 1), 'w')]);
+        let lines: Vec<&str> = compact.lines().collect();
+        assert_eq!(lines.len(), 17);
+        assert_eq!(lines[0], "   0         5         10        15");
+        assert_eq!(lines[1], " 0 X w . . . . . . . . . . . . . .");
+        assert_eq!(lines[2], "   O . . . . . . . . . . . . . . .");
+        assert_eq!(lines[6], " 5 . . . . . . . . . . . . . . . X");
+        // The grid is still there when asked for.
+        let grid = Theme { layout: Layout::Grid, ..Theme::ASCII };
+        assert_eq!(b.render(&grid).lines().count(), 32);
+
+        let braille = b.render(&Theme { layout: Layout::Braille, ..Theme::ASCII });
+        // Empty tiles are blank braille, which keeps the lines as long as the board.
+        assert_eq!(braille.lines().nth(1), Some(format!(" 0 ⣵{}", "\u{2800}".repeat(15)).as_str()));
+        assert_eq!(braille.lines().count(), 9);
+        let half = b.render(&Theme { layout: Layout::HalfBlock, ..Theme::ASCII });
+        assert!(half.lines().nth(1).is_some_and(|line| line.starts_with(" 0 \x1b[38;5;25;48;5;178m▀\x1b[0m")), "{}", half);
+    }
+
+    #[test]
+    fn hex() {
+        let mut b = Board::new(3, 3).with_shape(Shape::Hex);
+        for (tile, row, col) in [(Cross, 0, 0), (Nought, 0, 1), (Cross, 1, 1)] {
+            b.set(tile, row, col).unwrap();
+        }
+        // The square diagonal doesn't run on hexagons, the other one does.
+        assert!(!b.completes_row(Cross, 2, 2));
+        b.set(Cross, 2, 2).unwrap();
+        assert_eq!(b.board_status(), Continue);
+        b.set(Cross, 0, 2).unwrap();
+        assert!(b.completes_row(Cross, 2, 0));
+        b.set(Cross, 2, 0).unwrap();
+        assert_eq!(b.winning_line(), Some(vec![(0, 2), (1, 1), (2, 0)]));
+        assert_eq!(b.render(&Theme::ASCII), "  0 1 2\n0 X O X\n1  . X .\n2   X . X\n");
+        // Mirroring the rows alone would turn the rows down to the left the wrong way.
+        assert_eq!(b.transformed(2), b.transformed(3));
+    }
+
+    #[test]
+    fn blocked() {
+        let mut b = Board::from_notation("XX#/OO./...", 3).unwrap();
+        assert_eq!(b.set(Cross, 0, 2), Err(Error::Blocked));
+        assert_eq!(b.blocked_tiles(), vec![(0, 2)]);
+        let tactics = b.tactics(Cross);
+        assert!(tactics.wins.is_empty());
+        assert_eq!(tactics.blocks, vec![(1, 2)]);
+        assert_eq!(b.best_move(Cross).map(|m| (m.row, m.col)), Some((1, 2)));
+
+        // A row of blocked tiles wins nothing, and once every row is broken it's a tie.
+        assert_eq!(Board::from_notation("###/.../...", 3).unwrap().board_status(), Continue);
+        assert_eq!(Board::from_notation("#.#/.#./#.#", 3).unwrap().board_status(), Tie);
+        assert_eq!(b.render(&Theme::ASCII).lines().nth(1), Some("|X|X|#|"));
+    }
+
+    #[test]
+    fn scoring() {
+        let b = Board::from_notation("XXXX/O.O./..../....", 3).unwrap().with_scoring(true);
+        // A run of four is two rows of three, and neither ends the game.
+        assert_eq!(b.points(Cross), 2);
+        assert_eq!(b.board_status(), Continue);
+        assert_eq!(b.scored().map(|scored| scored.points), Some([2, 0]));
+        // The last tile makes the top row for X, for O it just draws.
+        let last = Board::from_notation("XX./OOX/XOO", 3).unwrap().with_scoring(true);
+        assert_eq!(last.move_values(Cross), vec![((0, 2), 1)]);
+        assert_eq!(last.move_values(Nought), vec![((0, 2), 0)]);
+
+        let full = |notation| Board::from_notation(notation, 3).unwrap().with_scoring(true).board_status();
+        assert_eq!(full("XXX/OOX/XOO"), Winner(Cross));
+        assert_eq!(full("XXX/OOO/XOX"), Tie);
+        assert_eq!(Board::from_notation("XXX/OOX/XOO", 3).unwrap().points(Nought), 0);
     }
 }
@@ -0,0 +1,652 @@
+use crate::completions::Shell;
+use crate::setup::{Handicap, Preset, MAX_SIZE};
+use std::path::PathBuf;
+use tick_tack_toe::render::{ColorDepth, Layout, Palette, Theme};
+use tick_tack_toe::variant::Variant;
+
+/// Command line options, parsed by hand to keep the dependency list short.
+#[derive(Default, Debug, PartialEq)]
+pub struct Options {
+    pub command: Command,
+    pub best_of: Option<u32>,
+    /// A file listing the variant of every game of a match, see `series`.
+    pub series: Option<PathBuf>,
+    /// Who moves first in the first game, later games of a match alternate.
+    pub first: First,
+    /// Rounds of a Swiss-system tournament, instead of round-robin.
+    pub swiss: Option<u32>,
+    /// What the first row and column is called in typed moves, 0 or 1.
+    pub origin: usize,
+    /// Players and board chosen up front instead of in the menu.
+    pub preset: Preset,
+    /// Play without printing the board or pausing, only the result.
+    pub headless: bool,
+    pub output: Output,
+    /// Where `simulate`, `analyze` and `positions` write their results, for `simulate` as JSON
+    /// for `.json` files and CSV otherwise.
+    pub out: Option<PathBuf>,
+    /// Where the final position of every game is drawn as SVG.
+    pub export_svg: Option<PathBuf>,
+    /// The same as a PNG image, only with the `image` feature.
+    pub export_png: Option<PathBuf>,
+    /// The whole game as an animated GIF, one frame per move, also only with `image`.
+    pub export_gif: Option<PathBuf>,
+    /// The final position with the moves, as Markdown for `.md` files and HTML otherwise.
+    pub export_text: Option<PathBuf>,
+    /// Milliseconds to pause after each move, instead of the default.
+    pub delay: Option<u64>,
+    /// Redraw the board at the top of the screen instead of printing it below the last one.
+    pub in_place: bool,
+    /// The characters boards are drawn with, ASCII when not given.
+    pub theme: Option<Theme>,
+    /// The colors boards are drawn in, plain when not given.
+    pub palette: Option<Palette>,
+    /// How many colors the terminal shows, instead of what it announces.
+    pub colors: Option<ColorDepth>,
+    /// How large boards are drawn, see `Theme::scale`.
+    pub scale: Option<usize>,
+    /// How boards are laid out, on a grid below 15x15 and compact from there when not given.
+    pub layout: Option<Layout>,
+    /// Humans move a cursor with the keys instead of typing moves.
+    pub cursor: bool,
+    /// Mark the tiles that win, must be blocked or fork before each human move.
+    pub teach: bool,
+    /// Speak the moves and the result, only with the `audio` feature.
+    pub speak: bool,
+    /// Have `solve` print the proof tree too.
+    pub proof: bool,
+    /// Have `positions` skip the ones that are turned or mirrored versions of another.
+    pub distinct: bool,
+    pub no_animation: bool,
+    /// Number of `-v` flags, each one makes logging more verbose.
+    pub verbosity: u8,
+}
+
+#[derive(Default, Debug, PartialEq)]
+pub enum Command {
+    #[default]
+    Play,
+    /// Show the statistics of one profile, or of all of them.
+    Stats(Option<String>),
+    /// Show the best rated players.
+    Leaderboard,
+    /// Play today's challenge.
+    Puzzle,
+    /// Show a game between two AIs with playback controls.
+    Watch,
+    /// Play this many games between two AIs and report the score.
+    Simulate(u32),
+    /// Play a tournament between these AI kinds.
+    Tournament(Vec<&'static str>),
+    /// Play a tournament between the strategies listed in this file.
+    Arena(PathBuf),
+    /// Show the game in this record file move by move.
+    Replay(PathBuf),
+    /// Evaluate every move of the game in this record file, or explore positions without one.
+    Analyze(Option<PathBuf>),
+    /// Host games for JSON-RPC clients over TCP on this address, e.g. `127.0.0.1:7878`, or
+    /// answer requests on stdin without one.
+    Serve(Option<String>),
+    /// Set up a position by hand to analyze or play on from.
+    Edit,
+    /// Count the positions this many moves from the empty board or the given position.
+    Perft(u32, Option<String>),
+    /// Check the searches against the retrograde tablebase of the --size board.
+    Verify,
+    /// List every position that can come up on the --size board.
+    Positions,
+    /// Solve the position in this notation.
+    Solve(String),
+    /// Rewrite the game record in the first file in the format of the second, SGF or not.
+    Convert(PathBuf, PathBuf),
+    /// Play as usual, with the moves of one side chosen by the `serve` server at this address.
+    ConnectEngine(String),
+    /// Play a board against each of these AI kinds at once.
+    Simul(Vec<&'static str>),
+    /// Print the completion script for this shell.
+    Completions(Shell),
+    /// Print this help, asked for with `-h` or `--help`.
+    Help(String),
+}
+
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+pub enum First {
+    #[default]
+    Cross,
+    Nought,
+    /// Decided by a coin flip, announced before the game.
+    CoinFlip,
+}
+
+/// How the result of a game is reported.
+#[derive(Default, Debug, PartialEq)]
+pub enum Output {
+    #[default]
+    Text,
+    /// A single `result=X moves=7 reason=line` line per game, implies `--headless`.
+    ResultOnly,
+}
+
+const DEFAULT_SIMULATIONS: u32 = 100;
+
+/// A subcommand: what it's called, what follows its name, what it does and the groups of options
+/// it takes, which are all its `--help` lists.
+pub struct Subcommand {
+    pub name: &'static str,
+    pub args: &'static str,
+    pub about: &'static str,
+    pub options: &'static [&'static [&'static str]],
+}
+
+const PLAYERS: &[&str] = &["--cross", "--nought", "--cross-name", "--nought-name", "--first"];
+const BOARD: &[&str] = &["--size", "--win-length", "--variant", "--handicap", "--obstacles"];
+const SIZE: &[&str] = &["--size", "--win-length"];
+const MATCH: &[&str] = &["--best-of", "--series"];
+const HUMANS: &[&str] = &["--cursor", "--teach"];
+const HEADLESS: &[&str] = &["--headless", "--output"];
+const EXPORT: &[&str] = &["--export-svg", "--export-png", "--export-gif", "--export-text"];
+const DISPLAY: &[&str] = &[
+    "--origin", "--delay", "--in-place", "--theme", "--palette", "--colors", "--scale", "--layout", "--speak", "--no-animation",
+];
+
+/// Every subcommand, the main ones first and `play` the one run without a name.
+pub const SUBCOMMANDS: &[Subcommand] = &[
+    Subcommand { name: "play", args: "", about: "Play a game or a match, the default without a command",
+        options: &[PLAYERS, BOARD, MATCH, HUMANS, HEADLESS, EXPORT, DISPLAY] },
+    Subcommand { name: "simulate", args: "[N]", about: "Play N games (default 100) between the --cross and --nought AIs",
+        options: &[PLAYERS, BOARD, &["--out"]] },
+    Subcommand { name: "tournament", args: "KIND...", about: "Play a round-robin tournament between AIs (random, perfect,
+                   minimax or montecarlo), any kind may enter many times",
+        options: &[SIZE, &["--swiss", "--arena"]] },
+    Subcommand { name: "solve", args: "POSITION", about: "Tell who wins a position such as X../.O./... and in how many
+                   moves, with the best line (and the whole proof with --proof)",
+        options: &[&["--win-length", "--proof", "--origin"]] },
+    Subcommand { name: "analyze", args: "[FILE]", about: "Evaluate every move of a game record and mark the mistakes,
+                   written to --out as Markdown for .md files, or without one try
+                   moves and lines from a position of the --size board",
+        options: &[SIZE, &["--out", "--origin"], DISPLAY] },
+    Subcommand { name: "serve", args: "[ADDR]", about: "Host many games at once for JSON-RPC clients over TCP on ADDR,
+                   or answer JSON-RPC requests on stdin without one",
+        options: &[] },
+    Subcommand { name: "replay", args: "FILE", about: "Show a saved or downloaded game record move by move",
+        options: &[EXPORT, DISPLAY] },
+    Subcommand { name: "puzzle", args: "", about: "Play today's challenge, the same for everyone",
+        options: &[HUMANS, DISPLAY] },
+    Subcommand { name: "watch", args: "", about: "Show a game between the --cross and --nought AIs move by move,
+                   --delay apart, with keys to pause, step and change the speed",
+        options: &[PLAYERS, BOARD, EXPORT, DISPLAY] },
+    Subcommand { name: "stats", args: "[NAME]", about: "Show the statistics of all player profiles, or of one",
+        options: &[] },
+    Subcommand { name: "leaderboard", args: "", about: "Show the best players by rating and win rate",
+        options: &[] },
+    Subcommand { name: "edit", args: "", about: "Set up a position on the --size board, then analyze or play it",
+        options: &[PLAYERS, SIZE, &["--variant"], HUMANS, DISPLAY] },
+    Subcommand { name: "convert", args: "FROM TO", about: "Convert a game record to or from SGF, by the .sgf file extension",
+        options: &[] },
+    Subcommand { name: "verify", args: "", about: "Check the searches against a tablebase of every position on
+                   the --size board (up to 3x3), and report where they disagree",
+        options: &[SIZE] },
+    Subcommand { name: "positions", args: "", about: "List every position that can come up on the --size board with
+                   the side to move, one per line, or to --out",
+        options: &[SIZE, &["--distinct", "--out"]] },
+    Subcommand { name: "perft", args: "DEPTH [POSITION]", about: "Count the positions DEPTH moves from the empty --size board or
+                   from POSITION, such as X../.O./..., to check move generation",
+        options: &[SIZE] },
+    Subcommand { name: "simul", args: "KIND...", about: "Play a board against each AI listed at once, moving on them in
+                   turn, as X unless --nought is human",
+        options: &[SIZE, &["--cross", "--nought"], DISPLAY] },
+    Subcommand { name: "connect-engine", args: "ADDR", about: "Play with the moves of one side chosen by the serve command at
+                   ADDR, O unless --nought is given and --cross isn't",
+        options: &[PLAYERS, BOARD, MATCH, HUMANS, HEADLESS, EXPORT, DISPLAY] },
+    Subcommand { name: "completions", args: "SHELL", about: "Print the completion script for bash, zsh, fish or powershell",
+        options: &[] },
+];
+
+/// How many of `SUBCOMMANDS` are the main ones.
+const MAIN_SUBCOMMANDS: usize = 9;
+
+/// Every option with its help, in the order the help lists them.
+pub const OPTIONS: &[(&[&str], &str)] = &[
+    (&["--cross"], "  --cross <KIND>   Who plays X: human, random, perfect, minimax or montecarlo"),
+    (&["--nought"], "  --nought <KIND>  Who plays O: human, random, perfect, minimax or montecarlo"),
+    (&["--cross-name", "--nought-name"], "  --cross-name <NAME>, --nought-name <NAME>
+                   What to call the players in prompts and results, instead of
+                   their profile names or the kind of AI"),
+    (&["--first"], "  --first <SIDE>   Who moves first: x (default), o or random for a coin flip"),
+    (&["--size"], "  --size <N>       Board size, from 1 to 26"),
+    (&["--win-length"], "  --win-length <N> Marks in a row needed to win"),
+    (&["--variant"], "  --variant <NAME> Rules to play by: standard (default), fog for fog of war,
+                   where each side only sees its own marks and the tiles next to them,
+                   hex for a board of hexagons, where rows run three ways, or
+                   decay[:N] where a side's oldest mark goes when it plays one
+                   more than N (default 3), so games never tie, or morris[:N]
+                   where each side has N pieces (default 3) and once they are
+                   all out moves one to a tile next to it, or scoring where
+                   every row is a point and the most points win once the board
+                   is full"),
+    (&["--handicap"], "  --handicap <SIDE[:MARKS]>
+                   Give SIDE (x or o) marks on the board before the first move,
+                   a number of random ones (default 1) or tiles such as a1,c3"),
+    (&["--obstacles"], "  --obstacles <N>  Block N random tiles, which nobody can play on and which break
+                   every row through them"),
+    (&["--best-of"], "  --best-of <N>    Play a match of up to N games, alternating who starts"),
+    (&["--series"], "  --series <FILE>  Play a match with the variant and board of each game listed
+                   in FILE, one per line such as \"fog size=5 win=4\""),
+    (&["--swiss"], "  --swiss <N>      Play N Swiss-system rounds of the tournament instead"),
+    (&["--arena"], "  --arena <FILE>   Enter the strategies listed in FILE instead of KINDs, one
+                   per line: name, algorithm and settings such as depth=3,
+                   rollouts=100 or seed=7"),
+    (&["--cursor"], "  --cursor         Choose moves with a cursor and the arrow keys, remapped in
+                   keys.txt in the data directory"),
+    (&["--teach"], "  --teach          Before each of your moves, mark the tiles that win (w), must be
+                   blocked (b) or make a fork (f)"),
+    (&["--headless"], "  --headless       Play two AIs without the board or pauses, print the result"),
+    (&["--output"], "  --output <MODE>  text (default), or result-only for one parseable line per
+                   game such as \"result=X moves=7 reason=line\", implies --headless"),
+    (&["--out"], "  --out <FILE>     Write the results to FILE, the simulations as JSON for .json
+                   and CSV otherwise"),
+    (&["--proof"], "  --proof          Print every defence and the answer to it"),
+    (&["--distinct"], "  --distinct       Leave out turned and mirrored copies"),
+    (&["--export-svg"], "  --export-svg <FILE>
+                   Draw the final position of the game, or of the last one, as SVG"),
+    (&["--export-png"], "  --export-png <FILE>
+                   The same as a PNG image, in builds with the image feature"),
+    (&["--export-gif"], "  --export-gif <FILE>
+                   Animate the game move by move as a GIF, also with the image feature"),
+    (&["--export-text"], "  --export-text <FILE>
+                   Write the final position and the moves as a Markdown table for
+                   .md files and an HTML snippet otherwise, to paste into issues"),
+    (&["--origin"], "  --origin <0|1>   Number coordinates from 0 (default) or from 1"),
+    (&["--delay"], "  --delay <MS>     Pause MS milliseconds after each move (default 800)"),
+    (&["--in-place"], "  --in-place       Redraw the board in place instead of scrolling"),
+    (&["--theme"], "  --theme <NAME>   Characters boards are drawn with: ascii (default), box for
+                   box-drawing lines, or emoji"),
+    (&["--palette"], "  --palette <NAME> Colors for the marks and highlights: plain (default), colorblind
+                   for blue and orange, or high-contrast"),
+    (&["--colors"], "  --colors <DEPTH> How many colors the terminal shows: truecolor, 256 or 16, found
+                   out from COLORTERM and TERM when not given"),
+    (&["--scale"], "  --scale <N>      Draw each tile N times as large with big marks, up to 8, for
+                   projectors and low vision (default 1)"),
+    (&["--layout"], "  --layout <NAME>  How tiles are laid out: auto (default, compact from 15x15), grid,
+                   compact for a character per tile, or half-block or braille for
+                   two rows of tiles to a line"),
+    (&["--speak"], "  --speak          Say the moves and the result aloud, in builds with the audio
+                   feature and a speech synthesizer such as espeak-ng"),
+    (&["--no-animation"], "  --no-animation   Don't blink newly placed marks when redrawing in place"),
+];
+
+/// The options every command takes.
+const COMMON_OPTIONS: &str = "  -v, -vv          Log moves, then also AI search statistics, to stderr
+  -h, --help       Print this help";
+
+/// The help without a command: what the commands are.
+pub fn usage() -> String {
+    let mut out = String::from("Usage: tick-tack-toe [COMMAND] [OPTIONS]\n\nCommands:\n");
+    for (i, command) in SUBCOMMANDS.iter().enumerate() {
+        if i == MAIN_SUBCOMMANDS {
+            out.push_str("\nMore commands:\n");
+        }
+        let name = format!("{} {}", command.name, command.args);
+        match name.trim_end() {
+            name if name.len() < 17 => out.push_str(&format!("  {:<17}{}\n", name, command.about)),
+            name => out.push_str(&format!("  {}\n                   {}\n", name, command.about)),
+        }
+    }
+    out.push_str("\nOptions:\n");
+    out.push_str(COMMON_OPTIONS);
+    out.push_str("\n\nSee tick-tack-toe COMMAND --help for the options of a command, which are those\n");
+    out.push_str("of play without one. Logging can also be set with RUST_LOG, e.g. RUST_LOG=debug.");
+    out
+}
+
+/// The help of `command` with the options it takes.
+fn command_help(command: &Subcommand) -> String {
+    let args = if command.args.is_empty() { String::new() } else { format!("{} ", command.args) };
+    let mut out = format!("Usage: tick-tack-toe {} {}[OPTIONS]\n\n", command.name, args);
+    out.push_str(&command.about.replace("\n                   ", "\n"));
+    out.push_str(".\n\nOptions:\n");
+    for (names, help) in OPTIONS {
+        if names.iter().any(|name| takes(command, name)) {
+            out.push_str(help);
+            out.push('\n');
+        }
+    }
+    out.push_str(COMMON_OPTIONS);
+    out
+}
+
+pub fn takes(command: &Subcommand, option: &str) -> bool {
+    command.options.iter().any(|group| group.contains(&option))
+}
+
+fn subcommand(name: &str) -> Option<&'static Subcommand> {
+    SUBCOMMANDS.iter().find(|command| command.name == name)
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let args: Vec<String> = args.into_iter().collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        let help = args.iter().find_map(|arg| subcommand(arg)).map_or_else(usage, command_help);
+        return Ok(Options { command: Command::Help(help), ..Options::default() });
+    }
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    // The command once named, and the options given, checked against it at the end.
+    let mut named: Option<&'static Subcommand> = None;
+    let mut given: Vec<String> = Vec::new();
+    let mut arena: Option<PathBuf> = None;
+
+    while let Some(arg) = args.next() {
+        if arg.starts_with("--") {
+            given.push(arg.clone());
+        }
+        match arg.as_str() {
+            "--best-of" => options.best_of = Some(number(&arg, args.next())?),
+            "--series" => options.series = Some(args.next().ok_or("--series needs a file listing the games.")?.into()),
+            "--swiss" => options.swiss = Some(number(&arg, args.next())?),
+            "--arena" => arena = Some(args.next().ok_or("--arena needs a file listing the entrants.")?.into()),
+            "--first" => {
+                options.first = match args.next().map(|side| side.to_lowercase()).as_deref() {
+                    Some("x") => First::Cross,
+                    Some("o") => First::Nought,
+                    Some("random") => First::CoinFlip,
+                    _ => return Err("--first needs x, o or random.".to_string()),
+                }
+            }
+            "--origin"  => {
+                options.origin = match args.next().as_deref() {
+                    Some("0") => 0,
+                    Some("1") => 1,
+                    _ => return Err("--origin needs 0 or 1.".to_string()),
+                }
+            }
+            "--cross"  => options.preset.players[0] = Some(kind(&arg, args.next())?),
+            "--nought" => options.preset.players[1] = Some(kind(&arg, args.next())?),
+            "--cross-name"  => options.preset.names[0] = Some(args.next().ok_or("--cross-name needs a name.")?),
+            "--nought-name" => options.preset.names[1] = Some(args.next().ok_or("--nought-name needs a name.")?),
+            "--size" => match number(&arg, args.next())? as usize {
+                size if size <= MAX_SIZE => options.preset.size = Some(size),
+                _ => return Err(format!("--size goes up to {}.", MAX_SIZE)),
+            },
+            "--win-length" => options.preset.win_length = Some(number(&arg, args.next())? as usize),
+            "--variant" => {
+                let name = args.next().ok_or("--variant needs standard, fog, hex, decay, morris or scoring.")?;
+                options.preset.variant = Variant::named(&name).ok_or(format!("'{}' isn't a variant, use standard, fog, hex, decay:N, morris:N or scoring.", name))?;
+            }
+            "--handicap" => {
+                options.preset.handicap = Some(Handicap::parse(&args.next().ok_or("--handicap needs a side, e.g. o:2.")?)?);
+            }
+            "--obstacles" => options.preset.obstacles = number(&arg, args.next())? as usize,
+            "--headless" => options.headless = true,
+            "--out" => options.out = Some(args.next().ok_or("--out needs a file name.")?.into()),
+            "--export-svg" => options.export_svg = Some(args.next().ok_or("--export-svg needs a file name.")?.into()),
+            "--export-png" if cfg!(feature = "image") => {
+                options.export_png = Some(args.next().ok_or("--export-png needs a file name.")?.into());
+            }
+            "--export-png" => return Err("--export-png needs a build with --features image.".to_string()),
+            "--export-gif" if cfg!(feature = "image") => {
+                options.export_gif = Some(args.next().ok_or("--export-gif needs a file name.")?.into());
+            }
+            "--export-gif" => return Err("--export-gif needs a build with --features image.".to_string()),
+            "--export-text" => options.export_text = Some(args.next().ok_or("--export-text needs a file name.")?.into()),
+            "--output" => {
+                options.output = match args.next().as_deref() {
+                    Some("text") => Output::Text,
+                    Some("result-only") => Output::ResultOnly,
+                    _ => return Err("--output needs text or result-only.".to_string()),
+                }
+            }
+            "--delay" => {
+                let value = args.next().ok_or("--delay needs a value.")?;
+                options.delay = Some(value.parse().map_err(|_| format!("--delay needs milliseconds, got '{}'.", value))?);
+            }
+            "--in-place" => options.in_place = true,
+            "--theme" => {
+                let name = args.next().ok_or("--theme needs ascii, box or emoji.")?;
+                options.theme = Some(Theme::named(&name).ok_or(format!("'{}' isn't a theme, use ascii, box or emoji.", name))?);
+            }
+            "--palette" => {
+                let name = args.next().ok_or("--palette needs plain, colorblind or high-contrast.")?;
+                options.palette = Some(Palette::named(&name)
+                    .ok_or(format!("'{}' isn't a palette, use plain, colorblind or high-contrast.", name))?);
+            }
+            "--colors" => {
+                let depth = args.next().ok_or("--colors needs truecolor, 256 or 16.")?;
+                options.colors = Some(ColorDepth::named(&depth).ok_or(format!("'{}' isn't a color depth, use truecolor, 256 or 16.", depth))?);
+            }
+            "--scale" => match number(&arg, args.next())? as usize {
+                scale @ 1..=Theme::MAX_SCALE => options.scale = Some(scale),
+                _ => return Err(format!("--scale goes from 1 to {}.", Theme::MAX_SCALE)),
+            },
+            "--layout" => {
+                let name = args.next().ok_or("--layout needs auto, grid, compact, half-block or braille.")?;
+                options.layout = Some(Layout::named(&name).ok_or(format!("'{}' isn't a layout, use auto, grid, compact, half-block or braille.", name))?);
+            }
+            "--cursor" => options.cursor = true,
+            "--teach" => options.teach = true,
+            "--speak" if cfg!(feature = "audio") => options.speak = true,
+            "--speak" => return Err("--speak needs a build with --features audio.".to_string()),
+            "--proof" => options.proof = true,
+            "--distinct" => options.distinct = true,
+            "--no-animation" => options.no_animation = true,
+            _ if arg.len() > 1 && arg.starts_with('-') && arg[1..].chars().all(|c| c == 'v') => {
+                options.verbosity += arg.len() as u8 - 1;
+            }
+            _ if named.is_none() && subcommand(&arg).is_some() => {
+                named = subcommand(&arg);
+                options.command = match arg.as_str() {
+                    "stats"       => Command::Stats(None),
+                    "leaderboard" => Command::Leaderboard,
+                    "puzzle"      => Command::Puzzle,
+                    "watch"       => Command::Watch,
+                    "edit"        => Command::Edit,
+                    "simulate"    => Command::Simulate(DEFAULT_SIMULATIONS),
+                    "tournament"  => Command::Tournament(Vec::new()),
+                    "simul"       => Command::Simul(Vec::new()),
+                    "positions"   => Command::Positions,
+                    "verify"      => Command::Verify,
+                    "serve"       => Command::Serve(None),
+                    "analyze"     => Command::Analyze(None),
+                    "replay" => Command::Replay(args.next().ok_or("replay needs a game record file.")?.into()),
+                    "solve" => Command::Solve(args.next().ok_or("solve needs a position, such as X../.O./....")?),
+                    "perft" => Command::Perft(number("perft", args.next())?, None),
+                    "connect-engine" => {
+                        Command::ConnectEngine(args.next().ok_or("connect-engine needs an address, e.g. 10.0.0.2:7878.")?)
+                    }
+                    "completions" => {
+                        let shell = args.next().ok_or("completions needs bash, zsh, fish or powershell.")?;
+                        Command::Completions(Shell::named(&shell).ok_or(format!("'{}' isn't a shell, use bash, zsh, fish or powershell.", shell))?)
+                    }
+                    "convert" => {
+                        let from = args.next().ok_or("convert needs the file to read and the file to write.")?;
+                        let to = args.next().ok_or("convert needs the file to read and the file to write.")?;
+                        Command::Convert(from.into(), to.into())
+                    }
+                    _ => Command::Play,
+                };
+            }
+            _ if options.command == Command::Simulate(DEFAULT_SIMULATIONS) && arg.parse::<u32>().is_ok() => {
+                options.command = Command::Simulate(number("simulate", Some(arg))?);
+            }
+            _ if !arg.starts_with('-') && matches!(options.command, Command::Perft(_, None)) => {
+                if let Command::Perft(_, position) = &mut options.command {
+                    *position = Some(arg);
+                }
+            }
+            _ if !arg.starts_with('-') && options.command == Command::Stats(None) => {
+                options.command = Command::Stats(Some(arg));
+            }
+            _ if !arg.starts_with('-') && options.command == Command::Analyze(None) => {
+                options.command = Command::Analyze(Some(arg.into()));
+            }
+            _ if !arg.starts_with('-') && options.command == Command::Serve(None) => {
+                options.command = Command::Serve(Some(arg));
+            }
+            _ if !arg.starts_with('-') && matches!(options.command, Command::Tournament(_)) => {
+                let kind = match kind("tournament", Some(arg))? {
+                    "human" => return Err("Tournament entrants must be AIs.".to_string()),
+                    kind => kind,
+                };
+                if let Command::Tournament(kinds) = &mut options.command {
+                    kinds.push(kind);
+                }
+            }
+            _ if !arg.starts_with('-') && matches!(options.command, Command::Simul(_)) => {
+                let kind = match kind("simul", Some(arg))? {
+                    "human" => return Err("Simul opponents must be AIs.".to_string()),
+                    kind => kind,
+                };
+                if let Command::Simul(kinds) = &mut options.command {
+                    kinds.push(kind);
+                }
+            }
+            _ => return Err(format!("Unknown argument '{}'.\n\n{}", arg, usage())),
+        }
+    }
+
+    let command = named.unwrap_or(&SUBCOMMANDS[0]);
+    if let Some(option) = given.iter().find(|option| !takes(command, option)) {
+        return Err(format!("{} isn't an option of {}, see tick-tack-toe {} --help.", option, command.name, command.name));
+    }
+    match (&options.command, arena) {
+        (Command::Tournament(kinds), Some(_)) if !kinds.is_empty() => Err("A tournament takes either KINDs or an --arena file.".to_string()),
+        (Command::Tournament(_), Some(path)) => Ok(Options { command: Command::Arena(path), ..options }),
+        (Command::Tournament(kinds), None) if kinds.len() < 2 => Err("A tournament needs at least two entrants.".to_string()),
+        (Command::Simul(kinds), _) if kinds.is_empty() => Err("A simul needs at least one opponent.".to_string()),
+        _ => Ok(options),
+    }
+}
+
+/// The player kind named on the command line, as used by `Player::from_kind`.
+fn kind(flag: &str, value: Option<String>) -> Result<&'static str, String> {
+    match value.as_deref() {
+        Some("human")   => Ok("human"),
+        Some("random")  => Ok("random AI"),
+        Some("perfect") => Ok("perfect AI"),
+        Some("minimax") => Ok("minimax AI"),
+        Some("montecarlo") => Ok("monte carlo AI"),
+        _ => Err(format!("{} needs human, random, perfect, minimax or montecarlo.", flag)),
+    }
+}
+
+fn number(flag: &str, value: Option<String>) -> Result<u32, String> {
+    let value = value.ok_or(format!("{} needs a value.", flag))?;
+
+    match value.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("{} needs a positive number, got '{}'.", flag, value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, usage, ColorDepth, Command, First, Handicap, Layout, Options, Output, Palette, Shell, Theme, Variant};
+    use tick_tack_toe::board::Tile::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parse_options() {
+        assert_eq!(parse(args("")), Ok(Options::default()));
+        assert_eq!(parse(args("--best-of 5")), Ok(Options { best_of: Some(5), ..Options::default() }));
+        assert!(parse(args("--best-of")).is_err());
+        assert!(parse(args("--best-of 0")).is_err());
+        assert_eq!(parse(args("--series mixed.txt")).unwrap().series, Some("mixed.txt".into()));
+        assert_eq!(parse(args("--origin 1")).unwrap().origin, 1);
+        assert!(parse(args("--origin 2")).is_err());
+        assert_eq!(parse(args("--first O")).unwrap().first, First::Nought);
+        assert_eq!(parse(args("--first random")).unwrap().first, First::CoinFlip);
+        assert!(parse(args("--first me")).is_err());
+        assert_eq!(parse(args("-vv -v")).unwrap().verbosity, 3);
+        let options = parse(args("--headless --cross random --nought perfect --size 4 --win-length 3")).unwrap();
+        assert!(options.headless);
+        assert_eq!(options.preset.players, [Some("random AI"), Some("perfect AI")]);
+        assert_eq!((options.preset.size, options.preset.win_length), (Some(4), Some(3)));
+        assert!(parse(args("--cross nobody")).is_err());
+        assert_eq!(parse(args("--nought-name RandomBot")).unwrap().preset.names, [None, Some("RandomBot".to_string())]);
+        assert_eq!(parse(args("--handicap o:2")).unwrap().preset.handicap, Some(Handicap::Random(Nought, 2)));
+        assert_eq!(parse(args("--handicap X:c3,a1")).unwrap().preset.handicap, Some(Handicap::Tiles(Cross, vec![(0, 0), (2, 2)])));
+        assert!(parse(args("--handicap o:0")).is_err());
+        assert_eq!(parse(args("--obstacles 4")).unwrap().preset.obstacles, 4);
+        assert_eq!(parse(args("--variant fog")).unwrap().preset.variant, Variant::Fog);
+        assert_eq!(parse(args("--variant hex")).unwrap().preset.variant, Variant::Hex);
+        assert_eq!(parse(args("--variant decay:4")).unwrap().preset.variant, Variant::Decay(4));
+        assert_eq!(parse(args("--variant morris")).unwrap().preset.variant, Variant::Morris(3));
+        assert_eq!(parse(args("--variant scoring")).unwrap().preset.variant, Variant::Scoring);
+        assert!(parse(args("--handicap o:z")).is_err());
+        assert_eq!(parse(args("--output result-only")).unwrap().output, Output::ResultOnly);
+        assert!(parse(args("--output xml")).is_err());
+        assert_eq!(parse(args("--export-svg end.svg")).unwrap().export_svg, Some("end.svg".into()));
+        assert_eq!(parse(args("--export-text game.md")).unwrap().export_text, Some("game.md".into()));
+        assert!(parse(args("--size 27")).is_err());
+        assert_eq!(parse(args("--delay 0")).unwrap().delay, Some(0));
+        assert!(parse(args("--delay soon")).is_err());
+        assert_eq!(parse(args("--in-place --no-animation")), Ok(Options { in_place: true, no_animation: true, ..Options::default() }));
+        assert!(parse(args("--teach")).unwrap().teach);
+        assert!(parse(args("--cursor")).unwrap().cursor);
+        assert_eq!(parse(args("--palette colorblind")).unwrap().palette, Some(Palette::COLORBLIND));
+        assert!(parse(args("--palette red-green")).is_err());
+        assert_eq!(parse(args("--theme box")).unwrap().theme, Some(Theme::BOX));
+        assert_eq!(parse(args("--colors 16")).unwrap().colors, Some(ColorDepth::Ansi16));
+        assert!(parse(args("--colors 8")).is_err());
+        assert!(parse(args("--theme fancy")).is_err());
+        assert_eq!(parse(args("--scale 3")).unwrap().scale, Some(3));
+        assert!(parse(args("--scale 0")).is_err());
+        assert_eq!(parse(args("--layout braille")).unwrap().layout, Some(Layout::Braille));
+        assert_eq!(parse(args("--speak")).is_ok(), cfg!(feature = "audio"));
+        assert_eq!(parse(args("stats Alice")).unwrap().command, Command::Stats(Some("Alice".to_string())));
+        assert_eq!(parse(args("simulate 20 --out r.csv")).unwrap().command, Command::Simulate(20));
+        assert_eq!(parse(args("simulate")).unwrap().command, Command::Simulate(100));
+        assert!(parse(args("simulate 0")).is_err());
+        let options = parse(args("tournament random perfect random --swiss 3")).unwrap();
+        assert_eq!(options.command, Command::Tournament(vec!["random AI", "perfect AI", "random AI"]));
+        assert_eq!(options.swiss, Some(3));
+        assert!(parse(args("tournament random")).is_err());
+        assert!(parse(args("tournament random human")).is_err());
+        assert_eq!(parse(args("tournament minimax montecarlo")).unwrap().command, Command::Tournament(vec!["minimax AI", "monte carlo AI"]));
+        assert_eq!(parse(args("simul random minimax --size 4")).unwrap().command, Command::Simul(vec!["random AI", "minimax AI"]));
+        assert!(parse(args("simul")).is_err());
+        assert_eq!(parse(args("tournament --arena bots.txt --size 4")).unwrap().command, Command::Arena("bots.txt".into()));
+        assert!(parse(args("tournament random perfect --arena bots.txt")).is_err());
+        assert!(parse(args("--unknown")).is_err());
+        assert!(parse(args("stats a b")).is_err());
+        assert_eq!(parse(args("leaderboard")).unwrap().command, Command::Leaderboard);
+        assert_eq!(parse(args("replay game.txt")).unwrap().command, Command::Replay("game.txt".into()));
+        assert!(parse(args("replay")).is_err());
+        assert_eq!(parse(args("replay game.txt --export-svg end.svg")).unwrap().export_svg, Some("end.svg".into()));
+        assert_eq!(parse(args("solve X../.O./... --proof")), Ok(Options { command: Command::Solve("X../.O./...".to_string()), proof: true, ..Options::default() }));
+        assert!(parse(args("solve")).is_err());
+        assert_eq!(parse(args("solve X.../.O../..../.... --win-length 4")).unwrap().preset.win_length, Some(4));
+        assert_eq!(parse(args("verify --size 2")).unwrap().command, Command::Verify);
+        assert_eq!(parse(args("positions --distinct")), Ok(Options { command: Command::Positions, distinct: true, ..Options::default() }));
+        assert_eq!(parse(args("perft 4 --size 4")).unwrap().command, Command::Perft(4, None));
+        assert_eq!(parse(args("perft 2 X../.O./...")).unwrap().command, Command::Perft(2, Some("X../.O./...".to_string())));
+        assert_eq!(parse(args("analyze game.sgf --out report.md")).unwrap().command, Command::Analyze(Some("game.sgf".into())));
+        assert_eq!(parse(args("analyze --size 4")).unwrap().command, Command::Analyze(None));
+        assert_eq!(parse(args("connect-engine 10.0.0.2:7878 --nought human")).unwrap().command, Command::ConnectEngine("10.0.0.2:7878".to_string()));
+        assert!(parse(args("connect-engine")).is_err());
+        assert_eq!(parse(args("convert a.txt b.sgf")).unwrap().command, Command::Convert("a.txt".into(), "b.sgf".into()));
+        assert!(parse(args("convert a.txt")).is_err());
+        assert_eq!(parse(args("edit --size 4")).unwrap().command, Command::Edit);
+        assert_eq!(parse(args("play --best-of 3")), Ok(Options { best_of: Some(3), ..Options::default() }));
+        assert_eq!(parse(args("puzzle --cursor")).unwrap().command, Command::Puzzle);
+        assert_eq!(parse(args("watch --cross minimax --nought montecarlo --delay 200")).unwrap().command, Command::Watch);
+        assert!(parse(args("watch --best-of 3")).is_err());
+        assert_eq!(parse(args("serve")).unwrap().command, Command::Serve(None));
+        assert_eq!(parse(args("serve 127.0.0.1:7878")).unwrap().command, Command::Serve(Some("127.0.0.1:7878".to_string())));
+        assert!(parse(args("play simulate")).is_err());
+        assert_eq!(parse(args("completions fish")).unwrap().command, Command::Completions(Shell::Fish));
+        assert!(parse(args("completions tcsh")).is_err());
+
+        // Commands only take their own options.
+        assert_eq!(parse(args("replay game.txt --best-of 3")), Err("--best-of isn't an option of replay, see tick-tack-toe replay --help.".to_string()));
+        assert!(parse(args("--proof")).is_err());
+        assert!(parse(args("serve --size 4")).is_err());
+        assert_eq!(parse(args("--help")).unwrap().command, Command::Help(usage()));
+        let Command::Help(help) = parse(args("solve --help")).unwrap().command else { panic!("solve --help isn't help") };
+        assert!(help.starts_with("Usage: tick-tack-toe solve POSITION [OPTIONS]"));
+        assert!(help.contains("--proof") && help.contains("--win-length") && !help.contains("--cross"));
+    }
+}
@@ -0,0 +1,48 @@
+//! The system clipboard, through whichever copy tool the platform has.
+
+use crate::websocket;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use tick_tack_toe::log;
+
+/// Copy tools and their arguments, tried in order until one works.
+const TOOLS: &[(&str, &[&str])] = &[
+    ("pbcopy",   &[]),
+    ("wl-copy",  &[]),
+    ("xclip",    &["-selection", "clipboard"]),
+    ("xsel",     &["--clipboard", "--input"]),
+    ("clip.exe", &[]),
+];
+
+/// Puts `text` on the clipboard, returning false when no copy tool took it. The text is then sent
+/// to the terminal as an OSC 52 sequence instead, which many terminals (also over ssh) copy.
+pub fn copy(text: &str) -> bool {
+    for (tool, args) in TOOLS {
+        match pipe(tool, args, text) {
+            Ok(()) => {
+                log::debug!("copied {} bytes with {}", text.len(), tool);
+                return true;
+            }
+            Err(err) => log::debug!("couldn't copy with {}: {}", tool, err),
+        }
+    }
+
+    print!("\x1b]52;c;{}\x07", websocket::base64(text.as_bytes()));
+    let _ = io::stdout().flush();
+    false
+}
+
+fn pipe(tool: &str, args: &[&str], text: &str) -> io::Result<()> {
+    let mut child = Command::new(tool)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    child.stdin.take().expect("stdin is piped").write_all(text.as_bytes())?;
+
+    match child.wait()?.success() {
+        true  => Ok(()),
+        false => Err(io::Error::other("it failed")),
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{clipboard, cursor, keys, player, screen};
+use tick_tack_toe::board::Tile;
+use tick_tack_toe::coords;
+use tick_tack_toe::game::Game;
+use tick_tack_toe::render::Theme;
+
+/// What the game loop does after a command has run.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Action {
+    /// Ask the same player for input again.
+    Prompt,
+    Resign,
+    Quit,
+}
+
+/// An in-game command a human can type instead of a move.
+pub struct Command {
+    pub names: &'static [&'static str],
+    pub help: &'static str,
+    pub run: fn(&mut Game, Tile) -> Action,
+}
+
+/// Every command available at the move prompt, new commands only need an entry here.
+pub const COMMANDS: &[Command] = &[
+    Command { names: &["q", "quit"],  help: "Quit the game",                          run: |_, _| Action::Quit },
+    Command { names: &["u", "undo"],  help: "Take back your last move and the reply", run: undo },
+    Command { names: &["h", "hint"],  help: "Suggest a move",                         run: hint },
+    Command { names: &["copy"],       help: "Copy the position notation",             run: |game, _| copy(game, false) },
+    Command { names: &["copy board"], help: "Copy the notation and the drawn board",  run: |game, _| copy(game, true) },
+    Command { names: &["resign"],     help: "Give up the game",                       run: |_, _| Action::Resign },
+    Command { names: &["keys"],       help: "Show the keys of the cursor mode",       run: keys },
+    Command { names: &["?", "help"],  help: "Show this help",                         run: help },
+];
+
+pub fn find(input: &str) -> Option<&'static Command> {
+    let input = input.trim().to_lowercase();
+    COMMANDS.iter().find(|c| c.names.contains(&input.as_str()))
+}
+
+fn undo(game: &mut Game, side: Tile) -> Action {
+    match game.undo_turn(side) {
+        Ok(()) => screen::show_board(game, &screen::theme(), None, side),
+        Err(err) => println!("{}", err),
+    }
+    Action::Prompt
+}
+
+fn keys(_: &mut Game, _: Tile) -> Action {
+    println!("{}", keys::keymap().help());
+    Action::Prompt
+}
+
+fn hint(game: &mut Game, side: Tile) -> Action {
+    match game.view(side).best_move(side) {
+        Some(m) => println!("Hint: {}", coords::format(m.row, m.col)),
+        None => println!("There is no move left to make."),
+    }
+    Action::Prompt
+}
+
+/// Copies the position for sharing, or to paste into `position` in the editor.
+fn copy(game: &mut Game, with_board: bool) -> Action {
+    let notation = game.board().notation();
+    let text = match with_board {
+        true  => format!("{}\n{}", notation, game.board().render(&Theme::default())),
+        false => notation.clone(),
+    };
+
+    match clipboard::copy(&text) {
+        true  => println!("Copied {} to the clipboard.", notation),
+        false => println!("There's no clipboard tool, asked the terminal to copy {} instead.", notation),
+    }
+    Action::Prompt
+}
+
+fn help(game: &mut Game, side: Tile) -> Action {
+    println!("{}", help_text(game, side));
+    Action::Prompt
+}
+
+/// How to enter moves on this board, the commands, the rules of the game in progress and the
+/// cursor mode keys when they are in use.
+pub fn help_text(game: &Game, side: Tile) -> String {
+    let board = game.board();
+    let corner = (0, board.length() - 1);
+    let example = coords::format(corner.0, corner.1);
+    let mut out = format!(
+        "Enter a move as x and y counting from {}, e.g. \"{}\", \"{}\" or \"{}\" for the top right tile, or one of the commands:\n",
+        coords::origin(), example, example.replace(',', ""), coords::notation(corner.0, corner.1),
+    );
+    for c in COMMANDS {
+        out.push_str(&format!("  {:<12} {}\n", c.names.join(", "), c.help));
+    }
+
+    out.push_str(&format!("Rules: {0}x{0} board, {1} in a row wins, you play {2:?} ({3}).\n",
+        board.length(), board.win_row_length(), side, side.char()));
+    let blocked = game.start().blocked_tiles().len();
+    let handicap = board.length() * board.length() - game.start().empty_tiles().len() - blocked;
+    if let Some(marks) = game.variant().decay() {
+        out.push_str(&format!("Marks decay: a side's oldest mark goes when it plays one more than {}.\n", marks));
+    }
+    if game.variant().scoring() {
+        out.push_str("Scoring: every row completed is a point, the most points when the board is full wins.\n");
+    }
+    if let Some(pieces) = game.variant().pieces() {
+        out.push_str(&format!("Morris: each side has {} pieces, then moves one to a tile next to it (FROM to TO). A side that can't move loses.\n", pieces));
+    }
+    if blocked > 0 {
+        out.push_str(&format!("{} tile{} blocked, nobody can play there or make a row through {}.\n",
+            blocked, if blocked == 1 { " is" } else { "s are" }, if blocked == 1 { "it" } else { "them" }));
+    }
+    if handicap > 0 {
+        out.push_str(&format!("The game started with {} mark{} already on the board.\n", handicap, if handicap == 1 { "" } else { "s" }));
+    }
+    if player::teaching() {
+        out.push_str("Teaching is on: winning tiles are marked w, tiles to block b and forks f.\n");
+    }
+    if cursor::enabled() {
+        out.push_str(&keys::keymap().help());
+        out.push('\n');
+    }
+    out.pop();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{help_text, COMMANDS};
+    use tick_tack_toe::board::{Board, Tile::*};
+    use tick_tack_toe::game::Game;
+
+    #[test]
+    fn help_lists_everything() {
+        let mut board = Board::new(4, 3);
+        board.set(Nought, 1, 1).unwrap();
+        let help = help_text(&Game::new(board), Cross);
+        for name in COMMANDS.iter().flat_map(|c| c.names) {
+            assert!(help.contains(name), "{} is missing from\n{}", name, help);
+        }
+        assert!(help.contains("\"3, 0\", \"3 0\" or \"d1\""), "{}", help);
+        assert!(help.contains("Rules: 4x4 board, 3 in a row wins, you play Cross (X)."), "{}", help);
+        assert!(help.contains("started with 1 mark already"), "{}", help);
+    }
+}
@@ -0,0 +1,267 @@
+//! Shell completion scripts, printed by `completions SHELL` for bash, zsh, fish or PowerShell.
+//! They are made from the commands and options of `cli`, so every command and option completes,
+//! as do the values of the options that take one of a few words.
+
+use crate::cli::{self, Subcommand, OPTIONS, SUBCOMMANDS};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+impl Shell {
+    pub const NAMES: &'static [&'static str] = &["bash", "zsh", "fish", "powershell"];
+
+    pub fn named(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "bash" => Some(Self::Bash),
+            "zsh"  => Some(Self::Zsh),
+            "fish" => Some(Self::Fish),
+            "powershell" | "pwsh" => Some(Self::Powershell),
+            _ => None,
+        }
+    }
+}
+
+/// What an option's value or a command's arguments complete to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Values {
+    Words(&'static [&'static str]),
+    Files,
+    /// Numbers, names and the like, with nothing to offer.
+    Any,
+}
+
+const KINDS: &[&str] = &["human", "random", "perfect", "minimax", "montecarlo"];
+
+/// The values of `option`, `None` for options that take none.
+fn values(option: &str) -> Option<Values> {
+    match option {
+        "--cross" | "--nought" => Some(Values::Words(KINDS)),
+        "--first"   => Some(Values::Words(&["x", "o", "random"])),
+        "--origin"  => Some(Values::Words(&["0", "1"])),
+        "--variant" => Some(Values::Words(&["standard", "fog", "hex", "decay", "morris", "scoring"])),
+        "--output"  => Some(Values::Words(&["text", "result-only"])),
+        "--theme"   => Some(Values::Words(&["ascii", "box", "emoji"])),
+        "--palette" => Some(Values::Words(&["plain", "colorblind", "high-contrast"])),
+        "--colors"  => Some(Values::Words(&["truecolor", "256", "16"])),
+        "--layout"  => Some(Values::Words(&["auto", "grid", "compact", "half-block", "braille"])),
+        "--series" | "--arena" | "--out" | "--export-svg" | "--export-png" | "--export-gif" | "--export-text" => Some(Values::Files),
+        "--cross-name" | "--nought-name" | "--size" | "--win-length" | "--handicap" | "--obstacles" | "--best-of" | "--swiss"
+        | "--delay" | "--scale" => Some(Values::Any),
+        _ => None,
+    }
+}
+
+/// What the arguments after the name of `command` complete to.
+fn arguments(command: &Subcommand) -> Values {
+    match command.name {
+        "tournament" | "simul"        => Values::Words(&KINDS[1..]),
+        "completions"                 => Values::Words(Shell::NAMES),
+        "replay" | "analyze" | "convert" => Values::Files,
+        _ => Values::Any,
+    }
+}
+
+/// The options of `command` in the order of the help, `--help` included.
+fn options(command: &Subcommand) -> Vec<&'static str> {
+    let mut options: Vec<&str> = OPTIONS.iter().flat_map(|(names, _)| names.iter().copied()).filter(|name| cli::takes(command, name)).collect();
+    options.push("--help");
+    options
+}
+
+/// Every option with a value, and its values.
+fn valued() -> impl Iterator<Item = (&'static str, Values)> {
+    OPTIONS.iter().flat_map(|(names, _)| names.iter()).filter_map(|&name| Some((name, values(name)?)))
+}
+
+/// The first line of an option's description in `help`.
+fn summary(help: &str) -> &str {
+    let mut lines = help.lines();
+    let first = lines.next().unwrap_or_default();
+    // Descriptions start in column 19, or on the next line after a long option.
+    match (first.as_bytes().get(18), first.get(19..)) {
+        (Some(b' '), Some(rest)) => rest.trim(),
+        _ => lines.next().unwrap_or_default().trim(),
+    }
+}
+
+pub fn script(shell: Shell) -> String {
+    match shell {
+        Shell::Bash       => bash(),
+        Shell::Zsh        => zsh(),
+        Shell::Fish       => fish(),
+        Shell::Powershell => powershell(),
+    }
+}
+
+fn names() -> Vec<&'static str> {
+    SUBCOMMANDS.iter().map(|command| command.name).collect()
+}
+
+fn bash() -> String {
+    let mut out = String::from("_tick_tack_toe() {\n");
+    out.push_str("    local cur=${COMP_WORDS[COMP_CWORD]} prev=${COMP_WORDS[COMP_CWORD-1]} command='' word\n");
+    out.push_str("    for word in \"${COMP_WORDS[@]:1:COMP_CWORD-1}\"; do\n");
+    out.push_str(&format!("        case $word in\n            {}) command=$word; break ;;\n        esac\n    done\n", names().join("|")));
+    out.push_str("    case $prev in\n");
+    for (name, values) in valued() {
+        let reply = match values {
+            Values::Words(words) => format!("COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))", words.join(" ")),
+            Values::Files => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+            Values::Any => "COMPREPLY=()".to_string(),
+        };
+        out.push_str(&format!("        {}) {}; return ;;\n", name, reply));
+    }
+    out.push_str("    esac\n    if [[ $cur == -* ]]; then\n        case $command in\n");
+    for command in SUBCOMMANDS {
+        let pattern = if command.name == "play" { "''|play" } else { command.name };
+        out.push_str(&format!("            {}) COMPREPLY=($(compgen -W \"{} -v\" -- \"$cur\")) ;;\n", pattern, options(command).join(" ")));
+    }
+    out.push_str("        esac\n        return\n    fi\n    case $command in\n");
+    out.push_str(&format!("        '') COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;\n", names().join(" ")));
+    for command in SUBCOMMANDS {
+        match arguments(command) {
+            Values::Words(words) => out.push_str(&format!("        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;\n", command.name, words.join(" "))),
+            Values::Files => out.push_str(&format!("        {}) COMPREPLY=($(compgen -f -- \"$cur\")) ;;\n", command.name)),
+            Values::Any => (),
+        }
+    }
+    out.push_str("    esac\n}\ncomplete -F _tick_tack_toe tick-tack-toe\n");
+    out
+}
+
+fn zsh() -> String {
+    let mut out = String::from("#compdef tick-tack-toe\n\n_tick_tack_toe() {\n");
+    out.push_str("    local command='' word\n    local -a options\n");
+    out.push_str("    for word in ${words[2,CURRENT-1]}; do\n");
+    out.push_str(&format!("        case $word in\n            ({}) command=$word; break ;;\n        esac\n    done\n", names().join("|")));
+    out.push_str("    case ${words[CURRENT-1]} in\n");
+    for (name, values) in valued() {
+        let reply = match values {
+            Values::Words(words) => format!("compadd -- {}", words.join(" ")),
+            Values::Files => "_files".to_string(),
+            Values::Any => "_message value".to_string(),
+        };
+        out.push_str(&format!("        ({}) {}; return ;;\n", name, reply));
+    }
+    out.push_str("    esac\n    if [[ $PREFIX == -* ]]; then\n        case $command in\n");
+    for command in SUBCOMMANDS {
+        let pattern = if command.name == "play" { "''|play" } else { command.name };
+        out.push_str(&format!("            ({}) options=({} -v) ;;\n", pattern, options(command).join(" ")));
+    }
+    out.push_str("        esac\n        compadd -- $options\n        return\n    fi\n    case $command in\n");
+    out.push_str(&format!("        ('') compadd -- {} ;;\n", names().join(" ")));
+    for command in SUBCOMMANDS {
+        match arguments(command) {
+            Values::Words(words) => out.push_str(&format!("        ({}) compadd -- {} ;;\n", command.name, words.join(" "))),
+            Values::Files => out.push_str(&format!("        ({}) _files ;;\n", command.name)),
+            Values::Any => (),
+        }
+    }
+    out.push_str("    esac\n}\n\n");
+    // Both autoloaded from the fpath and sourced.
+    out.push_str("if [[ $funcstack[1] == _tick_tack_toe ]]; then\n    _tick_tack_toe \"$@\"\nelse\n    compdef _tick_tack_toe tick-tack-toe\nfi\n");
+    out
+}
+
+fn fish() -> String {
+    let quote = |text: &str| format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"));
+    let mut out = String::from("complete -c tick-tack-toe -f\n");
+    for command in SUBCOMMANDS {
+        let about = command.about.replace("\n                   ", " ");
+        out.push_str(&format!("complete -c tick-tack-toe -n __fish_use_subcommand -a {} -d {}\n", command.name, quote(&about)));
+    }
+    for command in SUBCOMMANDS {
+        let condition = match command.name {
+            "play" => "'__fish_use_subcommand; or __fish_seen_subcommand_from play'".to_string(),
+            name => format!("'__fish_seen_subcommand_from {}'", name),
+        };
+        for (names, help) in OPTIONS {
+            for name in names.iter().filter(|name| cli::takes(command, name)) {
+                let value = match values(name) {
+                    Some(Values::Words(words)) => format!(" -xa {}", quote(&words.join(" "))),
+                    Some(Values::Files) => " -rF".to_string(),
+                    Some(Values::Any) => " -x".to_string(),
+                    None => String::new(),
+                };
+                out.push_str(&format!("complete -c tick-tack-toe -n {} -l {}{} -d {}\n", condition, &name[2..], value, quote(summary(help))));
+            }
+        }
+        match arguments(command) {
+            Values::Words(words) => out.push_str(&format!("complete -c tick-tack-toe -n {} -a {}\n", condition, quote(&words.join(" ")))),
+            Values::Files => out.push_str(&format!("complete -c tick-tack-toe -n {} -F\n", condition)),
+            Values::Any => (),
+        }
+    }
+    out
+}
+
+fn powershell() -> String {
+    let list = |words: &[&str]| words.iter().map(|word| format!("'{}'", word)).collect::<Vec<_>>().join(", ");
+    let mut out = String::from("Register-ArgumentCompleter -Native -CommandName tick-tack-toe -ScriptBlock {\n");
+    out.push_str("    param($wordToComplete, $commandAst, $cursorPosition)\n");
+    out.push_str("    $words = @($commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object { $_.ToString() })\n");
+    out.push_str("    if ($wordToComplete) { $words = @($words | Select-Object -SkipLast 1) }\n");
+    out.push_str(&format!("    $commands = @({})\n", list(&names())));
+    out.push_str("    $command = $words | Where-Object { $commands -contains $_ } | Select-Object -First 1\n");
+    out.push_str("    $previous = if ($words.Count) { $words[-1] } else { '' }\n");
+    out.push_str("    $candidates = switch ($previous) {\n");
+    for (name, values) in valued() {
+        match values {
+            Values::Words(words) => out.push_str(&format!("        '{}' {{ {} }}\n", name, list(words))),
+            // Nothing to offer, PowerShell falls back to files.
+            Values::Files | Values::Any => out.push_str(&format!("        '{}' {{ }}\n", name)),
+        }
+    }
+    out.push_str("        default {\n            if ($wordToComplete -like '-*') {\n                switch ($command) {\n");
+    for command in SUBCOMMANDS {
+        let options = list(&[options(command), vec!["-v"]].concat());
+        match command.name {
+            "play" => out.push_str(&format!("                    {{ $_ -in $null, 'play' }} {{ {} }}\n", options)),
+            name => out.push_str(&format!("                    '{}' {{ {} }}\n", name, options)),
+        }
+    }
+    out.push_str("                }\n            } elseif (-not $command) {\n                $commands\n            } else {\n                switch ($command) {\n");
+    for command in SUBCOMMANDS {
+        if let Values::Words(words) = arguments(command) {
+            out.push_str(&format!("                    '{}' {{ {} }}\n", command.name, list(words)));
+        }
+    }
+    out.push_str("                }\n            }\n        }\n    }\n");
+    out.push_str("    $candidates | Where-Object { $_ -like \"$wordToComplete*\" } | ForEach-Object {\n");
+    out.push_str("        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)\n    }\n}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{script, summary, values, Shell};
+    use crate::cli::{OPTIONS, SUBCOMMANDS};
+
+    #[test]
+    fn scripts() {
+        // Exactly the options with a <VALUE> in their help complete one.
+        for (names, help) in OPTIONS {
+            for name in *names {
+                assert_eq!(values(name).is_some(), help.contains(&format!("{} <", name)), "{}", name);
+            }
+        }
+        assert_eq!(summary(OPTIONS[0].1), "Who plays X: human, random, perfect, minimax or montecarlo");
+        assert_eq!(summary("  --export-svg <FILE>\n                   Draw the final position"), "Draw the final position");
+
+        for shell in Shell::NAMES.iter().map(|name| Shell::named(name).unwrap()) {
+            let script = script(shell);
+            for command in SUBCOMMANDS {
+                assert!(script.contains(command.name), "{:?} {}", shell, command.name);
+            }
+            assert!(script.contains("montecarlo") && script.contains("half-block"), "{:?}", shell);
+        }
+        assert!(script(Shell::Fish).contains("-l cross -xa 'human random perfect minimax montecarlo'"));
+        assert!(script(Shell::Fish).contains("-d 'Play today\\'s challenge"));
+        assert!(script(Shell::Bash).contains("--theme) COMPREPLY=($(compgen -W \"ascii box emoji\" -- \"$cur\")); return ;;"));
+        assert_eq!(Shell::named("ksh"), None);
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number the first row and column is called, 0 unless set from the command line.
+static ORIGIN: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_origin(origin: usize) {
+    ORIGIN.store(origin, Ordering::Relaxed);
+}
+
+pub fn origin() -> usize {
+    ORIGIN.load(Ordering::Relaxed)
+}
+
+/// Formats a tile the way moves are entered, column (x) first.
+pub fn format(row: usize, col: usize) -> String {
+    format!("{}, {}", col + origin(), row + origin())
+}
+
+/// Formats a tile in the origin independent letter notation used by game records, e.g. "b3".
+/// There is a letter for each of the 26 columns of the largest board.
+pub fn notation(row: usize, col: usize) -> String {
+    format!("{}{}", (b'a' + col as u8) as char, row + 1)
+}
+
+/// Parses the letter notation of `notation`.
+pub fn parse_notation(input: &str, size: usize) -> Result<(usize, usize), String> {
+    if !input.trim_start().starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(format!("'{}' isn't a tile in letter notation.", input));
+    }
+    parse_with_origin(input, size, 0)
+}
+
+/// Parses a move as `(row, col)` on a board of `size`, from `x, y`, `x y`, `(x, y)` or `b3`.
+/// Letter coordinates name the column and count rows from 1, like a spreadsheet.
+pub fn parse(input: &str, size: usize) -> Result<(usize, usize), String> {
+    parse_with_origin(input, size, origin())
+}
+
+fn parse_with_origin(input: &str, size: usize, origin: usize) -> Result<(usize, usize), String> {
+    let mut input = input.trim();
+    if let Some(inner) = input.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        input = inner.trim();
+    }
+    if input.is_empty() {
+        return Err("Enter a move such as \"1, 2\" or \"b3\".".to_string());
+    }
+
+    let (col, row) = match input.chars().next() {
+        Some(letter) if letter.is_ascii_alphabetic() => {
+            let number = input[1..].trim();
+            if number.is_empty() {
+                return Err(format!("'{}' needs a row number after the column letter, e.g. \"{}1\".", input, letter));
+            }
+
+            if !number.starts_with(|c: char| c.is_ascii_digit()) {
+                return Err(format!("'{}' isn't a move or a command, type ? for help.", input));
+            }
+
+            let row = number.parse::<usize>()
+                .map_err(|_| format!("'{}' isn't a row number.", number))?
+                .checked_sub(1)
+                .ok_or("Rows after a column letter start at 1.")?;
+            ((letter.to_ascii_lowercase() as u8 - b'a') as usize, row)
+        }
+        _ => {
+            let parts: Vec<&str> = input
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|s| !s.is_empty())
+                .collect();
+            if parts.len() != 2 {
+                return Err(format!("Expected two coordinates (x, y), got {}.", parts.len()));
+            }
+
+            let number = |part: &str, name| {
+                part.parse::<usize>()
+                    .map_err(|_| format!("The {} coordinate '{}' isn't a number.", name, part))?
+                    .checked_sub(origin)
+                    .ok_or(format!("Coordinates start at {}.", origin))
+            };
+            (number(parts[0], "x")?, number(parts[1], "y")?)
+        }
+    };
+
+    let last = size - 1 + origin;
+    if col >= size {
+        return Err(format!("The column is outside the board, x goes from {} to {} (a to {}).",
+            origin, last, (b'a' + size as u8 - 1) as char));
+    }
+    if row >= size {
+        return Err(format!("The row is outside the board, y goes from {} to {}.", origin, last));
+    }
+
+    Ok((row, col))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_with_origin;
+
+    #[test]
+    fn parse_formats() {
+        // Lines typed on Windows end in \r\n.
+        for input in ["2,1", "2 1", " (2, 1) ", "2 , 1", "c2", "C 2", "2 1\r\n", "c2\r\n"] {
+            assert_eq!(parse_with_origin(input, 3, 0), Ok((1, 2)), "{}", input);
+        }
+        assert_eq!(parse_with_origin("3, 2", 3, 1), Ok((1, 2)));
+        assert_eq!(parse_with_origin("c2", 3, 1), Ok((1, 2)));
+
+        assert!(parse_with_origin("0, 1", 3, 1).unwrap_err().contains("start at 1"));
+        assert!(parse_with_origin("1, x", 3, 0).unwrap_err().contains("y coordinate 'x'"));
+        assert!(parse_with_origin("1, 2, 3", 3, 0).unwrap_err().contains("got 3"));
+        assert!(parse_with_origin("3, 0", 3, 0).unwrap_err().contains("column"));
+        assert!(parse_with_origin("d1", 3, 0).unwrap_err().contains("column"));
+        assert!(parse_with_origin("a9", 3, 0).unwrap_err().contains("row"));
+        assert!(parse_with_origin("b", 3, 0).is_err());
+        assert!(parse_with_origin("foo", 3, 0).unwrap_err().contains("command"));
+        assert!(parse_with_origin("", 3, 0).is_err());
+    }
+}
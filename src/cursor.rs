@@ -0,0 +1,268 @@
+//! The cursor input mode: humans move a cursor over the board with the keys in `keys.txt` and
+//! place their mark under it, instead of typing coordinates. The terminal stops echoing and
+//! hands over every key press at once for the length of a turn.
+
+use crate::keys::{self, Control, Key};
+use crate::player::{self, Turn};
+use crate::screen;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tick_tack_toe::game::{Game, Move};
+use tick_tack_toe::board::Tile;
+use tick_tack_toe::render;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Plays a turn of `side` with the cursor. `None` when the terminal can't be switched to reading
+/// single keys, e.g. when the input is a pipe, and the move should be typed instead.
+pub fn human_turn(game: &mut Game, side: Tile) -> Option<Turn> {
+    let _raw = RawMode::enter().ok()?;
+    let length = game.board().length();
+    let mut at = game.history().last().map_or((length / 2, length / 2), |m| (m.row, m.col));
+    let mut message = String::new();
+    // Typed before a move to make it that many times, as in vi.
+    let mut count: Option<usize> = None;
+    // The piece picked up to move in Morris, put down on the next place.
+    let mut picked: Option<(usize, usize)> = None;
+
+    loop {
+        draw(game, side, at, picked, &message, count);
+        message.clear();
+
+        let Ok(key) = read_key() else { return Some(Turn::Quit) };
+        let control = keys::keymap().control(key);
+        if let (None, Key::Char(digit @ '0'..='9')) = (control, key) {
+            // A leading 0 isn't the start of a count.
+            if digit != '0' || count.is_some() {
+                let digit = digit as usize - '0' as usize;
+                count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                continue;
+            }
+        }
+        let steps = count.take().unwrap_or(1);
+
+        let (row, col) = at;
+        match control {
+            Some(control @ (Control::Up | Control::Down | Control::Left | Control::Right)) => at = step(at, control, steps, length),
+            Some(Control::Place) if game.board().must_move(side) && picked.is_none() => {
+                if game.board().get(row, col) == Some(side) {
+                    picked = Some(at);
+                } else {
+                    message = "Pick up one of your pieces to move first.".to_string();
+                }
+            }
+            Some(Control::Place) if picked == Some(at) => picked = None,
+            Some(Control::Place) => match game.play(Move { tile: side, row, col, from: picked }) {
+                Ok(_) => return Some(Turn::Moved),
+                Err(err) => message = err.to_string(),
+            },
+            Some(Control::Undo) => {
+                picked = None;
+                if let Err(err) = game.undo_turn(side) {
+                    message = err.to_string();
+                }
+            }
+            Some(Control::Hint) => match game.view(side).best_move(side) {
+                Some(m) => at = (m.row, m.col),
+                None => message = "There is no move left to make.".to_string(),
+            },
+            Some(Control::Quit) => return Some(Turn::Quit),
+            Some(Control::Keys) => message = keys::keymap().help(),
+            None => message = format!("'{}' does nothing, {} lists the keys.", key.name(), keys::keymap().describe(Control::Keys)),
+        }
+    }
+}
+
+/// Where the cursor at `at` ends up after `steps` of a move control, stopping at the edge of a
+/// board `length` tiles across. The other controls leave it where it is.
+fn step((row, col): (usize, usize), control: Control, steps: usize, length: usize) -> (usize, usize) {
+    let last = length.saturating_sub(1);
+    match control {
+        Control::Up    => (row.saturating_sub(steps), col),
+        Control::Down  => (row.saturating_add(steps).min(last), col),
+        Control::Left  => (row, col.saturating_sub(steps)),
+        Control::Right => (row, col.saturating_add(steps).min(last)),
+        _ => (row, col),
+    }
+}
+
+fn draw(game: &Game, side: Tile, at: (usize, usize), picked: Option<(usize, usize)>, message: &str, count: Option<usize>) {
+    let keymap = keys::keymap();
+    let arrows = [Control::Up, Control::Down, Control::Left, Control::Right].map(|control| keymap.describe(control));
+    print!("\x1b[H\x1b[2J");
+    println!("{}", render::turn_indicator(game, side, &player::names()));
+    let marked: Vec<(usize, usize)> = std::iter::once(at).chain(picked).collect();
+    println!("{}", render::board_view(game, Some(side), &screen::theme(), &marked));
+    let place = match picked {
+        Some(_) => "puts the piece down",
+        None if game.board().must_move(side) => "picks up a piece",
+        None => "places",
+    };
+    println!("{:?} to move: {} move the cursor, {} {}, {} lists the keys.",
+        side, arrows.join(" "), keymap.describe(Control::Place), place, keymap.describe(Control::Keys));
+    if !message.is_empty() {
+        println!("{}", message);
+    }
+    if let Some(count) = count {
+        println!("{}", count);
+    }
+    let _ = io::stdout().flush();
+}
+
+/// The next key pressed within `timeout`, or whenever it comes without one. `None` when none
+/// was pressed in time. Only for keys handed over in `RawMode`.
+pub fn key_within(timeout: Option<Duration>) -> io::Result<Option<Key>> {
+    match input_within(timeout) {
+        true  => read_key().map(Some),
+        false => Ok(None),
+    }
+}
+
+/// Reads one key press, an arrow key's escape sequence included.
+fn read_key() -> io::Result<Key> {
+    let mut byte = [0];
+    read_exact(&mut byte)?;
+    let key = match byte[0] {
+        b'\r' | b'\n' => Key::Enter,
+        0x7f | 0x08 => Key::Backspace,
+        // A lone escape, or the start of an arrow key.
+        0x1b if !input_within(Some(ESCAPE_SEQUENCE_WAIT)) => Key::Escape,
+        0x1b => {
+            let mut sequence = [0; 2];
+            read_exact(&mut sequence)?;
+            match sequence {
+                [b'[' | b'O', b'A'] => Key::Up,
+                [b'[' | b'O', b'B'] => Key::Down,
+                [b'[' | b'O', b'C'] => Key::Right,
+                [b'[' | b'O', b'D'] => Key::Left,
+                _ => Key::Escape,
+            }
+        }
+        first => {
+            // The rest of a character of more than one byte.
+            let mut bytes = vec![first];
+            let length = match first {
+                0xf0.. => 4,
+                0xe0.. => 3,
+                0xc0.. => 2,
+                _ => 1,
+            };
+            bytes.resize(length, 0);
+            read_exact(&mut bytes[1..])?;
+            Key::Char(String::from_utf8_lossy(&bytes).chars().next().unwrap_or(' '))
+        }
+    };
+    Ok(key)
+}
+
+/// Reads straight from the terminal, as a buffer in between would hide whether the rest of an
+/// escape sequence has arrived.
+#[cfg(unix)]
+fn read_exact(buf: &mut [u8]) -> io::Result<()> {
+    use std::os::unix::io::FromRawFd;
+    // SAFETY: stdin stays open, and ManuallyDrop keeps the File from closing it.
+    let mut stdin = std::mem::ManuallyDrop::new(unsafe { std::fs::File::from_raw_fd(libc::STDIN_FILENO) });
+    stdin.read_exact(buf)
+}
+
+#[cfg(not(unix))]
+fn read_exact(buf: &mut [u8]) -> io::Result<()> {
+    io::stdin().read_exact(buf)
+}
+
+/// How long the rest of an arrow key takes to arrive after the escape that starts it, at most.
+const ESCAPE_SEQUENCE_WAIT: Duration = Duration::from_millis(50);
+
+/// Whether input arrives within `timeout`, or at all without one.
+#[cfg(unix)]
+fn input_within(timeout: Option<Duration>) -> bool {
+    let millis = timeout.map_or(-1, |timeout| timeout.as_millis().min(i32::MAX as u128) as i32);
+    let mut poll = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+    // SAFETY: poll gets one valid pollfd to fill in.
+    unsafe { libc::poll(&mut poll, 1, millis) > 0 }
+}
+
+#[cfg(not(unix))]
+fn input_within(_timeout: Option<Duration>) -> bool {
+    false
+}
+
+/// The terminal settings to put back after a turn, also when the game is interrupted mid-turn.
+#[cfg(unix)]
+static SAVED: std::sync::Mutex<Option<libc::termios>> = std::sync::Mutex::new(None);
+
+/// Keys are handed over as they are pressed and not echoed while this lives. Ctrl-C still
+/// interrupts.
+pub struct RawMode;
+
+#[cfg(unix)]
+impl RawMode {
+    pub fn enter() -> io::Result<Self> {
+        // SAFETY: termios is plain data, and tcgetattr fills it in or fails.
+        let mut settings: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut settings) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        *SAVED.lock().unwrap_or_else(|err| err.into_inner()) = Some(settings);
+
+        let mut raw = settings;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        // SAFETY: raw is a copy of valid settings.
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        print!("\x1b[?25l");
+        Ok(Self)
+    }
+}
+
+#[cfg(not(unix))]
+impl RawMode {
+    pub fn enter() -> io::Result<Self> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Puts the terminal back the way it was before the cursor mode, if it was changed.
+pub fn restore() {
+    #[cfg(unix)]
+    if let Some(settings) = SAVED.lock().unwrap_or_else(|err| err.into_inner()).take() {
+        // SAFETY: the settings came from tcgetattr.
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &settings) };
+    }
+    print!("\x1b[?25h");
+    let _ = io::stdout().flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::step;
+    use crate::keys::Control;
+
+    #[test]
+    fn steps() {
+        assert_eq!(step((1, 1), Control::Right, 1, 3), (1, 2));
+        assert_eq!(step((1, 1), Control::Right, 3, 5), (1, 4));
+        // Counts past the edge stop there.
+        assert_eq!(step((1, 1), Control::Up, 9, 3), (0, 1));
+        assert_eq!(step((1, 1), Control::Down, usize::MAX, 3), (2, 1));
+        assert_eq!(step((1, 1), Control::Place, 2, 3), (1, 1));
+    }
+}
@@ -0,0 +1,186 @@
+use tick_tack_toe::board::{Board, Tile};
+use crate::player::Player;
+use tick_tack_toe::Result;
+use crate::profile::{data_dir, Outcome};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::{fs, io, io::Write, time::SystemTime};
+
+const RESULTS_FILE: &str = "daily.txt";
+
+/// `(size, win_length, perfect_ai, handicap)`, where handicap is the number of marks the AI starts with.
+const CHALLENGES: [(usize, usize, bool, usize); 6] = [
+    (3, 3, true,  0),
+    (3, 3, true,  1),
+    (3, 3, false, 2),
+    (4, 3, false, 1),
+    (4, 4, false, 0),
+    (5, 4, false, 2),
+];
+
+/// The challenge of one day, the same for everyone playing on that date.
+#[derive(Debug, PartialEq)]
+pub struct Challenge {
+    pub date: String,
+    pub size: usize,
+    pub win_length: usize,
+    pub human: Tile,
+    pub perfect_ai: bool,
+    /// Tiles already held by the AI when the game starts.
+    pub handicap: Vec<(usize, usize)>,
+}
+impl Challenge {
+    pub fn today() -> Self {
+        Self::for_day(days_today())
+    }
+
+    /// Derives the challenge from the number of days since 1970-01-01.
+    pub fn for_day(days: i64) -> Self {
+        let mut rng = StdRng::seed_from_u64(days as u64);
+
+        let (size, win_length, perfect_ai, handicap) = *CHALLENGES.choose(&mut rng).unwrap_or(&CHALLENGES[0]);
+        let human = if rng.gen() { Tile::Cross } else { Tile::Nought };
+
+        let mut tiles: Vec<(usize, usize)> = (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .collect();
+        tiles.shuffle(&mut rng);
+        tiles.truncate(handicap);
+
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            date: format!("{:04}-{:02}-{:02}", year, month, day),
+            size,
+            win_length,
+            human,
+            perfect_ai,
+            handicap: tiles,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "Daily challenge {}: {:?} on a {}x{} board with {} in a row against the {} AI{}.",
+            self.date,
+            self.human,
+            self.size,
+            self.size,
+            self.win_length,
+            if self.perfect_ai { "perfect" } else { "random" },
+            match self.handicap.len() {
+                0 => String::new(),
+                n => format!(", which starts with {} mark{} on the board", n, if n == 1 { "" } else { "s" }),
+            },
+        )
+    }
+
+    /// The players in `[Cross, Nought]` order, the board and the index of who moves first.
+    pub fn start(&self) -> Result<([Player; 2], Board, usize)> {
+        let ai = self.human.opposite().ok_or(tick_tack_toe::Error::NotASide)?;
+        let ai_player = if self.perfect_ai { Player::OptimalAi(ai) } else { Player::RandomAi(ai) };
+
+        let mut board = Board::new(self.size, self.win_length);
+        for (row, col) in &self.handicap {
+            board.set(ai, *row, *col)?;
+        }
+
+        let human_index = if self.human == Tile::Cross { 0 } else { 1 };
+        let players = match human_index {
+            0 => [Player::Human(self.human), ai_player],
+            _ => [ai_player, Player::Human(self.human)],
+        };
+
+        let first = if self.handicap.is_empty() { 0 } else { human_index };
+        Ok((players, board, first))
+    }
+}
+
+/// One player's result for the challenge of `date`.
+pub struct DailyResult {
+    pub date: String,
+    pub name: String,
+    pub outcome: Outcome,
+    pub moves: usize,
+}
+
+pub fn save_result(result: &DailyResult) -> io::Result<()> {
+    fs::create_dir_all(data_dir())?;
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(data_dir().join(RESULTS_FILE))?;
+    writeln!(file, "{}\t{}\t{}\t{}", result.date, result.name, result.outcome.name(), result.moves)
+}
+
+/// The saved results for `date`, best first: wins before draws before losses, fewer moves first.
+pub fn results(date: &str) -> io::Result<Vec<DailyResult>> {
+    let text = match fs::read_to_string(data_dir().join(RESULTS_FILE)) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+
+    let mut results: Vec<DailyResult> = text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [day, name, outcome, moves] = fields[..] else { return None };
+            let outcome = [Outcome::Win, Outcome::Loss, Outcome::Draw].into_iter().find(|o| o.name() == outcome)?;
+
+            Some(DailyResult { date: day.to_string(), name: name.to_string(), outcome, moves: moves.parse().ok()? })
+        })
+        .filter(|r| r.date == date)
+        .collect();
+
+    let rank = |outcome| match outcome {
+        Outcome::Win  => 0,
+        Outcome::Draw => 1,
+        Outcome::Loss => 2,
+    };
+    results.sort_by_key(|r| (rank(r.outcome), r.moves));
+    Ok(results)
+}
+
+fn days_today() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / (24 * 60 * 60)) as i64
+}
+
+/// Today's date the way game records write it, e.g. `2026.10.14`.
+pub fn record_date() -> String {
+    let (year, month, day) = civil_from_days(days_today());
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+/// Converts days since 1970-01-01 to a `(year, month, day)` date in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{civil_from_days, Challenge};
+
+    #[test]
+    fn challenge_for_day() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20_740), (2026, 10, 14));
+
+        let challenge = Challenge::for_day(20_740);
+        assert_eq!(challenge, Challenge::for_day(20_740));
+        assert_eq!(challenge.date, "2026-10-14");
+
+        for day in 20_000..20_100 {
+            let challenge = Challenge::for_day(day);
+            let (_, board, _) = challenge.start().unwrap();
+            assert_eq!(board.board_status(), tick_tack_toe::board::BoardStatus::Continue);
+        }
+    }
+}
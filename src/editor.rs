@@ -0,0 +1,164 @@
+//! Setting up a position by hand, to analyze it or play on from it.
+
+use crate::{screen, setup};
+use tick_tack_toe::board::{Board, BoardStatus, Tile};
+use tick_tack_toe::coords;
+
+const HELP: &str = "\
+  x TILE, o TILE   Put a mark on a tile, e.g. \"x b2\"
+  block TILE       Block a tile, so nobody can play on it or make a row through it
+  clear TILE       Empty a tile
+  reset            Empty the whole board
+  position NOTATION
+                   Load a position such as \"x../.o./..#\", # for a blocked tile,
+                   e.g. one copied in a game
+  turn x, turn o   Choose the side to move
+  analyze          Show the value of every move for the side to move
+  play             Play on from this position
+  q, quit          Leave the editor
+  ?, help          Show this help";
+
+/// Edits `board` until the position is played or the editor is left, returning the position and
+/// the side to move for `play`.
+pub fn run(mut board: Board) -> Option<(Board, Tile)> {
+    let theme = screen::theme();
+    let mut to_move = Tile::Cross;
+    println!("Set up the position, type ? for help.");
+
+    loop {
+        print!("{}", board.render(&theme));
+        println!("Position {}, {:?} to move.", board.notation(), to_move);
+        let input = setup::ask("Edit")?.to_lowercase();
+        let (command, argument) = input.split_once(' ').unwrap_or((input.as_str(), ""));
+
+        let result = match command {
+            "x"     => place(&mut board, Tile::Cross, argument),
+            "o"     => place(&mut board, Tile::Nought, argument),
+            "block" => place(&mut board, Tile::Blocked, argument),
+            "clear" => place(&mut board, Tile::Empty, argument),
+            "reset" => {
+                board = Board::new(board.length(), board.win_row_length());
+                Ok(())
+            }
+            "position" => Board::from_notation(argument, board.win_row_length().min(argument.split('/').count()))
+                .map(|position| board = position)
+                .map_err(|err| err.to_string()),
+            "turn" => match argument.trim().chars().next().and_then(Tile::from_char) {
+                Some(side) if side.is_player() => {
+                    to_move = side;
+                    Ok(())
+                }
+                _ => Err("Say whose turn it is, \"turn x\" or \"turn o\".".to_string()),
+            },
+            "analyze" => check(&board, to_move).map(|()| analyze(&board, to_move)),
+            "play"    => match check(&board, to_move) {
+                Ok(()) if board.board_status() != BoardStatus::Continue => Err("The game is already over.".to_string()),
+                Ok(()) => return Some((board, to_move)),
+                Err(err) => Err(err),
+            },
+            "q" | "quit" => return None,
+            "?" | "help" => {
+                println!("{}", HELP);
+                Ok(())
+            }
+            _ => Err(format!("'{}' isn't an editor command, type ? for help.", input)),
+        };
+        if let Err(err) = result {
+            println!("{}", err);
+        }
+
+        // With unequal marks it can only be one side's turn, with equal ones it's up to the user.
+        to_move = match marks(&board, Tile::Cross).cmp(&marks(&board, Tile::Nought)) {
+            std::cmp::Ordering::Greater => Tile::Nought,
+            std::cmp::Ordering::Less    => Tile::Cross,
+            std::cmp::Ordering::Equal   => to_move,
+        };
+    }
+}
+
+/// Puts `tile` on the tile named by `argument`, replacing whatever was there.
+fn place(board: &mut Board, tile: Tile, argument: &str) -> Result<(), String> {
+    let (row, col) = coords::parse(argument, board.length())?;
+    board.clear(row, col);
+    if tile != Tile::Empty {
+        board.set(tile, row, col).map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+fn analyze(board: &Board, to_move: Tile) {
+    for ((row, col), value) in board.move_values(to_move) {
+        let outcome = match value {
+            1  => "wins",
+            -1 => "loses",
+            _  => "draws",
+        };
+        println!("  {:<8} {}", coords::format(row, col), outcome);
+    }
+}
+
+/// Whether the position can come up in a game with `to_move` to play: the sides take turns, so
+/// their marks differ by at most one, and play stops at the first completed row.
+pub fn check(board: &Board, to_move: Tile) -> Result<(), String> {
+    let (crosses, noughts) = (marks(board, Tile::Cross), marks(board, Tile::Nought));
+
+    if crosses.abs_diff(noughts) > 1 {
+        return Err("One side can only have one mark more than the other.".to_string());
+    }
+    if (to_move == Tile::Cross && crosses > noughts) || (to_move == Tile::Nought && noughts > crosses) {
+        return Err(format!("{:?} has more marks, so it isn't its turn.", to_move));
+    }
+
+    let won = |side| only(board, side).winning_line().is_some();
+    match (won(Tile::Cross), won(Tile::Nought)) {
+        (true, true) => Err("Only one side can have completed a row.".to_string()),
+        (true, _) if to_move == Tile::Nought => Ok(()),
+        (_, true) if to_move == Tile::Cross  => Ok(()),
+        (true, _) | (_, true) => Err(format!("{:?} has already won, so it can't be its turn.", to_move)),
+        _ => Ok(()),
+    }
+}
+
+/// Whose turn it is in a game Cross started: the side with fewer marks, Cross when they're even.
+pub fn side_to_move(board: &Board) -> Tile {
+    if marks(board, Tile::Cross) > marks(board, Tile::Nought) { Tile::Nought } else { Tile::Cross }
+}
+
+fn marks(board: &Board, side: Tile) -> usize {
+    (0..board.length())
+        .flat_map(|row| (0..board.length()).map(move |col| (row, col)))
+        .filter(|&(row, col)| board.get(row, col) == Some(side))
+        .count()
+}
+
+/// `board` with only the marks of `side`.
+fn only(board: &Board, side: Tile) -> Board {
+    let mut only = Board::new(board.length(), board.win_row_length()).with_shape(board.shape());
+    for row in 0..board.length() {
+        for col in 0..board.length() {
+            if board.get(row, col) == Some(side) {
+                let _ = only.set(side, row, col);
+            }
+        }
+    }
+    only
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check;
+    use tick_tack_toe::board::{Board, Tile::*};
+
+    #[test]
+    fn check_positions() {
+        let board = |notation| Board::from_notation(notation, 3).unwrap();
+
+        assert_eq!(check(&board("X../.O./..X"), Nought), Ok(()));
+        assert!(check(&board("X../.O./..X"), Cross).is_err());
+        assert_eq!(check(&board("O../.../..."), Cross), Ok(()));
+        assert!(check(&board("XX./.../..X"), Nought).is_err());
+        assert_eq!(check(&board("XXX/OO./..."), Nought), Ok(()));
+        assert!(check(&board("XXX/OO./..."), Cross).is_err());
+        assert!(check(&board("XXX/OOO/X.."), Nought).is_err());
+    }
+}
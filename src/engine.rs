@@ -0,0 +1,159 @@
+//! Moves chosen by an engine on another machine: the `bestMove` of a `serve` server, asked
+//! over one connection that is kept open, so the search can run on a faster machine than the one
+//! the game is played on.
+
+use crate::jsonrpc;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::Mutex;
+use std::time::Duration;
+use tick_tack_toe::board::{Board, Shape, Tile};
+use tick_tack_toe::json::Json;
+use tick_tack_toe::log;
+
+/// How long to wait for the engine to answer before giving up on it.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct Engine {
+    pub address: String,
+    /// `None` after the connection failed, it is made again for the next move.
+    connection: Mutex<Option<BufReader<TcpStream>>>,
+}
+impl Engine {
+    /// The engine's move for `side`, `None` when it can't be reached, answers with an error or
+    /// names a tile that can't be played.
+    pub fn choose(&self, board: &Board, side: Tile) -> Option<(usize, usize)> {
+        let (row, col) = match self.best_move(board, side) {
+            Ok(m) => m,
+            Err(err) => {
+                log::error!("the engine at {} failed: {}", self.address, err);
+                return None;
+            }
+        };
+        if board.get(row, col) != Some(Tile::Empty) {
+            log::warn!("the engine at {} tried to play the unavailable tile {}, {}", self.address, col, row);
+            return None;
+        }
+        Some((row, col))
+    }
+
+    fn best_move(&self, board: &Board, side: Tile) -> io::Result<(usize, usize)> {
+        // `bestMove` searches square boards only.
+        if board.shape() != Shape::Square {
+            return Err(io::Error::other("engines only play square boards"));
+        }
+        let mut params = jsonrpc::state(board, side);
+        if let Json::Object(fields) = &mut params {
+            fields.retain(|(name, _)| name == "board" || name == "toMove");
+            fields.push(("winLength".to_string(), Json::from(board.win_row_length())));
+        }
+        let request = Json::object([
+            ("jsonrpc", Json::from("2.0")),
+            ("id", Json::from(1usize)),
+            ("method", Json::from("bestMove")),
+            ("params", params),
+        ]);
+
+        let mut connection = self.connection.lock().unwrap_or_else(|err| err.into_inner());
+        // The server may have closed the connection while a human was thinking, so a failed
+        // request is sent once more on a new one.
+        let reply = match connection.as_mut().map(|c| exchange(c, &request)) {
+            Some(Ok(reply)) => reply,
+            _ => {
+                *connection = None;
+                let mut fresh = open(&self.address)?;
+                let reply = exchange(&mut fresh, &request)?;
+                *connection = Some(fresh);
+                reply
+            }
+        };
+
+        if let Some(message) = reply.get("error").and_then(|error| error.get("message")).and_then(Json::as_str) {
+            return Err(io::Error::other(message.to_string()));
+        }
+        let result = reply.get("result");
+        let field = |name| result.and_then(|result| result.get(name)).and_then(Json::as_usize);
+        match (field("row"), field("col")) {
+            (Some(row), Some(col)) => Ok((row, col)),
+            _ => Err(io::Error::other("the answer has no row and col")),
+        }
+    }
+}
+
+/// Engines are told apart by their address.
+impl PartialEq for Engine {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+    }
+}
+
+impl fmt::Debug for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Engine({})", self.address)
+    }
+}
+
+/// Connects to the engine at `address`, e.g. `10.0.0.2:7878`. Engines stay connected until the
+/// program exits.
+pub fn connect(address: &str) -> Result<&'static Engine, String> {
+    let connection = open(address).map_err(|err| format!("Couldn't connect to the engine at {}: {}", address, err))?;
+    Ok(Box::leak(Box::new(Engine { address: address.to_string(), connection: Mutex::new(Some(connection)) })))
+}
+
+fn open(address: &str) -> io::Result<BufReader<TcpStream>> {
+    let stream = TcpStream::connect(address)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    Ok(BufReader::new(stream))
+}
+
+/// Sends one request and reads the answer to it.
+fn exchange(connection: &mut BufReader<TcpStream>, request: &Json) -> io::Result<Json> {
+    writeln!(connection.get_mut(), "{}", request)?;
+    let mut line = String::new();
+    if connection.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Json::parse(line.trim()).map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::connect;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use tick_tack_toe::board::{Board, Tile::*};
+
+    #[test]
+    fn remote_moves() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut requests = Vec::new();
+            for answer in [r#"{"row":0,"col":2}"#, r#"{"row":0,"col":0}"#] {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                requests.push(line);
+                writeln!(&stream, r#"{{"jsonrpc":"2.0","id":1,"result":{}}}"#, answer).unwrap();
+            }
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            writeln!(&stream, r#"{{"jsonrpc":"2.0","id":1,"error":{{"code":-32000,"message":"No legal moves."}}}}"#).unwrap();
+            requests
+        });
+
+        let engine = connect(&address).unwrap();
+        let board = Board::from_notation("XX./OO./...", 3).unwrap();
+        assert_eq!(engine.choose(&board, Cross), Some((0, 2)));
+        // Taken tiles and errors are no move.
+        assert_eq!(engine.choose(&board, Cross), None);
+        assert_eq!(engine.choose(&board, Cross), None);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0], "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"bestMove\",\"params\":{\"board\":[\"XX \",\"OO \",\"   \"],\"toMove\":\"X\",\"winLength\":3}}\n");
+        assert!(connect("127.0.0.1:1").unwrap_err().starts_with("Couldn't connect to the engine at 127.0.0.1:1"));
+    }
+}
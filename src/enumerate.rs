@@ -0,0 +1,66 @@
+//! Every legal position that can come up in a game, for statistics, tablebases and exhaustive
+//! tests.
+
+use crate::board::{Board, BoardStatus, Tile};
+use std::collections::HashSet;
+
+/// Calls `visit` with every position reachable from `start` with `side` to move, the start and
+/// finished games included, and the side to move in each. Positions that are turned or mirrored
+/// versions of one already visited are skipped when `distinct` is set, the first one found is
+/// visited in its canonical form. Returns how many positions were visited.
+///
+/// The positions come in order of the number of moves made, and only those with the same number
+/// are kept, so memory only grows with the widest level rather than the whole game.
+pub fn positions(start: &Board, side: Tile, distinct: bool, mut visit: impl FnMut(&Board, Tile)) -> usize {
+    let normalize = |board: Board| if distinct { board.canonical_form() } else { board };
+    let mut level = vec![normalize(start.clone())];
+    let mut to_move = side;
+    let mut count = 0;
+
+    while !level.is_empty() {
+        let Some(other) = to_move.opposite() else { break };
+        let mut seen = HashSet::new();
+        let mut next = Vec::new();
+
+        for board in &level {
+            visit(board, to_move);
+            count += 1;
+            if board.board_status() != BoardStatus::Continue {
+                continue;
+            }
+            for (row, col) in board.empty_tiles() {
+                let mut child = board.clone();
+                let _ = child.set(to_move, row, col);
+                let child = normalize(child);
+                if seen.insert(child.notation()) {
+                    next.push(child);
+                }
+            }
+        }
+        level = next;
+        to_move = other;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::positions;
+    use crate::board::{Board, BoardStatus, Tile::*};
+
+    #[test]
+    fn enumerate() {
+        assert_eq!(positions(&Board::new(3, 3), Cross, false, |_, _| ()), 5478);
+
+        let mut finished = 0;
+        let distinct = positions(&Board::new(3, 3), Cross, true, |board, _| {
+            finished += usize::from(board.board_status() != BoardStatus::Continue);
+        });
+        // The usual 138 finished games, and 5 with one tile left that can't win, already drawn here.
+        assert_eq!((distinct, finished), (765, 143));
+
+        let mut sides = Vec::new();
+        positions(&Board::from_notation("XO/X.", 2).unwrap(), Nought, false, |board, side| sides.push((board.notation(), side)));
+        assert_eq!(sides, [("XO/X.".to_string(), Nought)]);
+    }
+}
@@ -0,0 +1,371 @@
+use crate::board::{Board, BoardStatus, Tile};
+use crate::coords;
+use crate::variant::{self, Variant};
+use crate::{Error, Result};
+use std::sync::mpsc;
+
+/// A Morris game is drawn when a position comes up this many times with the same side to move.
+const REPETITIONS: usize = 3;
+
+/// A mark placed on `(row, col)`, or in the movement phase of Morris a piece moved there `from`
+/// a tile next to it.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Move {
+    pub tile: Tile,
+    pub row: usize,
+    pub col: usize,
+    pub from: Option<(usize, usize)>,
+}
+impl Move {
+    /// E.g. `Xb2`, or `Xa1-b2` for a piece moved from a1.
+    pub fn notation(&self) -> String {
+        match self.from {
+            Some((row, col)) => format!("{}{}-{}", self.tile.char(), coords::notation(row, col), coords::notation(self.row, self.col)),
+            None => format!("{}{}", self.tile.char(), coords::notation(self.row, self.col)),
+        }
+    }
+
+    /// E.g. `1, 2`, or `0, 0 to 1, 2` for a piece moved, the way moves are entered.
+    pub fn describe(&self) -> String {
+        match self.from {
+            Some((row, col)) => format!("{} to {}", coords::format(row, col), coords::format(self.row, self.col)),
+            None => coords::format(self.row, self.col),
+        }
+    }
+}
+
+/// Gets told what happens in a game it was added to with `Game::add_observer`, so loggers,
+/// renderers and statistics can follow games without changes to the game loop.
+pub trait GameObserver {
+    fn on_move(&mut self, _m: &Move, _board: &Board) {}
+
+    /// The status changed, after a move or when moves were undone.
+    fn on_status_change(&mut self, _status: BoardStatus) {}
+
+    /// A move ended the game with `status`.
+    fn on_game_end(&mut self, _status: BoardStatus) {}
+}
+
+/// What a `GameObserver` is told, as a value that can be sent to another thread.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GameEvent {
+    Moved(Move),
+    StatusChanged(BoardStatus),
+    Ended(BoardStatus),
+}
+
+/// Sends every event down the channel, once the receiver is gone events are dropped.
+impl GameObserver for mpsc::Sender<GameEvent> {
+    fn on_move(&mut self, m: &Move, _board: &Board) {
+        let _ = self.send(GameEvent::Moved(*m));
+    }
+
+    fn on_status_change(&mut self, status: BoardStatus) {
+        let _ = self.send(GameEvent::StatusChanged(status));
+    }
+
+    fn on_game_end(&mut self, status: BoardStatus) {
+        let _ = self.send(GameEvent::Ended(status));
+    }
+}
+
+/// A board together with the moves that were played on it.
+pub struct Game {
+    start: Board,
+    board: Board,
+    history: Vec<Move>,
+    variant: Variant,
+    observers: Vec<Box<dyn GameObserver + Send>>,
+}
+impl Game {
+    pub fn new(board: Board) -> Self {
+        Self::with_variant(board, Variant::Standard)
+    }
+
+    /// A game of `variant` on `board`, which takes the shape the variant is played on.
+    pub fn with_variant(board: Board, variant: Variant) -> Self {
+        let board = board.with_shape(variant.shape())
+            .with_decay(variant.decay())
+            .with_pieces(variant.pieces())
+            .with_scoring(variant.scoring());
+        Self { start: board.clone(), board, history: Vec::new(), variant, observers: Vec::new() }
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// The tiles `side` can't see, none but in fog of war before the game ends.
+    pub fn hidden(&self, side: Tile) -> Vec<(usize, usize)> {
+        match self.variant {
+            Variant::Fog if self.status() == BoardStatus::Continue => variant::fogged(&self.board, side),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The board as `side` sees it, with the tiles it can't see empty.
+    pub fn view(&self, side: Tile) -> Board {
+        let mut board = self.board.clone();
+        for (row, col) in self.hidden(side) {
+            board.clear(row, col);
+        }
+        board
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn GameObserver + Send>) {
+        self.observers.push(observer);
+    }
+
+    /// A stream of the events of this game, for frontends that would rather receive than poll.
+    pub fn subscribe(&mut self) -> mpsc::Receiver<GameEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.add_observer(Box::new(sender));
+        receiver
+    }
+
+    /// The position before the first move in the history.
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Mutable access for searches, which leave the board as they found it.
+    pub fn board_mut(&mut self) -> &mut Board {
+        &mut self.board
+    }
+
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// How the game stands. In Morris a side with no piece it can move loses, and the game is a
+    /// draw once the same position comes up a third time, as it would go round in circles.
+    pub fn status(&self) -> BoardStatus {
+        let status = self.board.board_status();
+        match self.history.last() {
+            Some(last) if status == BoardStatus::Continue && self.board.pieces().is_some() => {
+                let stuck = last.tile.opposite().is_some_and(|side| variant::moves(&self.board, side).is_empty());
+                if stuck {
+                    BoardStatus::Winner(last.tile)
+                } else if self.repetitions() >= REPETITIONS {
+                    BoardStatus::Tie
+                } else {
+                    status
+                }
+            }
+            _ => status,
+        }
+    }
+
+    /// How often the position on the board came up with the same side to move, this time included.
+    fn repetitions(&self) -> usize {
+        let mut board = self.start.clone();
+        let mut count = usize::from(board == self.board && self.history.len().is_multiple_of(2));
+        for (index, m) in self.history.iter().enumerate() {
+            let _ = board.play(m);
+            if board == self.board && (self.history.len() - index) % 2 == 1 {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Places a mark of `tile` on `(row, col)`.
+    pub fn apply(&mut self, tile: Tile, row: usize, col: usize) -> Result<()> {
+        self.play(Move { tile, row, col, from: None })
+    }
+
+    /// Plays `m`, a mark placed or in Morris a piece moved once all of them are on the board.
+    pub fn play(&mut self, m: Move) -> Result<()> {
+        if !m.tile.is_player() {
+            return Err(Error::NotASide);
+        }
+        if self.status() != BoardStatus::Continue {
+            return Err(Error::GameOver);
+        }
+        match (self.board.must_move(m.tile), m.from) {
+            (true, None)     => return Err(Error::MustMove),
+            (false, Some(_)) => return Err(Error::MustPlace),
+            _ => (),
+        }
+
+        self.board.play(&m)?;
+        self.history.push(m);
+
+        for observer in &mut self.observers {
+            observer.on_move(&m, &self.board);
+        }
+        self.notify_status(BoardStatus::Continue);
+        Ok(())
+    }
+
+    fn notify_status(&mut self, before: BoardStatus) {
+        let status = self.status();
+        if status == before {
+            return;
+        }
+
+        for observer in &mut self.observers {
+            observer.on_status_change(status);
+            if status != BoardStatus::Continue {
+                observer.on_game_end(status);
+            }
+        }
+    }
+
+    /// Takes back moves until it is `side`'s turn again before its last move.
+    pub fn undo_turn(&mut self, side: Tile) -> Result<()> {
+        if !self.history.iter().any(|m| m.tile == side) {
+            return Err(Error::NothingToUndo);
+        }
+
+        let before = self.status();
+        while let Some(m) = self.history.pop() {
+            if m.tile == side {
+                break;
+            }
+        }
+        // Played again from the start, which also brings back the marks that decayed.
+        self.board = self.start.clone();
+        for m in &self.history {
+            let _ = self.board.play(m);
+        }
+        self.notify_status(before);
+        Ok(())
+    }
+
+    /// The index of the first move where `side` threw away a better result, and a move that would
+    /// have kept it. Searches every position `side` moved in, so it is only quick on small boards.
+    pub fn first_mistake(&self, side: Tile) -> Option<(usize, (usize, usize))> {
+        self.mistakes(side).first().map(|m| (m.index, m.better))
+    }
+
+    /// Every move where `side` threw away a better result, searching each position `side` moved in.
+    pub fn mistakes(&self, side: Tile) -> Vec<Mistake> {
+        let mut board = self.start.clone();
+        let mut mistakes = Vec::new();
+
+        for (index, m) in self.history.iter().enumerate() {
+            if m.tile == side {
+                let values = board.move_values(side);
+                let best = values.iter().max_by_key(|(_, value)| *value);
+                let played = values.iter().find(|(pos, _)| *pos == (m.row, m.col));
+                if let (Some(&(better, before)), Some(&(_, after))) = (best, played) {
+                    if after < before {
+                        mistakes.push(Mistake { index, position: board.clone(), before, after, better });
+                    }
+                }
+            }
+            if board.play(m).is_err() {
+                break;
+            }
+        }
+        mistakes
+    }
+}
+
+/// A move that made the result worse for the side that played it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Mistake {
+    /// The move's index in the history.
+    pub index: usize,
+    /// The board before the move.
+    pub position: Board,
+    /// The value of the position before and after the move, 1 is a forced win and -1 a forced loss.
+    pub before: i8,
+    pub after: i8,
+    /// A move that would have kept the better result.
+    pub better: (usize, usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Game, GameEvent, GameObserver, Move};
+    use crate::board::{Board, BoardStatus, Tile::*};
+    use crate::variant::Variant;
+    use crate::Error;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn undo_turn() {
+        let mut g = Game::new(Board::new(3, 3));
+        assert!(g.undo_turn(Cross).is_err());
+
+        g.apply(Nought, 0, 0).unwrap();
+        g.apply(Cross, 1, 1).unwrap();
+        g.apply(Nought, 2, 2).unwrap();
+        assert!(g.apply(Cross, 1, 1).is_err());
+
+        g.undo_turn(Cross).unwrap();
+        assert_eq!(g.history().len(), 1);
+        assert_eq!(g.board().get(1, 1), Some(Empty));
+        assert_eq!(g.board().get(2, 2), Some(Empty));
+        assert_eq!(g.board().get(0, 0), Some(Nought));
+    }
+
+    #[test]
+    fn morris() {
+        let mut g = Game::with_variant(Board::new(3, 3), Variant::Morris(3));
+        assert_eq!(g.play(Move { tile: Cross, row: 1, col: 1, from: Some((0, 0)) }), Err(Error::MustPlace));
+
+        let mut g = Game::with_variant(Board::from_notation("XO./XO./OX.", 3).unwrap(), Variant::Morris(3));
+        assert_eq!(g.apply(Cross, 2, 2), Err(Error::MustMove));
+        let shuffle = [(Cross, (2, 1), (2, 2)), (Nought, (1, 1), (1, 2)), (Cross, (2, 2), (2, 1)), (Nought, (1, 2), (1, 1))];
+        for (tile, from, (row, col)) in shuffle.iter().chain(&shuffle).copied() {
+            assert_eq!(g.status(), BoardStatus::Continue);
+            g.play(Move { tile, row, col, from: Some(from) }).unwrap();
+        }
+        // The starting position a third time.
+        assert_eq!(g.status(), BoardStatus::Tie);
+    }
+
+    #[test]
+    fn first_mistake() {
+        let mut g = Game::new(Board::from_notation(".../.X./...", 3).unwrap());
+        for (tile, row, col) in [(Nought, 0, 1), (Cross, 0, 0), (Nought, 2, 2), (Cross, 2, 0)] {
+            g.apply(tile, row, col).unwrap();
+        }
+
+        assert_eq!(g.first_mistake(Cross), None);
+        assert_eq!(g.first_mistake(Nought), Some((0, (2, 2))));
+        let mistakes = g.mistakes(Nought);
+        assert_eq!(mistakes.iter().map(|m| (m.index, m.before, m.after)).collect::<Vec<_>>(), [(0, 0, -1)]);
+        assert_eq!(mistakes[0].position.notation(), ".../.X./...");
+    }
+
+    #[test]
+    fn observers() {
+        struct Events(Arc<Mutex<Vec<String>>>);
+        impl GameObserver for Events {
+            fn on_move(&mut self, m: &Move, _: &Board) {
+                self.0.lock().unwrap().push(format!("{:?}", m.tile));
+            }
+            fn on_status_change(&mut self, status: BoardStatus) {
+                self.0.lock().unwrap().push(format!("{:?}", status));
+            }
+            fn on_game_end(&mut self, _: BoardStatus) {
+                self.0.lock().unwrap().push("end".to_string());
+            }
+        }
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut g = Game::new(Board::from_notation("XX./OO./...", 3).unwrap());
+        g.add_observer(Box::new(Events(events.clone())));
+
+        g.apply(Cross, 0, 2).unwrap();
+        g.undo_turn(Cross).unwrap();
+        assert_eq!(*events.lock().unwrap(), ["Cross", "Winner(Cross)", "end", "Continue"]);
+
+        let events = g.subscribe();
+        g.apply(Cross, 0, 2).unwrap();
+        let events: Vec<GameEvent> = events.try_iter().collect();
+        assert_eq!(events, [
+            GameEvent::Moved(Move { tile: Cross, row: 0, col: 2, from: None }),
+            GameEvent::StatusChanged(BoardStatus::Winner(Cross)),
+            GameEvent::Ended(BoardStatus::Winner(Cross)),
+        ]);
+    }
+}
@@ -0,0 +1,356 @@
+//! Boards as raster images, for bots and reports that attach pictures instead of text, and whole
+//! games as animated GIFs. The encoders are written out here; PNGs are left uncompressed, board
+//! images are small.
+
+use crate::board::{Board, Tile};
+use crate::game::Game;
+use std::collections::HashMap;
+
+const CELL: usize = 60;
+const MARGIN: usize = 10;
+
+const BACKGROUND: [u8; 3] = [0xff, 0xff, 0xff];
+const HIGHLIGHT: [u8; 3] = [0xff, 0xe6, 0x80];
+const INK: [u8; 3] = [0x22, 0x22, 0x22];
+
+/// Every color `draw` uses, the palette of GIFs.
+const PALETTE: [[u8; 3]; 4] = [BACKGROUND, HIGHLIGHT, INK, [0, 0, 0]];
+
+/// RGB pixels, row by row.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+impl Canvas {
+    pub fn new(width: usize, height: usize, color: [u8; 3]) -> Self {
+        Self { width, height, pixels: vec![color; width * height] }
+    }
+
+    pub fn fill(&mut self, (x, y): (usize, usize), (width, height): (usize, usize), color: [u8; 3]) {
+        for row in y..(y + height).min(self.height) {
+            for col in x..(x + width).min(self.width) {
+                self.pixels[row * self.width + col] = color;
+            }
+        }
+    }
+
+    /// Colors the pixels within `radius` of `center` for which `inside` holds.
+    fn paint(&mut self, center: (f64, f64), radius: f64, color: [u8; 3], inside: impl Fn(f64, f64) -> bool) {
+        let (left, top) = ((center.0 - radius).max(0.0) as usize, (center.1 - radius).max(0.0) as usize);
+        let (right, bottom) = ((center.0 + radius) as usize, (center.1 + radius) as usize);
+
+        for y in top..=bottom.min(self.height - 1) {
+            for x in left..=right.min(self.width - 1) {
+                if inside(x as f64 + 0.5, y as f64 + 0.5) {
+                    self.pixels[y * self.width + x] = color;
+                }
+            }
+        }
+    }
+
+    pub fn line(&mut self, from: (f64, f64), to: (f64, f64), thickness: f64, color: [u8; 3]) {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = (dx * dx + dy * dy).sqrt().max(f64::EPSILON);
+        let center = ((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0);
+
+        self.paint(center, length / 2.0 + thickness, color, |x, y| {
+            // The nearest point of the segment, then the distance to it.
+            let t = (((x - from.0) * dx + (y - from.1) * dy) / (length * length)).clamp(0.0, 1.0);
+            let (nx, ny) = (from.0 + t * dx - x, from.1 + t * dy - y);
+            (nx * nx + ny * ny).sqrt() <= thickness / 2.0
+        });
+    }
+
+    pub fn ring(&mut self, center: (f64, f64), radius: f64, thickness: f64, color: [u8; 3]) {
+        self.paint(center, radius + thickness, color, |x, y| {
+            let distance = ((x - center.0).powi(2) + (y - center.1).powi(2)).sqrt();
+            (distance - radius).abs() <= thickness / 2.0
+        });
+    }
+}
+
+/// Draws `board` like `Board::to_svg_highlighted`, with crosses and noughts as shapes.
+pub fn draw(board: &Board, highlight: &[(usize, usize)]) -> Canvas {
+    let size = board.length() * CELL + 2 * MARGIN;
+    let mut canvas = Canvas::new(size, size, BACKGROUND);
+
+    for &(row, col) in highlight {
+        canvas.fill((MARGIN + col * CELL, MARGIN + row * CELL), (CELL, CELL), HIGHLIGHT);
+    }
+    for i in 1..board.length() {
+        canvas.fill((MARGIN + i * CELL - 1, MARGIN), (2, size - 2 * MARGIN), INK);
+        canvas.fill((MARGIN, MARGIN + i * CELL - 1), (size - 2 * MARGIN, 2), INK);
+    }
+
+    let (cell, padding) = (CELL as f64, CELL as f64 / 5.0);
+    for row in 0..board.length() {
+        for col in 0..board.length() {
+            let (left, top) = ((MARGIN + col * CELL) as f64, (MARGIN + row * CELL) as f64);
+            match board.get(row, col) {
+                Some(Tile::Cross) => {
+                    let (near, far) = (padding, cell - padding);
+                    canvas.line((left + near, top + near), (left + far, top + far), 6.0, INK);
+                    canvas.line((left + far, top + near), (left + near, top + far), 6.0, INK);
+                }
+                Some(Tile::Nought) => canvas.ring((left + cell / 2.0, top + cell / 2.0), cell / 2.0 - padding, 6.0, INK),
+                _ => (),
+            }
+        }
+    }
+    canvas
+}
+
+/// `canvas` as a PNG file.
+pub fn png(canvas: &Canvas) -> Vec<u8> {
+    // Every row starts with its filter type, 0 for none.
+    let mut raw = Vec::with_capacity(canvas.height * (canvas.width * 3 + 1));
+    for row in canvas.pixels.chunks(canvas.width.max(1)) {
+        raw.push(0);
+        raw.extend(row.iter().flatten());
+    }
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&(canvas.width as u32).to_be_bytes());
+    header.extend_from_slice(&(canvas.height as u32).to_be_bytes());
+    // 8 bits per channel, RGB, and the only compression, filter and interlace methods there are.
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut out, b"IHDR", &header);
+    chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+/// Every position of `game` from the start, each with its last move highlighted and the last with
+/// the winning line, as an animated GIF showing each frame for `delay` hundredths of a second.
+pub fn gif(game: &Game, delay: u16) -> Vec<u8> {
+    let mut board = game.start().clone();
+    let mut frames = vec![draw(&board, &[])];
+    for m in game.history() {
+        let _ = board.play(m);
+        frames.push(draw(&board, &[(m.row, m.col)]));
+    }
+    if let Some(line) = board.winning_line() {
+        frames.push(draw(&board, &line));
+    }
+    animation(&frames, delay)
+}
+
+/// `frames`, all the size of the first, as a looping GIF.
+pub fn animation(frames: &[Canvas], delay: u16) -> Vec<u8> {
+    let (width, height) = frames.first().map_or((0, 0), |f| (f.width as u16, f.height as u16));
+
+    let mut out = b"GIF89a".to_vec();
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+    // A global color table of 2^(1 + 1) colors, background color 0 and square pixels.
+    out.extend_from_slice(&[0xf1, 0, 0]);
+    out.extend(PALETTE.iter().flatten());
+    // Loop forever.
+    out.extend_from_slice(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+    for (i, frame) in frames.iter().enumerate() {
+        // The last frame stays up three times as long, before the animation starts over.
+        let delay = if i + 1 == frames.len() { delay.saturating_mul(3) } else { delay };
+        out.extend_from_slice(&[0x21, 0xf9, 4, 0]);
+        out.extend_from_slice(&delay.to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+
+        out.push(0x2c);
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.extend_from_slice(&width.to_le_bytes());
+        out.extend_from_slice(&height.to_le_bytes());
+        out.push(0);
+
+        let indices: Vec<u8> = frame.pixels.iter()
+            .map(|color| PALETTE.iter().position(|c| c == color).unwrap_or(0) as u8)
+            .collect();
+        out.push(MIN_CODE_SIZE);
+        for block in lzw(&indices).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend_from_slice(block);
+        }
+        out.push(0);
+    }
+    out.push(0x3b);
+    out
+}
+
+/// Bits per palette index in the LZW stream, at least 2.
+const MIN_CODE_SIZE: u8 = 2;
+
+/// The variable length LZW codes of GIF image data, packed least significant bit first.
+fn lzw(indices: &[u8]) -> Vec<u8> {
+    const MAX_CODES: u16 = 4096;
+    let clear = 1u16 << MIN_CODE_SIZE;
+    let end = clear + 1;
+
+    let mut out = Vec::new();
+    let (mut bits, mut pending) = (0u32, 0u32);
+    let mut size = u32::from(MIN_CODE_SIZE) + 1;
+    let mut emit = |code: u16, size: u32| {
+        pending |= u32::from(code) << bits;
+        bits += size;
+        while bits >= 8 {
+            out.push(pending as u8);
+            pending >>= 8;
+            bits -= 8;
+        }
+    };
+
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    emit(clear, size);
+
+    if let Some((&first, rest)) = indices.split_first() {
+        let mut prefix = u16::from(first);
+        for &index in rest {
+            if let Some(&code) = codes.get(&(prefix, index)) {
+                prefix = code;
+                continue;
+            }
+
+            emit(prefix, size);
+            if next < MAX_CODES {
+                codes.insert((prefix, index), next);
+                next += 1;
+                // The decoder adds its codes one step later, so it grows right before the next code.
+                if u32::from(next) > 1 << size && size < 12 {
+                    size += 1;
+                }
+            } else {
+                emit(clear, size);
+                codes.clear();
+                next = end + 1;
+                size = u32::from(MIN_CODE_SIZE) + 1;
+            }
+            prefix = u16::from(index);
+        }
+        emit(prefix, size);
+    }
+    emit(end, size);
+    emit(0, 7);
+    out
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// A zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(u8::from(blocks.peek().is_none()));
+        let length = block.len() as u16;
+        out.extend_from_slice(&length.to_le_bytes());
+        out.extend_from_slice(&(!length).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32, draw, gif, lzw, png, INK, HIGHLIGHT};
+    use crate::board::{Board, Tile::*};
+    use crate::game::Game;
+
+    /// A GIF decoder's reading of `data`, to check the encoder against.
+    fn unlzw(data: &[u8]) -> Vec<u8> {
+        let (clear, end) = (4usize, 5usize);
+        let mut table: Vec<Vec<u8>> = (0..=end).map(|i| vec![i as u8]).collect();
+        let (mut size, mut position, mut previous) = (3, 0, None::<usize>);
+        let mut out = Vec::new();
+
+        loop {
+            let code = (0..size).fold(0, |code, bit| code | usize::from(data[(position + bit) / 8] >> ((position + bit) % 8) & 1) << bit);
+            position += size;
+            if code == clear {
+                table.truncate(end + 1);
+                (size, previous) = (3, None);
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+
+            let entry = match table.get(code) {
+                Some(entry) => entry.clone(),
+                None => {
+                    let previous = &table[previous.unwrap()];
+                    [previous.clone(), vec![previous[0]]].concat()
+                }
+            };
+            out.extend_from_slice(&entry);
+            if let Some(previous) = previous {
+                let added = [table[previous].clone(), vec![entry[0]]].concat();
+                table.push(added);
+                if table.len() == 1 << size && size < 12 {
+                    size += 1;
+                }
+            }
+            previous = Some(code);
+        }
+    }
+
+    #[test]
+    fn encode() {
+        let indices: Vec<u8> = (0..20_000u32).map(|i| ((i * i / 7 + i / 13) % 4) as u8).collect();
+        assert_eq!(unlzw(&lzw(&indices)), indices);
+
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+
+        let board = Board::from_notation("X./.O", 2).unwrap();
+        let canvas = draw(&board, &[(1, 1)]);
+        assert_eq!((canvas.width, canvas.height), (140, 140));
+        // The middle of the cross, and the highlighted middle of the nought.
+        assert_eq!(canvas.pixels[40 * 140 + 40], INK);
+        assert_eq!(canvas.pixels[100 * 140 + 100], HIGHLIGHT);
+
+        let file = png(&canvas);
+        assert!(file.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x8c\0\0\0\x8c"));
+        assert!(file.ends_with(b"IEND\xae\x42\x60\x82"));
+
+        let mut game = Game::new(Board::new(3, 3));
+        game.apply(Cross, 1, 1).unwrap();
+        let gif = gif(&game, 100);
+        assert!(gif.starts_with(b"GIF89a\xc8\0\xc8\0") && gif.ends_with(b"\x3b"));
+        // A graphic control block for each position.
+        assert_eq!(gif.windows(3).filter(|w| w == b"\x21\xf9\x04").count(), 2);
+    }
+}
@@ -0,0 +1,76 @@
+//! Ctrl-C handling: the game in progress is written to a recovery file and the terminal is reset
+//! before exiting, instead of the process dying mid-prompt.
+
+use crate::profile::data_dir;
+use std::{fs, path::PathBuf, sync::Mutex};
+
+const RECOVERY_FILE: &str = "recovery.txt";
+
+/// The record of the game in progress, saved if the process is interrupted.
+static SNAPSHOT: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn set_snapshot(record: Option<String>) {
+    *SNAPSHOT.lock().unwrap_or_else(|err| err.into_inner()) = record;
+}
+
+pub fn recovery_path() -> PathBuf {
+    data_dir().join(RECOVERY_FILE)
+}
+
+/// Traps SIGINT. The handler only wakes a watcher thread through a pipe, which does the saving.
+#[cfg(unix)]
+pub fn install() {
+    use std::io::Read;
+    use std::os::unix::io::FromRawFd;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    static WAKE_FD: AtomicI32 = AtomicI32::new(-1);
+
+    extern "C" fn handler(_: libc::c_int) {
+        let byte = 1u8;
+        // SAFETY: write is async-signal-safe and the fd stays open for the whole process.
+        unsafe { libc::write(WAKE_FD.load(Ordering::Relaxed), &byte as *const u8 as *const libc::c_void, 1) };
+    }
+
+    let mut fds = [0; 2];
+    // SAFETY: fds has room for the two descriptors pipe writes.
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        tick_tack_toe::log::warn!("couldn't create the interrupt pipe, Ctrl-C won't save the game");
+        return;
+    }
+    WAKE_FD.store(fds[1], Ordering::Relaxed);
+
+    // SAFETY: the read end is owned by this File alone from here on.
+    let mut wake = unsafe { fs::File::from_raw_fd(fds[0]) };
+    std::thread::spawn(move || {
+        let mut byte = [0];
+        if wake.read_exact(&mut byte).is_ok() {
+            on_interrupt();
+        }
+    });
+
+    // SAFETY: the handler only calls async-signal-safe functions.
+    unsafe { libc::signal(libc::SIGINT, handler as *const () as libc::sighandler_t) };
+}
+
+#[cfg(not(unix))]
+pub fn install() {}
+
+#[cfg(unix)]
+fn on_interrupt() -> ! {
+    // Reset colors and show the cursor again in case a frontend changed them.
+    print!("\x1b[0m\x1b[?25h");
+    crate::cursor::restore();
+    println!();
+
+    let snapshot = SNAPSHOT.lock().unwrap_or_else(|err| err.into_inner()).take();
+    if let Some(record) = snapshot {
+        let saved = fs::create_dir_all(data_dir()).and_then(|_| fs::write(recovery_path(), record));
+        match saved {
+            Ok(()) => println!("Interrupted, the game was saved to {}.", recovery_path().display()),
+            Err(err) => eprintln!("Interrupted, and the game couldn't be saved: {}", err),
+        }
+    }
+
+    std::process::exit(130);
+}
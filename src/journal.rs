@@ -0,0 +1,52 @@
+//! An on-disk journal of the game in progress, so it can be resumed after the process dies.
+//! The journal is a game record that is appended to, one move per line, and gets a `Result`
+//! tag once the game is over.
+
+use crate::profile::data_dir;
+use std::{fs, io, io::Write, path::PathBuf, time::Duration};
+use tick_tack_toe::board::BoardStatus;
+use tick_tack_toe::game::Move;
+use tick_tack_toe::record::{self, Record, Termination};
+
+const JOURNAL_FILE: &str = "journal.txt";
+
+fn path() -> PathBuf {
+    data_dir().join(JOURNAL_FILE)
+}
+
+fn append(line: &str) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new().append(true).open(path())?;
+    writeln!(file, "{}", line)
+}
+
+/// Starts a new journal for `record`, replacing the previous game's.
+pub fn start(record: &Record) -> io::Result<()> {
+    fs::create_dir_all(data_dir())?;
+    fs::write(path(), record.to_string())
+}
+
+/// Appends `m`, with the time it took when known.
+pub fn record_move(m: &Move, time: Option<Duration>) -> io::Result<()> {
+    let mut line = m.notation();
+    if let Some(time) = time {
+        line.push_str(&format!(" {{[%emt {}]}}", record::clock(time)));
+    }
+    append(&line)
+}
+
+/// Appends the `Result` and `Termination` tags, which mark the game as over.
+pub fn finish(status: BoardStatus, termination: Termination) -> io::Result<()> {
+    append(&format!("[Result \"{}\"]", record::result(status)))?;
+    append(&format!("[Termination \"{}\"]", termination.name()))
+}
+
+/// The journaled game, if it was left unfinished.
+pub fn unfinished() -> Option<Record> {
+    let text = fs::read_to_string(path()).ok()?;
+    let record = Record::parse(&text).ok()?;
+
+    match record.tag("Result") {
+        Some(_) => None,
+        None => Some(record),
+    }
+}
@@ -0,0 +1,315 @@
+use std::fmt;
+
+/// How deep arrays and objects may nest, so that input can't run the parser out of stack.
+const MAX_DEPTH: usize = 64;
+
+/// Minimal JSON value, enough for the line based protocols.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, &'static str> {
+        let mut parser = Parser { chars: text.chars().peekable(), depth: 0 };
+
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.chars.next().is_some() {
+            return Err("Trailing characters after JSON value.");
+        }
+
+        Ok(value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Self::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Self::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as usize),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn object<K: Into<String>>(fields: impl IntoIterator<Item = (K, Json)>) -> Json {
+        Self::Object(fields.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Self::String(s.to_string())
+    }
+}
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        Self::Number(n as f64)
+    }
+}
+impl From<i64> for Json {
+    fn from(n: i64) -> Self {
+        Self::Number(n as f64)
+    }
+}
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Null      => write!(f, "null"),
+            Self::Bool(b)   => write!(f, "{}", b),
+            Self::Number(n) => {
+                if n.is_finite() && n.fract() == 0.0 && n.abs() < 1e15 {
+                    write!(f, "{}", *n as i64)
+                } else if n.is_finite() {
+                    write!(f, "{}", n)
+                } else {
+                    write!(f, "null")
+                }
+            }
+            Self::String(s) => write_string(f, s),
+            Self::Array(values) => {
+                write!(f, "[")?;
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 { write!(f, ",")?; }
+                    write!(f, "{}", v)?;
+                }
+                write!(f, "]")
+            }
+            Self::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    if i > 0 { write!(f, ",")?; }
+                    write_string(f, k)?;
+                    write!(f, ":{}", v)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"'  => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    /// The arrays and objects the parser is inside of.
+    depth: usize,
+}
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.chars.peek() {
+            self.chars.next();
+        }
+    }
+
+    fn next_char(&mut self) -> Result<char, &'static str> {
+        self.chars.next().ok_or("Unexpected end of JSON.")
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), &'static str> {
+        for e in expected.chars() {
+            if self.next_char()? != e {
+                return Err("Invalid JSON literal.");
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Json, &'static str> {
+        self.skip_whitespace();
+
+        match self.chars.peek().copied() {
+            Some('n') => self.expect("null").map(|_| Json::Null),
+            Some('t') => self.expect("true").map(|_| Json::Bool(true)),
+            Some('f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') => self.nested(Self::array),
+            Some('{') => self.nested(Self::object),
+            Some('-' | '0'..='9') => self.number(),
+            Some(_) => Err("Unexpected character in JSON."),
+            None => Err("Unexpected end of JSON."),
+        }
+    }
+
+    /// Parses an array or object with `parse`, one level deeper than the value it's in.
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Json, &'static str>) -> Result<Json, &'static str> {
+        if self.depth == MAX_DEPTH {
+            return Err("Nesting too deep.");
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn number(&mut self) -> Result<Json, &'static str> {
+        let mut text = String::new();
+        while let Some(c @ ('-' | '+' | '.' | 'e' | 'E' | '0'..='9')) = self.chars.peek() {
+            text.push(*c);
+            self.chars.next();
+        }
+
+        text.parse()
+            .map(Json::Number)
+            .or(Err("Invalid JSON number."))
+    }
+
+    fn string(&mut self) -> Result<String, &'static str> {
+        self.expect("\"")?;
+
+        let mut s = String::new();
+        loop {
+            match self.next_char()? {
+                '"' => return Ok(s),
+                '\\' => match self.next_char()? {
+                    '"'  => s.push('"'),
+                    '\\' => s.push('\\'),
+                    '/'  => s.push('/'),
+                    'b'  => s.push('\u{8}'),
+                    'f'  => s.push('\u{c}'),
+                    'n'  => s.push('\n'),
+                    'r'  => s.push('\r'),
+                    't'  => s.push('\t'),
+                    'u'  => {
+                        let mut code = self.hex_code()?;
+                        if (0xD800..0xDC00).contains(&code) {
+                            self.expect("\\u")?;
+                            let low = self.hex_code()?;
+                            code = 0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+                        }
+                        s.push(char::from_u32(code).ok_or("Invalid unicode escape.")?);
+                    }
+                    _ => return Err("Invalid escape in JSON string."),
+                },
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn hex_code(&mut self) -> Result<u32, &'static str> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.next_char()?.to_digit(16).ok_or("Invalid unicode escape.")?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn array(&mut self) -> Result<Json, &'static str> {
+        self.expect("[")?;
+
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if let Some(']') = self.chars.peek() {
+            self.chars.next();
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.next_char()? {
+                ',' => continue,
+                ']' => return Ok(Json::Array(values)),
+                _ => return Err("Expected ',' or ']' in JSON array."),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, &'static str> {
+        self.expect("{")?;
+
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if let Some('}') = self.chars.peek() {
+            self.chars.next();
+            return Ok(Json::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.next_char()? {
+                ',' => continue,
+                '}' => return Ok(Json::Object(fields)),
+                _ => return Err("Expected ',' or '}' in JSON object."),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Json;
+
+    #[test]
+    fn parse_and_print() {
+        let text = r#"{"a": [1, 2.5, -3], "b": {"c": "d\"\n"}, "e": true, "f": null}"#;
+        let value = Json::parse(text).unwrap();
+
+        assert_eq!(value.get("a").unwrap().as_array().unwrap()[0].as_usize(), Some(1));
+        assert_eq!(value.get("b").unwrap().get("c").unwrap().as_str(), Some("d\"\n"));
+        assert_eq!(value.to_string(), r#"{"a":[1,2.5,-3],"b":{"c":"d\"\n"},"e":true,"f":null}"#);
+        assert_eq!(Json::parse(&value.to_string()).unwrap(), value);
+
+        assert_eq!(Json::parse(r#""é\ud83d\ude00""#).unwrap(), Json::from("é😀"));
+        assert!(Json::parse("[1, 2").is_err());
+        assert!(Json::parse("{} x").is_err());
+
+        // Deep nesting is an error rather than a stack overflow.
+        assert!(Json::parse(&format!("{}{}", "[".repeat(64), "]".repeat(64))).is_ok());
+        assert_eq!(Json::parse(&format!("{}{}", "[".repeat(65), "]".repeat(65))), Err("Nesting too deep."));
+        assert_eq!(Json::parse(&"[{\"a\":".repeat(100_000)), Err("Nesting too deep."));
+    }
+}
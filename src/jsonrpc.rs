@@ -0,0 +1,415 @@
+use crate::setup::{MAX_PERFECT_AI_SIZE, MAX_SIZE};
+use tick_tack_toe::board::{Board, BoardStatus, Tile};
+use tick_tack_toe::json::Json;
+use tick_tack_toe::log;
+use std::io::{self, BufRead, Write};
+
+const PARSE_ERROR: i64          = -32700;
+const INVALID_REQUEST: i64      = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64   = -32602;
+pub const GAME_ERROR: i64       = -32000;
+
+pub type RpcResult = Result<Json, (i64, &'static str)>;
+
+/// The longest request line read, longer ones are skipped and answered with an error.
+pub const MAX_LINE: usize = 64 * 1024;
+
+/// A line read by `read_line`.
+#[derive(PartialEq, Debug)]
+pub enum Line {
+    Text(String),
+    /// Longer than `MAX_LINE`, none of it kept.
+    TooLong,
+}
+
+/// Serves JSON-RPC 2.0 requests, one per line on stdin, answering on stdout.
+pub fn run() {
+    let mut session = Session::default();
+    let mut stdout = std::io::stdout();
+    let mut stdin = std::io::stdin().lock();
+
+    while let Ok(Some(line)) = read_line(&mut stdin) {
+        let Line::Text(line) = line else {
+            if writeln!(stdout, "{}", too_long()).and_then(|_| stdout.flush()).is_err() {
+                break;
+            }
+            continue;
+        };
+        if line.trim().is_empty() { continue }
+
+        if let Some(response) = session.handle_line(&line) {
+            if writeln!(stdout, "{}", response).and_then(|_| stdout.flush()).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Session {
+    /// The board and the side to move, `None` until `newGame`.
+    game: Option<(Board, Tile)>,
+}
+impl Session {
+    /// Returns `None` for notifications, which get no response.
+    fn handle_line(&mut self, line: &str) -> Option<Json> {
+        respond(line, |method, params| self.call(method, params))
+    }
+
+    fn call(&mut self, method: &str, params: &Json) -> RpcResult {
+        match method {
+            "newGame"   => self.new_game(params),
+            "applyMove" => self.apply_move(params),
+            "bestMove"  => self.best_move(),
+            "analyze"   => self.analyze(),
+            _ => Err((METHOD_NOT_FOUND, "Method not found.")),
+        }
+    }
+
+    fn new_game(&mut self, params: &Json) -> RpcResult {
+        self.game = Some(game(params)?);
+        self.state()
+    }
+
+    fn apply_move(&mut self, params: &Json) -> RpcResult {
+        let (board, side) = self.in_progress()?;
+        let (row, col) = position(params)?;
+
+        board.set(side, row, col).map_err(|err| (GAME_ERROR, err.message()))?;
+        if let Some((_, to_move)) = &mut self.game {
+            *to_move = side.opposite().unwrap_or(side);
+        }
+        self.state()
+    }
+
+    fn best_move(&mut self) -> RpcResult {
+        let (board, side) = self.searchable()?;
+        best_move(board, side)
+    }
+
+    fn analyze(&mut self) -> RpcResult {
+        let (board, side) = self.searchable()?;
+
+        let moves = board.move_values(side)
+            .into_iter()
+            .map(|((row, col), value)| Json::object([
+                ("row", Json::from(row)),
+                ("col", Json::from(col)),
+                ("value", Json::from(value as i64)),
+            ]))
+            .collect();
+
+        let mut state = self.state()?;
+        if let Json::Object(fields) = &mut state {
+            fields.push(("moves".to_string(), Json::Array(moves)));
+        }
+        Ok(state)
+    }
+
+    fn in_progress(&mut self) -> Result<(&mut Board, Tile), (i64, &'static str)> {
+        let (board, to_move) = self.game.as_mut().ok_or((GAME_ERROR, "No game, call newGame first."))?;
+
+        if board.board_status() != BoardStatus::Continue {
+            return Err((GAME_ERROR, "The game is already over."));
+        }
+
+        Ok((board, *to_move))
+    }
+
+    /// The game in progress, if the search can finish on its board.
+    fn searchable(&mut self) -> Result<(&mut Board, Tile), (i64, &'static str)> {
+        let (board, to_move) = self.in_progress()?;
+        // The search goes to the end of the game, which takes far too long on larger boards.
+        if board.length() > MAX_PERFECT_AI_SIZE {
+            return Err((INVALID_PARAMS, "bestMove and analyze only search boards up to 3x3."));
+        }
+        Ok((board, to_move))
+    }
+
+    fn state(&self) -> RpcResult {
+        let (board, to_move) = self.game.as_ref().ok_or((GAME_ERROR, "No game, call newGame first."))?;
+        Ok(state(board, *to_move))
+    }
+}
+
+/// Reads the next line of `reader` without its line ending, `None` at the end of the input.
+/// Only `MAX_LINE` bytes of a line are kept, past them the rest of it is skipped.
+pub fn read_line(reader: &mut impl BufRead) -> io::Result<Option<Line>> {
+    let mut bytes = Vec::new();
+    let (mut read, mut too_long) = (false, false);
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        read = true;
+        let end = buf.iter().position(|&b| b == b'\n');
+        let part = &buf[..end.unwrap_or(buf.len())];
+        too_long |= bytes.len() + part.len() > MAX_LINE;
+        match too_long {
+            true  => bytes.clear(),
+            false => bytes.extend_from_slice(part),
+        }
+        let used = end.map_or(buf.len(), |end| end + 1);
+        reader.consume(used);
+        if end.is_some() {
+            break;
+        }
+    }
+
+    if !read {
+        return Ok(None);
+    }
+    if too_long {
+        return Ok(Some(Line::TooLong));
+    }
+    if bytes.last() == Some(&b'\r') {
+        bytes.pop();
+    }
+    String::from_utf8(bytes)
+        .map(|text| Some(Line::Text(text)))
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "request isn't UTF-8"))
+}
+
+/// The answer to a request line longer than `MAX_LINE`.
+pub fn too_long() -> Json {
+    log::warn!("request longer than {} bytes", MAX_LINE);
+    error_response(Json::Null, INVALID_REQUEST, "Request too long.")
+}
+
+/// Parses one request and answers it with `call(method, params)`.
+/// Returns `None` for notifications, which get no response.
+pub fn respond(line: &str, mut call: impl FnMut(&str, &Json) -> RpcResult) -> Option<Json> {
+    let request = match Json::parse(line) {
+        Ok(request) => request,
+        Err(err) => {
+            log::warn!("unparsable request: {}", err);
+            return Some(error_response(Json::Null, PARSE_ERROR, "Parse error."));
+        }
+    };
+
+    let id = request.get("id").cloned();
+    let method = request.get("method").and_then(Json::as_str);
+    let result = match method {
+        Some(method) => call(method, request.get("params").unwrap_or(&Json::Null)),
+        None => Err((INVALID_REQUEST, "Missing method.")),
+    };
+    if let Err((code, message)) = result {
+        log::warn!("request {:?} failed with {}: {}", method.unwrap_or(""), code, message);
+    }
+
+    let id = id?;
+    Some(match result {
+        Ok(result) => Json::object([
+            ("jsonrpc", Json::from("2.0")),
+            ("id", id),
+            ("result", result),
+        ]),
+        Err((code, message)) => error_response(id, code, message),
+    })
+}
+
+/// The position described by the `size`, `winLength`, `board` and `toMove` params of `newGame`,
+/// with the side to move.
+pub fn game(params: &Json) -> Result<(Board, Tile), (i64, &'static str)> {
+    let rows = match params.get("board") {
+        Some(board) => Some(parse_rows(board)?),
+        None => None,
+    };
+    let mut board = new_board(params, rows.as_ref().map_or(3, Vec::len))?;
+    let size = board.length();
+
+    let (mut crosses, mut noughts) = (0, 0);
+    for (row, tiles) in rows.iter().flatten().enumerate() {
+        if tiles.len() != size {
+            return Err((INVALID_PARAMS, "Every board row must have one tile per column."));
+        }
+        for (col, tile) in tiles.iter().enumerate() {
+            match tile {
+                Tile::Cross   => crosses += 1,
+                Tile::Nought  => noughts += 1,
+                Tile::Blocked => (),
+                Tile::Empty   => continue,
+            }
+            board.set(*tile, row, col).map_err(|err| (INVALID_PARAMS, err.message()))?;
+        }
+    }
+
+    let to_move = match params.get("toMove").and_then(Json::as_str) {
+        Some(side) => side.chars().next().and_then(Tile::from_char).filter(Tile::is_player),
+        None if crosses > noughts => Some(Tile::Nought),
+        None => Some(Tile::Cross),
+    };
+    let to_move = to_move.ok_or((INVALID_PARAMS, "'toMove' must be \"X\" or \"O\"."))?;
+
+    Ok((board, to_move))
+}
+
+/// The `row` and `col` of the perfect move for `side`.
+pub fn best_move(board: &Board, side: Tile) -> RpcResult {
+    let m = board.best_move(side).ok_or((GAME_ERROR, "No legal moves."))?;
+    Ok(Json::object([("row", Json::from(m.row)), ("col", Json::from(m.col))]))
+}
+
+/// An empty board of the `size` and `winLength` params, `size` defaults to `default_size`.
+pub fn new_board(params: &Json, default_size: usize) -> Result<Board, (i64, &'static str)> {
+    let param = |name, default| match params.get(name) {
+        Some(value) => value.as_usize().ok_or((INVALID_PARAMS, "Expected a non-negative integer.")),
+        None => Ok(default),
+    };
+    let size = param("size", default_size)?;
+    // Checked before the board is made, which a client could otherwise have take any memory.
+    if size > MAX_SIZE {
+        return Err((INVALID_PARAMS, "Boards go up to 26x26."));
+    }
+    let win_length = param("winLength", 3.min(size))?;
+
+    if size == 0 || win_length == 0 || win_length > size {
+        return Err((INVALID_PARAMS, "Win length must be between 1 and the board size."));
+    }
+    Ok(Board::new(size, win_length))
+}
+
+/// The `row` and `col` params.
+pub fn position(params: &Json) -> Result<(usize, usize), (i64, &'static str)> {
+    let row = params.get("row").and_then(Json::as_usize);
+    let col = params.get("col").and_then(Json::as_usize);
+    match (row, col) {
+        (Some(row), Some(col)) => Ok((row, col)),
+        _ => Err((INVALID_PARAMS, "Expected integer params 'row' and 'col'.")),
+    }
+}
+
+/// The board as row strings, the side to move and the status, with the winning line once won.
+pub fn state(board: &Board, to_move: Tile) -> Json {
+    let rows = (0..board.length())
+        .map(|row| {
+            (0..board.length())
+                .map(|col| board.get(row, col).unwrap_or(Tile::Empty).char())
+                .collect::<String>()
+                .into()
+        })
+        .collect();
+
+    let mut fields = vec![
+        ("board", Json::Array(rows)),
+        ("toMove", Json::from(to_move.char())),
+    ];
+    match board.board_status() {
+        BoardStatus::Winner(tile) => {
+            fields.push(("status", Json::from("winner")));
+            fields.push(("winner", Json::from(tile.char())));
+            // The row that won, as `[row, col]` pairs, for clients to highlight.
+            let line = board.winning_line().unwrap_or_default()
+                .into_iter()
+                .map(|(row, col)| Json::Array(vec![Json::from(row), Json::from(col)]))
+                .collect();
+            fields.push(("line", Json::Array(line)));
+        }
+        BoardStatus::Tie      => fields.push(("status", Json::from("tie"))),
+        BoardStatus::Continue => fields.push(("status", Json::from("continue"))),
+    }
+    // Scored by rows, the points of X and O.
+    if let Some(scored) = board.scored() {
+        fields.push(("points", Json::Array(scored.points.map(Json::from).to_vec())));
+    }
+
+    Json::object(fields)
+}
+
+fn parse_rows(board: &Json) -> Result<Vec<Vec<Tile>>, (i64, &'static str)> {
+    const INVALID_BOARD: (i64, &str) = (INVALID_PARAMS, "'board' must be an array of strings of ' ', 'X' and 'O'.");
+
+    board.as_array()
+        .ok_or(INVALID_BOARD)?
+        .iter()
+        .map(|row| {
+            row.as_str()
+                .ok_or(INVALID_BOARD)?
+                .chars()
+                .map(|c| Tile::from_char(c).ok_or(INVALID_BOARD))
+                .collect()
+        })
+        .collect()
+}
+
+pub fn error_response(id: Json, code: i64, message: &str) -> Json {
+    Json::object([
+        ("jsonrpc", Json::from("2.0")),
+        ("id", id),
+        ("error", Json::object([("code", Json::from(code)), ("message", Json::from(message))])),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_line, Line, Session, MAX_LINE};
+    use std::io::{BufReader, Cursor};
+
+    #[test]
+    fn handle_line() {
+        let mut s = Session::default();
+
+        let response = s.handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"newGame","params":{"size":3}}"#);
+        assert_eq!(
+            response.unwrap().to_string(),
+            r#"{"jsonrpc":"2.0","id":1,"result":{"board":["   ","   ","   "],"toMove":"X","status":"continue"}}"#
+        );
+
+        for (row, col) in [(0, 0), (1, 1), (0, 1)] {
+            let line = format!(r#"{{"jsonrpc":"2.0","method":"applyMove","params":{{"row":{},"col":{}}}}}"#, row, col);
+            assert_eq!(s.handle_line(&line), None);
+        }
+
+        let response = s.handle_line(r#"{"jsonrpc":"2.0","id":"a","method":"bestMove"}"#);
+        assert_eq!(response.unwrap().to_string(), r#"{"jsonrpc":"2.0","id":"a","result":{"row":0,"col":2}}"#);
+
+        let response = s.handle_line(r#"{"jsonrpc":"2.0","id":2,"method":"applyMove","params":{"row":0,"col":0}}"#);
+        assert_eq!(
+            response.unwrap().to_string(),
+            r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32000,"message":"Already occupied tile."}}"#
+        );
+
+        let response = s.handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"newGame","params":{"board":["XX ","OO ","   "]}}"#);
+        assert!(response.unwrap().to_string().contains(r#""toMove":"X""#));
+        let response = s.handle_line(r#"{"jsonrpc":"2.0","id":4,"method":"bestMove"}"#);
+        assert_eq!(response.unwrap().to_string(), r#"{"jsonrpc":"2.0","id":4,"result":{"row":0,"col":2}}"#);
+        let response = s.handle_line(r#"{"jsonrpc":"2.0","id":5,"method":"applyMove","params":{"row":0,"col":2}}"#);
+        assert!(response.unwrap().to_string().contains(r#""winner":"X","line":[[0,0],[0,1],[0,2]]"#));
+
+        let response = s.handle_line(r#"{"jsonrpc":"2.0","id":6,"method":"newGame","params":{"size":1000000}}"#);
+        assert_eq!(
+            response.unwrap().to_string(),
+            r#"{"jsonrpc":"2.0","id":6,"error":{"code":-32602,"message":"Boards go up to 26x26."}}"#
+        );
+        let response = s.handle_line(r#"{"jsonrpc":"2.0","id":7,"method":"newGame","params":{"size":26}}"#);
+        assert!(response.unwrap().to_string().contains(r#""toMove":"X""#));
+
+        s.handle_line(r#"{"jsonrpc":"2.0","id":8,"method":"newGame","params":{"size":4}}"#);
+        for method in ["bestMove", "analyze"] {
+            let response = s.handle_line(&format!(r#"{{"jsonrpc":"2.0","id":9,"method":"{}"}}"#, method));
+            assert_eq!(
+                response.unwrap().to_string(),
+                r#"{"jsonrpc":"2.0","id":9,"error":{"code":-32602,"message":"bestMove and analyze only search boards up to 3x3."}}"#
+            );
+        }
+
+        let response = s.handle_line("not json");
+        assert!(response.unwrap().to_string().contains("-32700"));
+        // Nesting this deep once overflowed the stack and took the server down.
+        let response = s.handle_line(&"[".repeat(100_000));
+        assert!(response.unwrap().to_string().contains("-32700"));
+    }
+
+    #[test]
+    fn read_lines() {
+        let input = format!("a\r\n{}\n\nb", "x".repeat(MAX_LINE + 1));
+        let mut reader = BufReader::with_capacity(16, Cursor::new(input));
+        assert_eq!(read_line(&mut reader).unwrap(), Some(Line::Text("a".to_string())));
+        assert_eq!(read_line(&mut reader).unwrap(), Some(Line::TooLong));
+        assert_eq!(read_line(&mut reader).unwrap(), Some(Line::Text(String::new())));
+        assert_eq!(read_line(&mut reader).unwrap(), Some(Line::Text("b".to_string())));
+        assert_eq!(read_line(&mut reader).unwrap(), None);
+    }
+}
@@ -0,0 +1,245 @@
+//! Key bindings for the cursor input mode, the defaults or those in `keys.txt` in the data
+//! directory, one control per line followed by the keys for it:
+//!
+//! ```text
+//! # control  keys
+//! place      enter space
+//! undo       u backspace
+//! ```
+//!
+//! The keys listed for a control replace its default ones, the other controls keep theirs.
+//! Keys are single characters or up, down, left, right, enter, space, backspace and esc.
+
+use crate::profile::data_dir;
+use std::sync::OnceLock;
+use std::{fs, io, path::PathBuf};
+
+const KEYS_FILE: &str = "keys.txt";
+
+static KEYMAP: OnceLock<Keymap> = OnceLock::new();
+
+/// A key press as the cursor mode reads it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Backspace,
+    Escape,
+}
+impl Key {
+    pub fn parse(name: &str) -> Option<Self> {
+        let key = match name.to_lowercase().as_str() {
+            "up"        => Self::Up,
+            "down"      => Self::Down,
+            "left"      => Self::Left,
+            "right"     => Self::Right,
+            "enter"     => Self::Enter,
+            "space"     => Self::Char(' '),
+            "backspace" => Self::Backspace,
+            "esc"       => Self::Escape,
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Self::Char(c),
+                    _ => return None,
+                }
+            }
+        };
+        Some(key)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Char(' ') => "space".to_string(),
+            Self::Char(c)   => c.to_string(),
+            Self::Up        => "up".to_string(),
+            Self::Down      => "down".to_string(),
+            Self::Left      => "left".to_string(),
+            Self::Right     => "right".to_string(),
+            Self::Enter     => "enter".to_string(),
+            Self::Backspace => "backspace".to_string(),
+            Self::Escape    => "esc".to_string(),
+        }
+    }
+}
+
+/// What a key does in the cursor mode.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Control {
+    Up,
+    Down,
+    Left,
+    Right,
+    Place,
+    Undo,
+    Hint,
+    Quit,
+    Keys,
+}
+impl Control {
+    pub const ALL: [Control; 9] = [
+        Self::Up, Self::Down, Self::Left, Self::Right, Self::Place, Self::Undo, Self::Hint, Self::Quit, Self::Keys,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Up    => "up",
+            Self::Down  => "down",
+            Self::Left  => "left",
+            Self::Right => "right",
+            Self::Place => "place",
+            Self::Undo  => "undo",
+            Self::Hint  => "hint",
+            Self::Quit  => "quit",
+            Self::Keys  => "keys",
+        }
+    }
+
+    pub fn help(&self) -> &'static str {
+        match self {
+            Self::Up    => "Move the cursor up",
+            Self::Down  => "Move the cursor down",
+            Self::Left  => "Move the cursor left",
+            Self::Right => "Move the cursor right",
+            Self::Place => "Place your mark under the cursor",
+            Self::Undo  => "Take back your last move and the reply",
+            Self::Hint  => "Move the cursor to a suggested move",
+            Self::Quit  => "Quit the game",
+            Self::Keys  => "Show these key bindings",
+        }
+    }
+
+    fn default_keys(&self) -> Vec<Key> {
+        match self {
+            Self::Up    => vec![Key::Up, Key::Char('k')],
+            Self::Down  => vec![Key::Down, Key::Char('j')],
+            Self::Left  => vec![Key::Left, Key::Char('h')],
+            Self::Right => vec![Key::Right, Key::Char('l')],
+            Self::Place => vec![Key::Enter, Key::Char(' ')],
+            Self::Undo  => vec![Key::Char('u')],
+            Self::Hint  => vec![Key::Char('i')],
+            Self::Quit  => vec![Key::Char('q'), Key::Escape],
+            Self::Keys  => vec![Key::Char('?')],
+        }
+    }
+}
+
+/// The keys bound to every control.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Keymap {
+    bindings: Vec<(Control, Vec<Key>)>,
+}
+impl Default for Keymap {
+    fn default() -> Self {
+        Self { bindings: Control::ALL.iter().map(|control| (*control, control.default_keys())).collect() }
+    }
+}
+impl Keymap {
+    /// The defaults with the bindings in `text` in place, in the format of `keys.txt`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut keymap = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut fields = line.split_whitespace();
+            let Some(name) = fields.next() else { continue };
+
+            let control = Control::ALL.into_iter().find(|control| control.name() == name)
+                .ok_or(format!("Line {}: '{}' isn't a control, use up, down, left, right, place, undo, hint, quit or keys.", number + 1, name))?;
+            let keys = fields
+                .map(|key| Key::parse(key).ok_or(format!("Line {}: '{}' isn't a key.", number + 1, key)))
+                .collect::<Result<Vec<_>, _>>()?;
+            if keys.is_empty() {
+                return Err(format!("Line {}: {} needs at least one key.", number + 1, name));
+            }
+            for (other, taken) in &mut keymap.bindings {
+                if *other != control {
+                    taken.retain(|key| !keys.contains(key));
+                }
+            }
+            if let Some((_, bound)) = keymap.bindings.iter_mut().find(|(c, _)| *c == control) {
+                *bound = keys;
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// The control `key` is bound to, if any.
+    pub fn control(&self, key: Key) -> Option<Control> {
+        self.bindings.iter().find(|(_, keys)| keys.contains(&key)).map(|(control, _)| *control)
+    }
+
+    pub fn keys(&self, control: Control) -> &[Key] {
+        self.bindings.iter().find(|(c, _)| *c == control).map_or(&[], |(_, keys)| keys)
+    }
+
+    /// The keys of `control` for showing, e.g. `enter/space`.
+    pub fn describe(&self, control: Control) -> String {
+        match self.keys(control) {
+            []   => "(none)".to_string(),
+            keys => keys.iter().map(Key::name).collect::<Vec<_>>().join("/"),
+        }
+    }
+
+    /// One line per control with its keys and what it does.
+    pub fn help(&self) -> String {
+        let mut out = String::from("Cursor mode keys:\n");
+        for control in Control::ALL {
+            out.push_str(&format!("  {:<14}{}\n", self.describe(control), control.help()));
+        }
+        out.push_str("A number typed before a move repeats it, e.g. 3l moves three tiles right.\n");
+        out.push_str(&format!("Change them in {}.", path().display()));
+        out
+    }
+}
+
+pub fn path() -> PathBuf {
+    data_dir().join(KEYS_FILE)
+}
+
+/// Reads `keys.txt`, the defaults when there is none.
+pub fn load() -> Result<Keymap, String> {
+    match fs::read_to_string(path()) {
+        Ok(text) => Keymap::parse(&text).map_err(|err| format!("{}: {}", path().display(), err)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Keymap::default()),
+        Err(err) => Err(format!("Couldn't read {}: {}", path().display(), err)),
+    }
+}
+
+/// Uses `keymap` from now on, it can only be set once.
+pub fn set(keymap: Keymap) {
+    let _ = KEYMAP.set(keymap);
+}
+
+/// The bindings in use, the defaults unless `set` was called.
+pub fn keymap() -> &'static Keymap {
+    KEYMAP.get_or_init(Keymap::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Control, Key, Keymap};
+
+    #[test]
+    fn remap() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.control(Key::Enter), Some(Control::Place));
+        assert_eq!(keymap.control(Key::Char('x')), None);
+
+        let keymap = Keymap::parse("# my keys\nplace x\nundo  q backspace\n").unwrap();
+        assert_eq!(keymap.control(Key::Char('x')), Some(Control::Place));
+        assert_eq!(keymap.control(Key::Enter), None);
+        assert_eq!(keymap.control(Key::Backspace), Some(Control::Undo));
+        // Taken from quit, which keeps its other key.
+        assert_eq!(keymap.control(Key::Char('q')), Some(Control::Undo));
+        assert_eq!(keymap.describe(Control::Quit), "esc");
+        assert_eq!(keymap.control(Key::Up), Some(Control::Up));
+
+        assert!(Keymap::parse("jump j").unwrap_err().starts_with("Line 1: 'jump' isn't a control"));
+        assert!(Keymap::parse("\nplace enter ctrl-x").is_err());
+        assert!(Keymap::parse("place").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! The position laboratory, `analyze` without a game record: load a position, try moves and take
+//! them back, and ask the search for evaluations and best lines. Every line tried stays in the
+//! game tree as a branch to go back to, and none of it is a real game.
+
+use crate::{editor, screen, setup};
+use tick_tack_toe::board::{Board, BoardStatus, Tile};
+use tick_tack_toe::game::Move;
+use tick_tack_toe::tree::GameTree;
+use tick_tack_toe::{coords, render};
+
+const HELP: &str = "\
+  TILE             Try a move for the side to move, e.g. \"b2\" or \"1, 1\"
+  undo             Take the last move back
+  root             Go back to the loaded position
+  eval             Tell how the position ends with best play
+  moves            Show the value of every move for the side to move
+  best             Show the best line from here
+  branches         List every line tried from the loaded position
+  branch N         Go to the end of line N of branches
+  position NOTATION
+                   Load a position such as \"x../.o./...\", starting over
+  q, quit          Leave the laboratory
+  ?, help          Show this help";
+
+pub struct Lab {
+    tree: GameTree,
+    /// The moves from the loaded position to the one looked at.
+    path: Vec<(usize, usize)>,
+    /// Every line tried from the loaded position, none of them the start of another.
+    lines: Vec<Vec<(usize, usize)>>,
+}
+impl Lab {
+    pub fn new(board: Board, to_move: Tile) -> Self {
+        Self { tree: GameTree::new(board, to_move), path: Vec::new(), lines: Vec::new() }
+    }
+
+    /// The position looked at.
+    pub fn node(&self) -> &GameTree {
+        self.path.iter().fold(&self.tree, |node, &(row, col)| node.child(row, col).expect("the path was expanded"))
+    }
+
+    fn node_mut(&mut self) -> &mut GameTree {
+        self.path.iter().fold(&mut self.tree, |node, &(row, col)| node.child_mut(row, col).expect("the path was expanded"))
+    }
+
+    /// Plays `(row, col)` from the position looked at, keeping the line as a branch.
+    pub fn play(&mut self, row: usize, col: usize) -> Result<(), String> {
+        let node = self.node_mut();
+        if node.status() != BoardStatus::Continue {
+            return Err("The game is over here, undo to try something else.".to_string());
+        }
+        node.expand();
+        if node.child(row, col).is_none() {
+            return Err("That tile is taken.".to_string());
+        }
+        self.path.push((row, col));
+
+        let path = &self.path;
+        self.lines.retain(|line| !path.starts_with(line));
+        if !self.lines.iter().any(|line| line.starts_with(path)) {
+            self.lines.push(path.clone());
+        }
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> Result<(), String> {
+        self.path.pop().map(|_| ()).ok_or("This is the loaded position, there is nothing to undo.".to_string())
+    }
+
+    pub fn root(&mut self) {
+        self.path.clear();
+    }
+
+    /// Goes to the end of the `index`th line of `branches`.
+    pub fn branch(&mut self, index: usize) -> Result<(), String> {
+        let line = self.lines.get(index).ok_or(format!("There are {} lines, see branches.", self.lines.len()))?;
+        self.path = line.clone();
+        Ok(())
+    }
+
+    /// The value of the position looked at for the side to move, see `GameTree::evaluate`.
+    pub fn evaluate(&mut self) -> i8 {
+        self.node_mut().evaluate()
+    }
+
+    /// Every move from the position looked at with its value for the side to move.
+    pub fn moves(&mut self) -> Vec<(Move, i8)> {
+        let node = self.node_mut();
+        node.expand().iter_mut().filter_map(|child| Some((child.last_move()?, -child.evaluate()))).collect()
+    }
+
+    /// The lines of `branches` as moves, the one the position looked at is on marked.
+    pub fn branches(&self) -> Vec<(bool, Vec<Move>)> {
+        self.lines.iter().map(|line| (line.starts_with(&self.path), self.moves_of(line))).collect()
+    }
+
+    /// The moves from the loaded position to the one looked at.
+    pub fn line(&self) -> Vec<Move> {
+        self.moves_of(&self.path)
+    }
+
+    fn moves_of(&self, path: &[(usize, usize)]) -> Vec<Move> {
+        let mut node = &self.tree;
+        path.iter().filter_map(|&(row, col)| {
+            node = node.child(row, col)?;
+            node.last_move()
+        }).collect()
+    }
+}
+
+/// Runs the laboratory on `board` with `to_move` to play, until it's left.
+pub fn run(board: Board, to_move: Tile) {
+    let mut lab = Lab::new(board, to_move);
+    println!("Try moves and ask the search about them, type ? for help.");
+    let mut show = true;
+
+    loop {
+        if show {
+            print_position(lab.node(), &lab.line());
+        }
+        show = true;
+        let Some(input) = setup::ask("Analyze") else { return };
+        let input = input.to_lowercase();
+        let (command, argument) = input.split_once(' ').unwrap_or((input.as_str(), ""));
+
+        let result = match command {
+            "undo" | "u" => lab.undo(),
+            "root" => {
+                lab.root();
+                Ok(())
+            }
+            "eval" => {
+                show = false;
+                println!("{:?} to move {}.", lab.node().to_move(), outcome(lab.evaluate()));
+                Ok(())
+            }
+            "moves" => {
+                show = false;
+                for (m, value) in lab.moves() {
+                    println!("  {:<8} {}", coords::format(m.row, m.col), outcome(value));
+                }
+                Ok(())
+            }
+            "best" => {
+                show = false;
+                let line: Vec<String> = lab.node().principal_variation().map(|m| m.notation()).collect();
+                match line.is_empty() {
+                    true  => println!("The game is over."),
+                    false => println!("Best line: {}", line.join(" ")),
+                }
+                Ok(())
+            }
+            "branches" => {
+                show = false;
+                for (i, (current, line)) in lab.branches().into_iter().enumerate() {
+                    let moves: Vec<String> = line.iter().map(Move::notation).collect();
+                    println!("{}{:>3}. {}", if current { '>' } else { ' ' }, i + 1, moves.join(" "));
+                }
+                Ok(())
+            }
+            "branch" => match argument.trim().parse::<usize>() {
+                Ok(n @ 1..) => lab.branch(n - 1),
+                _ => Err("Say which line, e.g. \"branch 2\".".to_string()),
+            },
+            "position" => load(argument).map(|(board, to_move)| lab = Lab::new(board, to_move)),
+            "q" | "quit" => return,
+            "?" | "help" => {
+                show = false;
+                println!("{}", HELP);
+                Ok(())
+            }
+            _ => coords::parse(&input, lab.node().board().length())
+                .map_err(|_| format!("'{}' isn't a move or a command, type ? for help.", input))
+                .and_then(|(row, col)| lab.play(row, col)),
+        };
+        if let Err(err) = result {
+            show = false;
+            println!("{}", err);
+        }
+    }
+}
+
+/// Whether the search can finish on `board`: evaluations go through every position to the end.
+pub fn check_size(board: &Board) -> Result<(), String> {
+    match board.length() > setup::MAX_PERFECT_AI_SIZE {
+        true  => Err(format!("The laboratory searches every position to the end, which needs a board of at most {0}x{0}.", setup::MAX_PERFECT_AI_SIZE)),
+        false => Ok(()),
+    }
+}
+
+/// The position in `notation` with the side to move, up to three in a row to win.
+fn load(notation: &str) -> Result<(Board, Tile), String> {
+    let board = Board::from_notation(notation, notation.split('/').count().min(3)).map_err(|err| err.to_string())?;
+    check_size(&board)?;
+    let to_move = editor::side_to_move(&board);
+    editor::check(&board, to_move)?;
+    Ok((board, to_move))
+}
+
+fn print_position(node: &GameTree, line: &[Move]) {
+    let last: Vec<(usize, usize)> = node.last_move().map(|m| (m.row, m.col)).into_iter().collect();
+    print!("{}", node.board().render_highlighted(&screen::theme(), &last));
+    let moves: Vec<String> = line.iter().map(Move::notation).collect();
+    let line = if moves.is_empty() { String::new() } else { format!(" after {}", moves.join(" ")) };
+    match node.status() {
+        BoardStatus::Continue => println!("{:?} to move{}.", node.to_move(), line),
+        status => println!("{}{}.", render::result(status).trim_end_matches(['.', '!']), line),
+    }
+}
+
+fn outcome(value: i8) -> &'static str {
+    match value {
+        1  => "wins",
+        -1 => "loses",
+        _  => "draws",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load, Lab};
+    use tick_tack_toe::board::{Board, Tile::*};
+
+    #[test]
+    fn branches() {
+        let mut lab = Lab::new(Board::from_notation("XX./OO./...", 3).unwrap(), Cross);
+        assert_eq!(lab.evaluate(), 1);
+        let moves = lab.moves();
+        assert_eq!(moves.len(), 5);
+        assert!(moves.iter().any(|(m, value)| (m.row, m.col) == (0, 2) && *value == 1));
+
+        lab.play(2, 2).unwrap();
+        assert_eq!(lab.evaluate(), 1);
+        lab.play(1, 2).unwrap();
+        assert!(lab.play(0, 2).is_err());
+        lab.undo().unwrap();
+        lab.undo().unwrap();
+        assert!(lab.play(0, 0).is_err());
+        lab.play(0, 2).unwrap();
+
+        let branches = lab.branches();
+        assert_eq!(branches.len(), 2);
+        assert_eq!(branches[0].1.iter().map(|m| m.notation()).collect::<Vec<_>>(), ["Xc3", "Oc2"]);
+        assert!(branches[1].0 && !branches[0].0);
+        lab.branch(0).unwrap();
+        assert_eq!(lab.line().len(), 2);
+        assert_eq!(lab.node().to_move(), Cross);
+        lab.root();
+        assert!(lab.undo().is_err());
+        assert!(lab.branch(2).is_err());
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(load("X../.O./...").map(|(_, to_move)| to_move), Ok(Cross));
+        let err = load("X.../.O../..../....").unwrap_err();
+        assert_eq!(err, "The laboratory searches every position to the end, which needs a board of at most 3x3.");
+    }
+}
@@ -0,0 +1,25 @@
+//! The tic tac toe engine: boards, games, search and rendering, shared by every frontend.
+//! The parts that don't need the standard library are in `tick_tack_toe_core`.
+
+pub mod analysis;
+pub mod board;
+pub mod coords;
+pub mod enumerate;
+pub mod game;
+#[cfg(feature = "image")]
+pub mod image;
+pub mod json;
+pub mod log;
+pub mod openings;
+pub mod rating;
+pub mod record;
+pub mod render;
+pub mod retrograde;
+pub mod score;
+pub mod sgf;
+pub mod solve;
+pub mod tree;
+pub mod variant;
+
+pub use tick_tack_toe_core::{error, fixed_board};
+pub use error::{Error, Result};
@@ -0,0 +1,92 @@
+//! Leveled logging to stderr, a small stand-in for `tracing` that keeps the dependency list short.
+//! The level comes from `RUST_LOG` (e.g. `RUST_LOG=debug`) and is raised by each `-v` flag.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+impl Level {
+    const ALL: [Level; 5] = [Self::Error, Self::Warn, Self::Info, Self::Debug, Self::Trace];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn  => "WARN",
+            Self::Info  => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+}
+
+/// The most verbose level that is printed, 0 turns logging off.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+
+/// Sets the level from `RUST_LOG` and then raises it by `verbosity` steps.
+pub fn init(verbosity: u8) {
+    let base = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|value| parse_filter(&value))
+        .unwrap_or(Level::Warn as u8);
+
+    MAX_LEVEL.store(base.saturating_add(verbosity).min(Level::Trace as u8), Ordering::Relaxed);
+}
+
+/// Reads the level out of a `RUST_LOG` style filter, where the last directive wins,
+/// e.g. `info` or `tick_tack_toe=debug`.
+fn parse_filter(filter: &str) -> Option<u8> {
+    let directive = filter.split(',').next_back()?;
+    let level = directive.rsplit('=').next()?.trim();
+
+    if level.eq_ignore_ascii_case("off") {
+        return Some(0);
+    }
+    Level::ALL.iter()
+        .find(|l| l.name().eq_ignore_ascii_case(level))
+        .map(|l| *l as u8)
+}
+
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+pub fn log(level: Level, target: &str, args: fmt::Arguments) {
+    if enabled(level) {
+        eprintln!("{:<5} {}: {}", level.name(), target, args);
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_error { ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Error, module_path!(), format_args!($($arg)*)) } }
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_warn  { ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Warn,  module_path!(), format_args!($($arg)*)) } }
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_info  { ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Info,  module_path!(), format_args!($($arg)*)) } }
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_debug { ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Debug, module_path!(), format_args!($($arg)*)) } }
+pub use crate::{__log_debug as debug, __log_error as error, __log_info as info, __log_warn as warn};
+
+#[cfg(test)]
+mod tests {
+    use super::parse_filter;
+
+    #[test]
+    fn filters() {
+        assert_eq!(parse_filter("debug"), Some(4));
+        assert_eq!(parse_filter("tick_tack_toe=INFO"), Some(3));
+        assert_eq!(parse_filter("warn,tick_tack_toe=trace"), Some(5));
+        assert_eq!(parse_filter("off"), Some(0));
+        assert_eq!(parse_filter("loud"), None);
+    }
+}
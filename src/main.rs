@@ -1,89 +1,951 @@
-mod board;
-use board::{Board, Tile::{*, self}, BoardStatus};
-use std::{thread::sleep, time::Duration};
+mod arena;
+mod cli;
+mod clipboard;
+mod commands;
+mod completions;
+mod cursor;
+mod daily;
+mod editor;
+mod engine;
+mod interrupt;
+mod journal;
+mod lab;
+mod jsonrpc;
+mod keys;
+mod player;
+mod plugin;
+mod profile;
+mod screen;
+mod series;
+mod server;
+mod setup;
+mod simul;
+mod simulate;
+#[cfg(feature = "audio")]
+mod speech;
+mod strategy;
+mod terminal;
+mod tournament;
+mod watch;
+mod websocket;
+use player::{Player, Turn};
+use profile::{Outcome, Profiles};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use tick_tack_toe::board::{Board, BoardStatus, Tile};
+use tick_tack_toe::game::{Game, GameObserver, Move};
+use tick_tack_toe::record::{Record, Termination};
+use tick_tack_toe::render::{self, Layout, Names, Palette, Theme};
+use tick_tack_toe::score::SplitScore;
+use tick_tack_toe::retrograde::Tablebase;
+use tick_tack_toe::solve::{self, Solver};
+use tick_tack_toe::{analysis, coords, enumerate, log, sgf, Error};
 
-const RESPONSE_PAUSE: Duration = Duration::from_millis(800);
+/// The `--export-svg`, `--export-png`, `--export-gif` and `--export-text` files.
+static SVG_EXPORT: OnceLock<PathBuf> = OnceLock::new();
+static PNG_EXPORT: OnceLock<PathBuf> = OnceLock::new();
+static GIF_EXPORT: OnceLock<PathBuf> = OnceLock::new();
+static TEXT_EXPORT: OnceLock<PathBuf> = OnceLock::new();
 
-enum Player {
-    Human(Tile),
-    RandomAi(Tile),
-    OptimalAi(Tile),
+fn main() {
+    let options = cli::parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    });
+
+    coords::set_origin(options.origin);
+    terminal::init();
+    // Redrawing in place needs escape codes, without them boards scroll as usual.
+    screen::configure(options.in_place && terminal::escapes(), !options.no_animation);
+    if let Some(millis) = options.delay {
+        player::set_response_pause(millis);
+    }
+    player::set_teaching(options.teach);
+    cursor::set_enabled(options.cursor);
+    if options.cursor {
+        keys::set(keys::load().unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }));
+    }
+    let mut theme = options.theme.unwrap_or_default();
+    if !theme.is_ascii() && !terminal::unicode() {
+        log::warn!("the console can't show the theme's characters, drawing boards in ASCII");
+        theme = Theme::ASCII;
+    }
+    let mut layout = options.layout.unwrap_or(Layout::Auto);
+    let condensed = matches!(layout, Layout::HalfBlock | Layout::Braille);
+    if condensed && !terminal::unicode() || layout == Layout::HalfBlock && !terminal::escapes() {
+        log::warn!("the console can't show the {:?} layout, drawing boards compact", layout);
+        layout = Layout::Compact;
+    }
+    let palette = options.palette.unwrap_or(Palette::PLAIN);
+    screen::set_theme(Theme {
+        palette: if terminal::escapes() { palette } else { palette.without_escapes() },
+        colors: options.colors.unwrap_or_else(terminal::color_depth),
+        scale: options.scale.unwrap_or(1),
+        layout,
+        ..theme
+    });
+    #[cfg(feature = "audio")]
+    if options.speak {
+        speech::enable();
+    }
+    log::init(options.verbosity);
+    interrupt::install();
+    if let Some(path) = &options.export_svg {
+        let _ = SVG_EXPORT.set(path.clone());
+    }
+    if let Some(path) = &options.export_png {
+        let _ = PNG_EXPORT.set(path.clone());
+    }
+    if let Some(path) = &options.export_gif {
+        let _ = GIF_EXPORT.set(path.clone());
+    }
+    if let Some(path) = &options.export_text {
+        let _ = TEXT_EXPORT.set(path.clone());
+    }
+
+    if let Err(err) = run(options) {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
 }
-impl Player {
-    fn make_move(&self, board: &mut Board) {
-        match self {
-            Self::Human(tile)     => ensure_human_move(board, *tile),
-            Self::RandomAi(tile)  => board.make_random_move(*tile),
-            Self::OptimalAi(tile) => board.make_perfect_move(*tile),
+
+fn run(mut options: cli::Options) -> Result<(), Box<dyn std::error::Error>> {
+    if let cli::Command::ConnectEngine(address) = &options.command {
+        options.preset.players = engine_seats(options.preset.players, address)?;
+    }
+    match &options.command {
+        cli::Command::Simulate(games) => return simulate(&options, *games),
+        cli::Command::Tournament(kinds) => return play_tournament(&options, kinds),
+        cli::Command::Arena(path) => return play_arena(&options, path),
+        _ if options.headless || options.output == cli::Output::ResultOnly => return play_headless(&options),
+        cli::Command::Play | cli::Command::ConnectEngine(_) => (),
+        cli::Command::Stats(name) => return print_stats(name.as_deref()),
+        cli::Command::Leaderboard => return print_leaderboard(),
+        cli::Command::Replay(path) => return replay(path),
+        cli::Command::Analyze(Some(path)) => return analyze_game(path, options.out.as_deref()),
+        cli::Command::Analyze(None) => return explore(&options),
+        cli::Command::Perft(depth, position) => return perft(&options, *depth, position.as_deref()),
+        cli::Command::Solve(position) => return solve(&options, position),
+        cli::Command::Positions => return list_positions(&options),
+        cli::Command::Verify => return verify(&options),
+        cli::Command::Edit => return edit(&options),
+        cli::Command::Convert(from, to) => return convert(from, to),
+        cli::Command::Puzzle => return Ok(play_daily()?),
+        cli::Command::Watch => return watch(&options),
+        cli::Command::Serve(None) => {
+            jsonrpc::run();
+            return Ok(());
+        }
+        cli::Command::Serve(Some(address)) => return Ok(server::serve(address)?),
+        cli::Command::Simul(kinds) => return play_simul(&options, kinds),
+        cli::Command::Completions(shell) => {
+            print!("{}", completions::script(*shell));
+            return Ok(());
         }
+        cli::Command::Help(help) => {
+            println!("{}", help);
+            return Ok(());
+        }
+    }
+
+    let series = load_series(&options)?;
+    let unfinished = if match_games(&options, &series).is_none() { resume_unfinished() } else { None };
+    let (setup, mut resumed) = match unfinished {
+        Some((setup, game, to_move)) => (setup, Some((game, to_move))),
+        None => (setup::choose_setup(&options.preset), None),
+    };
+    setup.validate()?;
+    // A resumed game already knows whose turn it is, there is no coin to flip.
+    let mut first = match (&resumed, options.first) {
+        (Some(_), cli::First::CoinFlip) => 0,
+        _ => first_player(options.first),
+    };
+    if let Some(games) = match_games(&options, &series) {
+        return Ok(play_match(setup, games, first, &series)?);
     }
 
-    fn tile(&self) -> Tile {
-        match self {
-            Self::Human(tile)     => *tile,
-            Self::RandomAi(tile)  => *tile,
-            Self::OptimalAi(tile) => *tile,
+    let mut score = SplitScore::default();
+
+    loop {
+        let (mut game, to_move) = match resumed.take() {
+            Some(resumed) => resumed,
+            None => (setup.new_game(), first),
+        };
+        let Some(status) = play_game(&setup.players, &setup.shown_names(), to_move, &mut game)? else { return Ok(()) };
+        score.record(setup.players[to_move].tile(), status);
+        record_profiles(&setup, status);
+        println!("Score: {}", score.total());
+        if score.total().games() > 1 {
+            println!("By who moved first: {}", score);
+        }
+
+        // Moving first is an advantage, so rematches take turns unless asked not to.
+        let answer = setup::ask("Play again? (y: yes with the other side first, k: yes and keep who goes first, n: quit) [n]");
+        match answer.unwrap_or_default().to_lowercase().as_str() {
+            "y" | "yes" | "s" | "swap" => first = 1 - to_move,
+            "k" | "keep" => first = to_move,
+            _ => return Ok(()),
         }
     }
 }
 
-fn main() {
-    let mut b = Board::new(3, 3);
+/// Offers to resume the game left unfinished in the journal.
+/// Returns its setup, the game so far and the index of the player to move.
+fn resume_unfinished() -> Option<(setup::Setup, Game, usize)> {
+    let record = journal::unfinished()?;
+    let player = |tile: Tile| record.tag(&format!("{:?}", tile)).and_then(|kind| Player::from_kind(kind, tile));
+    let players = [player(Tile::Cross)?, player(Tile::Nought)?];
 
-    let players = [
-        Player::Human(Cross),
-        Player::OptimalAi(Nought),
-    ];
+    let game = record.to_game().ok()?;
+    if game.status() != BoardStatus::Continue {
+        return None;
+    }
+    let to_move = match game.history().last() {
+        Some(m) => usize::from(m.tile == Tile::Cross),
+        None => usize::from(record.tag("First") == Some("Nought")),
+    };
+
+    let question = format!(
+        "Resume the unfinished game of {} against {} on a {}x{} board, {} moves in? [y/N]",
+        players[0].kind(), players[1].kind(), record.start.length(), record.start.length(), game.history().len(),
+    );
+    let answer = setup::ask(&question)?;
+    if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+        return None;
+    }
+
+    let setup = setup::Setup {
+        players,
+        names: [None, None],
+        display_names: [Tile::Cross, Tile::Nought].map(|tile| record.tag(&format!("{:?}Name", tile)).map(str::to_string)),
+        size: record.start.length(),
+        win_length: record.start.win_row_length(),
+        handicap: None,
+        obstacles: 0,
+        variant: game.variant(),
+    };
+    Some((setup, game, to_move))
+}
+
+/// Plays AI against AI without drawing the board or pausing, and prints only the result.
+fn play_headless(options: &cli::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let setup = options.preset.unattended()?;
+    let series = load_series(options)?;
+
+    screen::set_headless(true);
+    screen::set_result_only(options.output == cli::Output::ResultOnly);
+    player::set_response_pause(0);
+
+    let first = first_player(options.first);
+    if let Some(games) = match_games(options, &series) {
+        return Ok(play_match(setup, games, first, &series)?);
+    }
+    let mut game = setup.new_game();
+    let names = setup.shown_names();
+    match play_game(&setup.players, &names, first, &mut game)? {
+        Some(status) if !screen::result_only() => println!("{}", render::named_result(status, &names)),
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Plays `games` AI games without output, prints the score and writes the results to `--out`.
+fn simulate(options: &cli::Options, games: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let setup = options.preset.unattended()?;
+    let results = simulate::run(&setup, games)?;
+
+    let split = simulate::split(&results);
+    println!("{} against {}, {} games: {}", setup.players[0].kind(), setup.players[1].kind(), games, split.total());
+    println!("{}", simulate::report(&setup.players, &split));
+    if let Some(path) = &options.out {
+        simulate::write(path, &setup.players, &results).map_err(|err| format!("couldn't write {}: {}", path.display(), err))?;
+    }
+    Ok(())
+}
+
+/// Shows a game between the `--cross` and `--nought` AIs with the playback controls of `watch`.
+fn watch(options: &cli::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let setup = options.preset.unattended()?;
+    let mut game = setup.new_game();
+    let Some(status) = watch::run(&setup, &mut game, first_player(options.first), player::response_pause())? else { return Ok(()) };
+
+    let names = setup.shown_names();
+    let result = match (status, game.status()) {
+        // Resignations end the game before the board does.
+        (BoardStatus::Winner(winner), BoardStatus::Continue) => render::resignation(winner.opposite().unwrap_or(Tile::Empty), &names),
+        _ => render::named_result(status, &names),
+    };
+    println!("{}", result);
+    announce(&result);
+    export_images(&game);
+    Ok(())
+}
+
+/// Plays a simul against `kinds` on the `--size` board, as Nought when `--nought human` is given.
+fn play_simul(options: &cli::Options, kinds: &[&'static str]) -> Result<(), Box<dyn std::error::Error>> {
+    let size = options.preset.size.unwrap_or(3);
+    let win_length = options.preset.win_length.unwrap_or(size.min(3));
+    if win_length > size {
+        return Err("The win length can't be longer than the board size.".into());
+    }
+    let side = match options.preset.players {
+        [_, Some("human")] => Tile::Nought,
+        _ => Tile::Cross,
+    };
+
+    let score = simul::play(kinds, side, size, win_length)?;
+    let other = side.opposite().unwrap_or(Tile::Empty);
+    println!("You won {}, drew {} and lost {} of {} boards.", score.wins(side), score.draws, score.wins(other), kinds.len());
+    Ok(())
+}
+
+/// Plays a tournament between `kinds` on the `--size` board, printing the standings after every
+/// round.
+fn play_tournament(options: &cli::Options, kinds: &[&'static str]) -> Result<(), Box<dyn std::error::Error>> {
+    let size = options.preset.size.unwrap_or(3);
+    let win_length = options.preset.win_length.unwrap_or(size.min(3));
+    if win_length > size {
+        return Err("The win length can't be longer than the board size.".into());
+    }
+
+    let pairing = options.swiss.map_or(tournament::Pairing::RoundRobin, tournament::Pairing::Swiss);
+    tournament::run(kinds, pairing, size, win_length, |round, entrants| {
+        println!("{}", tournament::standings(round, entrants));
+    })?;
+    Ok(())
+}
+
+/// Registers the strategies of the arena file at `path` and plays a tournament between them.
+fn play_arena(options: &cli::Options, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+    let kinds = arena::register(&arena::parse(&text)?)?;
+    play_tournament(options, &kinds)
+}
+
+/// The index of the player who moves first in the first game, flipping a coin if asked to.
+fn first_player(first: cli::First) -> usize {
+    match first {
+        cli::First::Cross  => 0,
+        cli::First::Nought => 1,
+        cli::First::CoinFlip => {
+            let first = usize::from(rand::random::<bool>());
+            if !screen::headless() {
+                println!("The coin flip says {:?} goes first.", [Tile::Cross, Tile::Nought][first]);
+            }
+            first
+        }
+    }
+}
+
+/// The rounds of the `--series` file, none without one.
+fn load_series(options: &cli::Options) -> Result<Vec<series::Round>, String> {
+    let Some(path) = &options.series else { return Ok(Vec::new()) };
+    let text = std::fs::read_to_string(path).map_err(|err| format!("Couldn't read {}: {}", path.display(), err))?;
+    series::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// The games of a match, `None` for a single game: `--best-of` or otherwise one per game of the
+/// series.
+fn match_games(options: &cli::Options, series: &[series::Round]) -> Option<u32> {
+    options.best_of.or((!series.is_empty()).then_some(series.len() as u32))
+}
+
+/// Plays up to `games` games, alternating who starts from `players[first]`, until one side has
+/// won a majority. When there is a series its games are played by turns, starting over after
+/// the last.
+fn play_match(mut setup: setup::Setup, games: u32, first: usize, series: &[series::Round]) -> Result<(), String> {
+    // Games of the series without a board of their own are played on this one.
+    let base = (setup.size, setup.win_length);
+    for (i, round) in series.iter().enumerate() {
+        round.check(base).map_err(|err| format!("Game {} of the series: {}", i + 1, err))?;
+    }
+    let mut split = SplitScore::default();
 
     loop {
-        for p in &players {
-            p.make_move(&mut b);
-            println!("{:?} move:", p.tile());
-            b.print();
+        let score = split.total();
+        if score.games() >= games || score.cross_wins.max(score.nought_wins) > games / 2 {
+            break;
+        }
+        let first = (first + score.games() as usize) % 2;
+        let round = (!series.is_empty()).then(|| &series[score.games() as usize % series.len()]);
+        if let Some(round) = round {
+            round.apply(&mut setup, base).map_err(|err| format!("Game {} of the series: {}", score.games() + 1, err))?;
+        }
+        if !screen::headless() {
+            match round {
+                Some(round) => println!("Game {} of up to {}, {}:", score.games() + 1, games, round.describe(&setup)),
+                None => println!("Game {} of up to {}:", score.games() + 1, games),
+            }
+        }
+
+        let mut game = setup.new_game();
+        let Some(status) = play_game(&setup.players, &setup.shown_names(), first, &mut game).map_err(|err| err.to_string())? else { return Ok(()) };
+        split.record(setup.players[first].tile(), status);
+        record_profiles(&setup, status);
+        if !screen::headless() {
+            println!("Match score: {}", split.total());
+        }
+    }
+
+    if screen::result_only() {
+        return Ok(());
+    }
+    let score = split.total();
+    let (most, least) = (score.cross_wins.max(score.nought_wins), score.cross_wins.min(score.nought_wins));
+    match score.leader() {
+        Some(tile) => println!("{:?} wins the match {}-{}!", tile, most, least),
+        None       => println!("The match is drawn."),
+    }
+    println!("By who moved first: {}", split);
+    Ok(())
+}
 
-            sleep(RESPONSE_PAUSE);
+/// The players of `connect-engine`: the engine at `address` takes the side `players` leaves
+/// open, Nought when both are, and a human the other one.
+fn engine_seats(players: [Option<&'static str>; 2], address: &str) -> Result<[Option<&'static str>; 2], String> {
+    if players.iter().all(Option::is_some) {
+        return Err("connect-engine needs a side for the engine, leave out --cross or --nought.".to_string());
+    }
+    let engine = engine::connect(address)?;
+    let kind = strategy::register(&format!("engine at {}", address), strategy::Algorithm::Engine(engine), strategy::Config::default())?.name;
+    Ok(match players {
+        [Some(cross), _]     => [Some(cross), Some(kind)],
+        [None, Some(nought)] => [Some(kind), Some(nought)],
+        [None, None]         => [Some("human"), Some(kind)],
+    })
+}
+
+/// Plays one game to the end from the position in `game`, starting with `players[first]`.
+/// Returns `None` when a player quits.
+fn play_game(players: &[Player; 2], names: &Names, first: usize, game: &mut Game) -> tick_tack_toe::Result<Option<BoardStatus>> {
+    player::set_names(names.clone());
+    let humans: Vec<Tile> = players.iter().filter(|p| matches!(p, Player::Human(_))).map(Player::tile).collect();
+    screen::set_viewpoint(match humans[..] {
+        [side] => screen::Viewpoint::Side(side),
+        [_, _] => screen::Viewpoint::ToMove,
+        _      => screen::Viewpoint::Everything,
+    });
+    let theme = screen::theme();
+    screen::show_board(game, &theme, None, players[first].tile());
+    game.add_observer(Box::new(MoveLog));
+
+    // Headless games are over in a moment, and mustn't replace the journal of an interactive one.
+    let journaled = !screen::headless();
+    if journaled {
+        let mut record = record(game, players, names);
+        record.set_tag("First", format!("{:?}", players[first].tile()));
+        if let Err(err) = journal::start(&record) {
+            log::warn!("couldn't start the journal: {}", err);
+        }
+    }
+
+    let started = Instant::now();
+    let mut thinking = Vec::new();
+    let status = play_turns(players, names, first, game, &theme, &mut thinking, journaled);
+    interrupt::set_snapshot(None);
+
+    match status {
+        Ok(status) if screen::result_only() => {
+            let resigned = status.is_some() && game.status() == BoardStatus::Continue;
+            println!("{}", render::result_line(status, game, resigned));
+        }
+        Ok(Some(status)) if journaled => {
+            // A resignation ends the game before the board does.
+            let termination = match game.status() {
+                BoardStatus::Continue => Termination::Resignation,
+                _ => Termination::of(status),
+            };
+            if let Err(err) = journal::finish(status, termination) {
+                log::warn!("couldn't finish the journal: {}", err);
+            }
+            print_summary(players, game, &theme, &thinking, started.elapsed(), status);
+        }
+        _ => (),
+    }
+    export_images(game);
+    status
+}
+
+/// Draws the position of `game` to the `--export-svg` and `--export-png` files, with the winning
+/// line or else the last move highlighted, and the moves leading to it to the `--export-gif` one.
+/// `--export-text` gets both as Markdown or HTML.
+fn export_images(game: &Game) {
+    /// Hundredths of a second each move is shown in GIFs.
+    #[cfg(feature = "image")]
+    const GIF_FRAME_DELAY: u16 = 100;
+
+    let highlight = game.board().winning_line()
+        .or_else(|| game.history().last().map(|m| vec![(m.row, m.col)]))
+        .unwrap_or_default();
+
+    let mut images = Vec::new();
+    if let Some(path) = SVG_EXPORT.get() {
+        images.push((path, game.board().to_svg_highlighted(&screen::theme(), &highlight).into_bytes()));
+    }
+    #[cfg(feature = "image")]
+    if let Some(path) = PNG_EXPORT.get() {
+        images.push((path, tick_tack_toe::image::png(&tick_tack_toe::image::draw(game.board(), &highlight))));
+    }
+    #[cfg(feature = "image")]
+    if let Some(path) = GIF_EXPORT.get() {
+        images.push((path, tick_tack_toe::image::gif(game, GIF_FRAME_DELAY)));
+    }
+    if let Some(path) = TEXT_EXPORT.get() {
+        let markdown = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+        let text = if markdown { render::markdown(game, &screen::theme()) } else { render::html(game, &screen::theme()) };
+        images.push((path, text.into_bytes()));
+    }
+    for (path, image) in images {
+        if let Err(err) = std::fs::write(path, image) {
+            log::warn!("couldn't write {}: {}", path.display(), err);
+        }
+    }
+}
+
+/// Logs every move at info level.
+struct MoveLog;
+impl GameObserver for MoveLog {
+    fn on_move(&mut self, m: &Move, _board: &Board) {
+        log::info!("{:?} plays {}", m.tile, coords::format(m.row, m.col));
+    }
+}
 
-            match b.board_status() {
-                BoardStatus::Winner(tile) => {
-                    println!("{:?} has won!", tile);
-                    return;
+fn print_summary(players: &[Player; 2], game: &Game, theme: &Theme, thinking: &[Option<Duration>], duration: Duration, status: BoardStatus) {
+    println!("{}", render::summary(game, theme, thinking, duration));
+    if game.board().length() > setup::MAX_PERFECT_AI_SIZE {
+        return;
+    }
+
+    // Humans who didn't win are walked through every move that cost them, AIs only get their first.
+    for player in players {
+        let side = player.tile();
+        match (player, status) {
+            (_, BoardStatus::Winner(winner)) if winner == side => (),
+            (Player::Human(_), _) => println!("\n{}", render::review(side, game, &game.mistakes(side), theme)),
+            (_, BoardStatus::Winner(_)) => {
+                if let Some((index, better)) = game.first_mistake(side) {
+                    println!("{}", render::mistake(side, index, game, better));
                 }
-                BoardStatus::Tie => {
-                    println!("Tie!");
-                    return;
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Plays turns until the game ends, recording the time each move in the history took in `thinking`.
+fn play_turns(
+    players: &[Player; 2],
+    names: &Names,
+    first: usize,
+    game: &mut Game,
+    theme: &Theme,
+    thinking: &mut Vec<Option<Duration>>,
+    journaled: bool,
+) -> tick_tack_toe::Result<Option<BoardStatus>> {
+    let mut turn = first;
+    loop {
+        let p = &players[turn];
+        let started = Instant::now();
+        match p.take_turn(game)? {
+            Turn::Moved => {
+                // Undone moves leave the history and resumed ones were timed by another run, so
+                // the times are kept in step with the history.
+                thinking.resize(game.history().len() - 1, None);
+                thinking.push(Some(started.elapsed()));
+            }
+            Turn::Resigned => {
+                println!("{}", render::resignation(p.tile(), names));
+                announce(&render::resignation(p.tile(), names));
+                return Ok(Some(BoardStatus::Winner(p.tile().opposite().ok_or(Error::NotASide)?)));
+            }
+            Turn::Quit => return Ok(None),
+        }
+        if let Some(m) = game.history().last() {
+            if journaled {
+                if let Err(err) = journal::record_move(m, thinking.last().copied().flatten()) {
+                    log::warn!("couldn't journal the move: {}", err);
                 }
-                BoardStatus::Continue => ()
             }
         }
+        let mut snapshot = record(game, players, names);
+        snapshot.set_times(thinking);
+        interrupt::set_snapshot(Some(snapshot.to_string()));
+        screen::show_move(game, theme, &render::move_made(p.tile(), names));
+        if let Some(m) = game.history().last() {
+            announce(&render::spoken_move(m.tile, m.row, m.col));
+        }
+
+        sleep(player::response_pause());
+
+        let status = game.status();
+        if status != BoardStatus::Continue {
+            if !screen::headless() {
+                println!("{}", render::named_result(status, names));
+                announce(&render::named_result(status, names));
+            }
+            return Ok(Some(status));
+        }
+
+        turn = 1 - turn;
+    }
+}
+
+/// Speaks `text` with `--speak`, in builds with the audio feature.
+fn announce(text: &str) {
+    #[cfg(feature = "audio")]
+    speech::say(text);
+    #[cfg(not(feature = "audio"))]
+    let _ = text;
+}
+
+/// The record of `game`, tagged with the date, who plays each side and what they are called.
+fn record(game: &Game, players: &[Player; 2], names: &Names) -> Record {
+    let mut record = Record::of(game);
+    record.set_tag("Date", daily::record_date());
+    for p in players {
+        record.set_tag(&format!("{:?}", p.tile()), p.kind());
+        if let Some(name) = names.get(p.tile()) {
+            record.set_tag(&format!("{:?}Name", p.tile()), name);
+        }
     }
+    record
 }
 
-fn ensure_human_move(board: &mut Board, side: Tile) {
-    human_make_move(board, side.clone()).unwrap_or_else(|err| {
-        println!("{}", err);
-        sleep(RESPONSE_PAUSE);
-        ensure_human_move(board, side.clone());
+fn record_profiles(setup: &setup::Setup, status: BoardStatus) {
+    if setup.names.iter().all(Option::is_none) {
+        return;
+    }
+
+    let mut profiles = match Profiles::load() {
+        Ok(profiles) => profiles,
+        Err(err) => return log::error!("couldn't read profiles, the result isn't saved: {}", err),
+    };
+
+    profiles.record_game(setup.names.each_ref().map(Option::as_deref), status, setup.size, setup.win_length);
+    if let Err(err) = profiles.save() {
+        log::error!("couldn't save profiles: {}", err);
+    }
+}
+
+fn print_stats(name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let profiles = Profiles::load().map_err(|err| format!("couldn't read profiles: {}", err))?;
+
+    match name {
+        Some(name) => match profiles.get(name) {
+            Some(profile) => println!("{}", profile),
+            None => println!("No profile named {}.", name),
+        },
+        None if profiles.iter().next().is_none() => println!("No profiles yet."),
+        None => profiles.iter().for_each(|profile| println!("{}", profile)),
+    }
+    Ok(())
+}
+
+fn print_leaderboard() -> Result<(), Box<dyn std::error::Error>> {
+    let profiles = Profiles::load().map_err(|err| format!("couldn't read profiles: {}", err))?;
+
+    let leaders = profiles.leaderboard(profile::LEADERBOARD_SIZE);
+    if leaders.is_empty() {
+        println!("Nobody has played yet.");
+    }
+    for (place, profile) in leaders.iter().enumerate() {
+        println!("{:>4}. {:<16} {:>5}  {:>5.1}% of {} games",
+            place + 1, profile.name, profile.rating, profile.win_rate() * 100.0, profile.games());
+    }
+    Ok(())
+}
+
+/// Shows the game recorded in `path` a move at a time with its annotations, e.g. one downloaded
+/// from a server.
+fn replay(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let record = read_record(path)?;
+
+    let names = Names([Tile::Cross, Tile::Nought].map(|tile| record.tag(&format!("{:?}Name", tile)).map(str::to_string)));
+    let theme = screen::theme();
+    let mut game = Game::with_variant(record.start.clone(), record.variant()?);
+    player::set_names(names.clone());
+    let first = record.moves.first().map_or(Tile::Cross, |m| m.tile);
+    screen::show_board(&game, &theme, None, first);
+    for (m, annotation) in record.moves.iter().zip(&record.annotations) {
+        sleep(player::response_pause());
+        game.play(*m)?;
+        screen::show_move(&game, &theme, &render::move_made(m.tile, &names));
+        announce(&render::spoken_move(m.tile, m.row, m.col));
+        if let Some(text) = render::annotation(annotation) {
+            println!("{}", text);
+        }
+    }
+
+    println!("{}", render::named_result(game.status(), &names));
+    announce(&render::named_result(game.status(), &names));
+    export_images(&game);
+    Ok(())
+}
+
+/// Prints the analysis of the game recorded in `path`, or writes it to `out`, as Markdown when
+/// its name ends in `.md`.
+fn analyze_game(path: &Path, out: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let record = read_record(path)?;
+    if record.start.length() > setup::MAX_PERFECT_AI_SIZE {
+        return Err(format!("Analysis searches every position to the end, which needs a board of at most {0}x{0}.", setup::MAX_PERFECT_AI_SIZE).into());
+    }
+
+    let analysis = analysis::analyze(&record)?;
+    match out {
+        Some(out) => {
+            let markdown = out.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+            let report = if markdown { analysis::markdown(&record, &analysis) } else { analysis::text(&record, &analysis) };
+            std::fs::write(out, report).map_err(|err| format!("couldn't write {}: {}", out.display(), err))?;
+        }
+        None => print!("{}", analysis::text(&record, &analysis)),
+    }
+    Ok(())
+}
+
+/// Prints the number of positions after every number of moves up to `depth`, from `position` or
+/// the empty board.
+fn perft(options: &cli::Options, depth: u32, position: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let board = match position {
+        Some(position) => read_position(options, position)?,
+        None => {
+            let size = options.preset.size.unwrap_or(3);
+            Board::new(size, options.preset.win_length.unwrap_or(size.min(3)))
+        }
+    };
+    if board.win_row_length() > board.length() {
+        return Err("The win length can't be longer than the board size.".into());
+    }
+
+    let side = editor::side_to_move(&board);
+    for depth in 1..=depth {
+        let started = Instant::now();
+        let positions = board.perft(side, depth);
+        println!("Depth {}: {} positions ({:.1}s)", depth, positions, started.elapsed().as_secs_f64());
+    }
+    Ok(())
+}
+
+/// Compares the minimax search, the alpha-beta solver and the symmetries of positions with the
+/// retrograde tablebase for every position of the `--size` board, and fails when any disagree.
+fn verify(options: &cli::Options) -> Result<(), Box<dyn std::error::Error>> {
+    // A few disagreements tell what is wrong, thousands of them only scroll.
+    const SHOWN: usize = 10;
+
+    let size = options.preset.size.unwrap_or(3);
+    let win_length = options.preset.win_length.unwrap_or(size.min(3));
+    if size > setup::MAX_PERFECT_AI_SIZE || win_length > size {
+        return Err(format!("Verifying needs every position of the board, which only works up to {0}x{0}.", setup::MAX_PERFECT_AI_SIZE).into());
+    }
+
+    let started = Instant::now();
+    let tablebase = Tablebase::build(&Board::new(size, win_length), Tile::Cross);
+    println!("The tablebase has {} positions ({:.1}s).", tablebase.len(), started.elapsed().as_secs_f64());
+
+    let mut solver = Solver::new();
+    let minimax = |board: &Board, side| {
+        // The search values the moves, which a finished game has none of.
+        let value = match board.board_status() {
+            BoardStatus::Winner(winner) => if winner == side { 1 } else { -1 },
+            BoardStatus::Tie => 0,
+            BoardStatus::Continue => board.move_values(side).iter().map(|(_, value)| *value).max().unwrap_or(0),
+        };
+        let outcome = match value {
+            1  => solve::Outcome::Win,
+            -1 => solve::Outcome::Loss,
+            _  => solve::Outcome::Draw,
+        };
+        solve::Value { outcome, distance: 0 }
+    };
+    let checks = [
+        ("minimax search", tablebase.check(false, minimax)),
+        ("alpha-beta solver", tablebase.check(true, |board, side| solver.value(board, side))),
+        ("canonical form lookup", tablebase.check(true, |board, side| {
+            tablebase.get(&board.canonical_form(), side).unwrap_or(solve::Value { outcome: solve::Outcome::Draw, distance: usize::MAX })
+        })),
+    ];
+
+    let mut failed = false;
+    for (name, disagreements) in &checks {
+        if disagreements.is_empty() {
+            println!("The {} agrees on every position.", name);
+            continue;
+        }
+        failed = true;
+        println!("The {} disagrees on {} positions:", name, disagreements.len());
+        for d in disagreements.iter().take(SHOWN) {
+            println!("  {} with {:?} to move: {} in the tablebase, {} found", d.board.notation(), d.side, d.expected, d.found);
+        }
+    }
+    match failed {
+        true  => Err("The searches don't agree with the tablebase.".into()),
+        false => Ok(()),
+    }
+}
+
+/// Writes every position the `--size` board can reach from empty with Cross to move first, as
+/// its notation and the side to move, to `--out` or standard output.
+fn list_positions(options: &cli::Options) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufWriter, Write};
+
+    let size = options.preset.size.unwrap_or(3);
+    let win_length = options.preset.win_length.unwrap_or(size.min(3));
+    if win_length > size {
+        return Err("The win length can't be longer than the board size.".into());
+    }
+
+    let out: Box<dyn Write> = match &options.out {
+        Some(path) => Box::new(std::fs::File::create(path).map_err(|err| format!("couldn't create {}: {}", path.display(), err))?),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let mut out = BufWriter::new(out);
+    let mut error = None;
+    let count = enumerate::positions(&Board::new(size, win_length), Tile::Cross, options.distinct, |board, side| {
+        if error.is_none() {
+            error = writeln!(out, "{} {}", board.notation(), side.char()).err();
+        }
     });
+    match error.or_else(|| out.flush().err()) {
+        // Whoever reads the list, e.g. `head`, has seen enough.
+        Some(err) if err.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+        Some(err) => return Err(format!("couldn't write the positions: {}", err).into()),
+        None => (),
+    }
+    if let Some(path) = &options.out {
+        println!("Wrote {} positions to {}.", count, path.display());
+    }
+    Ok(())
+}
+
+/// Prints the value of `position` with best play, the line that leads to it and, with `--proof`,
+/// every reply of the side that can't do better each with the move that answers it.
+fn solve(options: &cli::Options, position: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let board = read_position(options, position)?;
+    let side = editor::side_to_move(&board);
+    editor::check(&board, side)?;
+    if board.board_status() != BoardStatus::Continue {
+        println!("The game is over: {}", render::result(board.board_status()));
+        return Ok(());
+    }
+
+    let started = Instant::now();
+    let mut solver = Solver::new();
+    let value = solver.value(&board, side);
+    let moves = |n: usize| format!("{} move{}", n, if n == 1 { "" } else { "s" });
+    match value.outcome {
+        solve::Outcome::Win  => println!("{:?} to move wins in {}.", side, moves(value.distance)),
+        solve::Outcome::Loss => println!("{:?} to move loses in {}.", side, moves(value.distance)),
+        solve::Outcome::Draw => println!("{:?} to move can't do better than a draw, and neither can {:?}.", side, side.opposite().unwrap_or(side)),
+    }
+    let line: Vec<String> = solver.principal_variation(&board, side).iter()
+        .map(Move::notation)
+        .collect();
+    println!("Best line: {}", line.join(" "));
+    if options.proof {
+        print!("Proof:\n{}", solver.proof(&board, side));
+    }
+    println!("Searched {} positions in {:.1}s.", solver.positions, started.elapsed().as_secs_f64());
+    Ok(())
+}
+
+/// The board in the notation `position`, with the `--win-length` or up to three in a row to win.
+fn read_position(options: &cli::Options, position: &str) -> tick_tack_toe::Result<Board> {
+    let length = position.split('/').count();
+    Board::from_notation(position, options.preset.win_length.unwrap_or(length.min(3)))
 }
 
-fn human_make_move(board: &mut Board, side: Tile) -> Result<(), &'static str> {
-    println!("Make move (x, y): ");
+/// Sets up a position in the editor, then plays on from it. Such games don't count towards the
+/// profiles, they didn't start from an empty board.
+fn edit(options: &cli::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let size = options.preset.size.unwrap_or(3);
+    let win_length = options.preset.win_length.unwrap_or(size.min(3));
+    if win_length > size {
+        return Err("The win length can't be longer than the board size.".into());
+    }
 
-    let mut buf = String::new();
-    std::io::stdin().read_line(&mut buf).or(Err("Couldn't read input."))?;
+    let Some((board, to_move)) = editor::run(Board::new(size, win_length)) else { return Ok(()) };
+    let preset = setup::Preset {
+        players: options.preset.players,
+        names: options.preset.names.clone(),
+        size: Some(size),
+        win_length: Some(win_length),
+        handicap: None,
+        obstacles: 0,
+        variant: options.preset.variant,
+    };
+    let setup = setup::choose_setup(&preset);
 
-    let cordinates: Vec<usize> = buf
-        .split(',')
-        .map(|s|{
-            s.trim()
-                .parse()
-                .or(Err("You need to input proper numbers."))
-        })
-        .collect::<Result<Vec<usize>, &str>>()?;
+    let mut game = Game::with_variant(board, setup.variant);
+    play_game(&setup.players, &setup.shown_names(), usize::from(to_move == Tile::Nought), &mut game)?;
+    Ok(())
+}
 
-    if cordinates.len() != 2 {return Err("Incorrect number of arguments.")}
+/// Opens the position laboratory on the empty `--size` board.
+fn explore(options: &cli::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let size = options.preset.size.unwrap_or(3);
+    let win_length = options.preset.win_length.unwrap_or(size.min(3));
+    if win_length > size {
+        return Err("The win length can't be longer than the board size.".into());
+    }
+    let board = Board::new(size, win_length);
+    lab::check_size(&board)?;
+    lab::run(board, Tile::Cross);
+    Ok(())
+}
 
-    board.set(side, cordinates[1], cordinates[0])?;
-    
+/// Writes the game recorded in `from` to `to`, each in SGF when its name ends in `.sgf`.
+fn convert(from: &Path, to: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let record = read_record(from)?;
+    let text = if is_sgf(to) { sgf::write(&record) } else { record.to_string() };
+    std::fs::write(to, text).map_err(|err| format!("couldn't write {}: {}", to.display(), err))?;
+    Ok(())
+}
+
+fn read_record(path: &Path) -> Result<Record, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+    Ok(if is_sgf(path) { sgf::parse(&text)? } else { Record::parse(&text)? })
+}
+
+fn is_sgf(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("sgf"))
+}
+
+fn play_daily() -> tick_tack_toe::Result<()> {
+    let challenge = daily::Challenge::today();
+    println!("{}", challenge.describe());
+    let name = setup::ask_name(challenge.human);
+
+    let (players, board, first) = challenge.start()?;
+    let names = Names(players.each_ref().map(|p| match p.tile() == challenge.human {
+        true  => name.clone(),
+        false => p.default_name(),
+    }));
+    let mut game = Game::new(board);
+    let Some(status) = play_game(&players, &names, first, &mut game)? else { return Ok(()) };
+
+    let Some(name) = name else { return Ok(()) };
+    let result = daily::DailyResult {
+        date: challenge.date.clone(),
+        name,
+        outcome: Outcome::of(status, challenge.human),
+        moves: game.history().iter().filter(|m| m.tile == challenge.human).count(),
+    };
+    if let Err(err) = daily::save_result(&result) {
+        log::error!("couldn't save the daily result: {}", err);
+    }
+
+    match daily::results(&challenge.date) {
+        Ok(results) => {
+            println!("Results for {}:", challenge.date);
+            for r in results {
+                println!("  {}: {} in {} moves", r.name, r.outcome.name(), r.moves);
+            }
+        }
+        Err(err) => log::error!("couldn't read the daily results: {}", err),
+    }
     Ok(())
 }
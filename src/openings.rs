@@ -0,0 +1,88 @@
+//! Names of the 3x3 openings: the first move and the standard replies to it. They are looked up
+//! by letter notation on the board turned and mirrored so the first move is on a1, a2 or b2.
+
+use crate::board::Board;
+use crate::coords;
+use crate::game::Move;
+
+/// Every opening by its first move and reply, on the board turned so their notation comes first
+/// in the alphabet.
+const OPENINGS: [(&str, Option<&str>, &str); 15] = [
+    ("b2", None,       "Center opening"),
+    ("b2", Some("a1"), "Center opening, corner reply"),
+    ("b2", Some("a2"), "Center opening, edge reply"),
+    ("a1", None,       "Corner opening"),
+    ("a1", Some("b2"), "Corner opening, center reply"),
+    ("a1", Some("a2"), "Corner opening, adjacent edge reply"),
+    ("a1", Some("a3"), "Corner opening, adjacent corner reply"),
+    ("a1", Some("b3"), "Corner opening, far edge reply"),
+    ("a1", Some("c3"), "Corner opening, opposite corner reply"),
+    ("a2", None,       "Edge opening"),
+    ("a2", Some("b2"), "Edge opening, center reply"),
+    ("a2", Some("a1"), "Edge opening, adjacent corner reply"),
+    ("a2", Some("b1"), "Edge opening, adjacent edge reply"),
+    ("a2", Some("c1"), "Edge opening, far corner reply"),
+    ("a2", Some("c2"), "Edge opening, opposite edge reply"),
+];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Opening {
+    pub name: &'static str,
+    /// How many of the first moves the name covers, 1 or 2.
+    pub moves: usize,
+}
+
+/// The opening of a game from `start` with `moves`, when it was played from an empty 3x3 board
+/// with three in a row to win.
+pub fn classify(start: &Board, moves: &[Move]) -> Option<Opening> {
+    if start.length() != 3 || start.win_row_length() != 3 || start.empty_tiles().len() != 9 {
+        return None;
+    }
+    let tiles: Vec<(usize, usize)> = moves.iter().take(2).map(|m| (m.row, m.col)).collect();
+    let first = tiles.first()?;
+
+    // Of the eight ways to turn and mirror the board, the one that names the moves first in the
+    // alphabet, so every symmetric line has one name.
+    let (first, reply) = (0..8)
+        .map(|symmetry| {
+            let notation = |tile| {
+                let (row, col) = start.transform(symmetry, tile);
+                coords::notation(row, col)
+            };
+            (notation(*first), tiles.get(1).map(|&tile| notation(tile)))
+        })
+        .min()?;
+    let (_, _, name) = OPENINGS.iter().find(|(f, r, _)| *f == first && *r == reply.as_deref())?;
+    Some(Opening { name, moves: tiles.len() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify, Opening};
+    use crate::board::{Board, Tile};
+    use crate::coords;
+    use crate::game::Move;
+
+    /// The moves by letter notation, e.g. `["c3", "b2"]`, with Cross moving first.
+    fn moves(tiles: &[&str]) -> Vec<Move> {
+        tiles.iter().zip([Tile::Cross, Tile::Nought]).map(|(tile, side)| {
+            let (row, col) = coords::parse_notation(tile, 3).unwrap();
+            Move { tile: side, row, col, from: None }
+        }).collect()
+    }
+
+    #[test]
+    fn openings() {
+        let board = Board::new(3, 3);
+        let name = |tiles: &[&str]| classify(&board, &moves(tiles)).map(|opening| opening.name);
+        assert_eq!(name(&["b2"]), Some("Center opening"));
+        assert_eq!(name(&["c3", "b2"]), Some("Corner opening, center reply"));
+        assert_eq!(name(&["c1", "a3"]), Some("Corner opening, opposite corner reply"));
+        assert_eq!(name(&["c1", "b3"]), Some("Corner opening, far edge reply"));
+        assert_eq!(name(&["c2", "a2"]), Some("Edge opening, opposite edge reply"));
+        assert_eq!(name(&["b3", "c3"]), Some("Edge opening, adjacent corner reply"));
+        assert_eq!(name(&[]), None);
+        assert_eq!(classify(&board, &moves(&["a1", "b2", "c3"])), Some(Opening { name: "Corner opening, center reply", moves: 2 }));
+        assert_eq!(classify(&Board::new(4, 3), &moves(&["a1"])), None);
+    }
+}
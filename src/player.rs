@@ -0,0 +1,207 @@
+use tick_tack_toe::board::Tile;
+use crate::commands::{self, Action};
+use crate::strategy::{self, Strategy};
+use crate::{cursor, screen};
+use tick_tack_toe::coords;
+use tick_tack_toe::game::{Game, Move};
+use tick_tack_toe::render::{self, Names};
+use tick_tack_toe::variant::{self, Variant};
+use rand::seq::SliceRandom;
+use tick_tack_toe::{Error, Result};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::{thread::sleep, time::Duration};
+
+/// How long to pause after a move or a rejected input, in milliseconds, 800 unless set from the
+/// command line.
+static RESPONSE_PAUSE_MS: AtomicU64 = AtomicU64::new(800);
+
+/// Whether humans are shown the tiles that win, must be blocked or fork before each move.
+static TEACHING: AtomicBool = AtomicBool::new(false);
+
+static NAMES: Mutex<Names> = Mutex::new(Names([None, None]));
+
+pub fn set_response_pause(millis: u64) {
+    RESPONSE_PAUSE_MS.store(millis, Ordering::Relaxed);
+}
+
+pub fn response_pause() -> Duration {
+    Duration::from_millis(RESPONSE_PAUSE_MS.load(Ordering::Relaxed))
+}
+
+pub fn set_teaching(teaching: bool) {
+    TEACHING.store(teaching, Ordering::Relaxed);
+}
+
+/// Names the current game's prompts and results use.
+pub fn set_names(names: Names) {
+    *NAMES.lock().unwrap_or_else(|err| err.into_inner()) = names;
+}
+
+pub fn names() -> Names {
+    NAMES.lock().unwrap_or_else(|err| err.into_inner()).clone()
+}
+
+pub fn teaching() -> bool {
+    TEACHING.load(Ordering::Relaxed)
+}
+
+pub enum Player {
+    Human(Tile),
+    RandomAi(Tile),
+    OptimalAi(Tile),
+    /// An AI from the strategy registry.
+    Ai(Tile, &'static Strategy),
+}
+
+/// How a player's turn ended.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Turn {
+    Moved,
+    Resigned,
+    Quit,
+}
+
+impl Player {
+    pub fn take_turn(&self, game: &mut Game) -> Result<Turn> {
+        if game.board().pieces().is_some() && !matches!(self, Self::Human(_)) {
+            return morris_turn(self, game);
+        }
+        // AIs choose on the board as they see it, in fog of war they find out about a hidden mark
+        // by picking its tile and choose again.
+        let mut view = game.view(self.tile());
+        loop {
+            let (row, col) = match self {
+                Self::Human(tile)        => return Ok(human_turn(game, *tile)),
+                Self::RandomAi(_)        => view.random_move(),
+                Self::OptimalAi(tile)    => view.best_move(*tile).map(|m| (m.row, m.col)),
+                Self::Ai(tile, strategy) => match strategy.choose(&view, *tile) {
+                    // Plugins and remote engines give up by not naming a move.
+                    None if !view.empty_tiles().is_empty() => return Ok(Turn::Resigned),
+                    m => m,
+                },
+            }
+            .ok_or(Error::NoMoves)?;
+
+            match game.board().get(row, col) {
+                Some(Tile::Empty) | None => {
+                    game.apply(self.tile(), row, col)?;
+                    return Ok(Turn::Moved);
+                }
+                Some(hidden) => view.set(hidden, row, col)?,
+            }
+        }
+    }
+
+    /// The player described by `kind` in a game record, or a registered strategy of that name.
+    pub fn from_kind(kind: &str, tile: Tile) -> Option<Player> {
+        match kind {
+            "human"      => Some(Self::Human(tile)),
+            "random AI"  => Some(Self::RandomAi(tile)),
+            "perfect AI" => Some(Self::OptimalAi(tile)),
+            _ => strategy::get(kind).map(|strategy| Self::Ai(tile, strategy)),
+        }
+    }
+
+    /// Short description used in game records.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Human(_)        => "human",
+            Self::RandomAi(_)     => "random AI",
+            Self::OptimalAi(_)    => "perfect AI",
+            Self::Ai(_, strategy) => strategy.name,
+        }
+    }
+
+    /// What an AI goes by without a name of its own, e.g. `Perfect AI`. `None` for humans.
+    pub fn default_name(&self) -> Option<String> {
+        let kind = match self {
+            Self::Human(_) => return None,
+            player => player.kind(),
+        };
+        let mut chars = kind.chars();
+        chars.next().map(|first| first.to_uppercase().chain(chars).collect())
+    }
+
+    pub fn tile(&self) -> Tile {
+        match self {
+            Self::Human(tile)     => *tile,
+            Self::RandomAi(tile)  => *tile,
+            Self::OptimalAi(tile) => *tile,
+            Self::Ai(tile, _)     => *tile,
+        }
+    }
+}
+
+fn human_turn(game: &mut Game, side: Tile) -> Turn {
+    if cursor::enabled() {
+        if let Some(turn) = cursor::human_turn(game, side) {
+            return turn;
+        }
+    }
+    // The marks need the whole board, which fog of war hides.
+    if teaching() && game.variant() == Variant::Standard {
+        if let Some(marked) = render::teaching(game.board(), &screen::theme(), side) {
+            println!("{}", marked);
+        }
+    }
+
+    loop {
+        println!("{}", render::prompt(side, &names()));
+        if game.board().must_move(side) {
+            println!("All your pieces are out, move one to a tile next to it: FROM to TO.");
+        }
+
+        let mut buf = String::new();
+        match std::io::stdin().read_line(&mut buf) {
+            Ok(0) => return Turn::Quit,
+            Ok(_) => (),
+            Err(_) => {
+                println!("Couldn't read input.");
+                continue;
+            }
+        }
+
+        if let Some(command) = commands::find(&buf) {
+            match (command.run)(game, side) {
+                Action::Prompt => continue,
+                Action::Resign => return Turn::Resigned,
+                Action::Quit   => return Turn::Quit,
+            }
+        }
+
+        match human_make_move(game, side, &buf) {
+            Ok(()) => return Turn::Moved,
+            Err(err) => {
+                println!("{}", err);
+                sleep(response_pause());
+            }
+        }
+    }
+}
+
+/// An AI's turn in Morris, where moves can take a piece from one tile to another. The other AIs
+/// and plugins only name tiles to play on, so all but the random AI play the search's move.
+fn morris_turn(player: &Player, game: &mut Game) -> Result<Turn> {
+    let side = player.tile();
+    let m = match player {
+        Player::RandomAi(_) => variant::moves(game.board(), side).choose(&mut rand::thread_rng()).copied(),
+        _ => game.board().best_move(side),
+    }
+    .ok_or(Error::NoMoves)?;
+    game.play(m)?;
+    Ok(Turn::Moved)
+}
+
+/// Plays `1, 2`, or in Morris `0, 0 to 1, 2` to move the piece on 0, 0.
+fn human_make_move(game: &mut Game, side: Tile, input: &str) -> std::result::Result<(), String> {
+    let length = game.board().length();
+    let (from, to) = match input.split_once(" to ") {
+        Some((from, to)) => (Some(coords::parse(from, length)?), to),
+        None => (None, input),
+    };
+    let (row, col) = coords::parse(to, length)?;
+    game.play(Move { tile: side, row, col, from }).map_err(|err| err.to_string())?;
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! Strategies from shared libraries loaded at runtime, so other people's AIs can enter the arena
+//! without rebuilding this program. A plugin exports one C function:
+//!
+//! ```c
+//! /* `position` is the board notation, e.g. "X../.O./...", with the top row first and `side` is
+//!    'X' or 'O'. Returns 0 with the move in *row and *col, counted from 0, or anything else to
+//!    resign. */
+//! int choose_move(const char *position, char side, unsigned win_length, unsigned *row, unsigned *col);
+//! ```
+
+use std::ffi::{c_char, c_int, c_uint, CString};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tick_tack_toe::board::{Board, Tile};
+use tick_tack_toe::log;
+
+type ChooseMove = unsafe extern "C" fn(*const c_char, c_char, c_uint, *mut c_uint, *mut c_uint) -> c_int;
+
+pub struct Plugin {
+    pub path: PathBuf,
+    choose_move: ChooseMove,
+}
+impl Plugin {
+    /// The plugin's move for `side`, `None` when it resigns or names a tile that can't be played.
+    pub fn choose(&self, board: &Board, side: Tile) -> Option<(usize, usize)> {
+        let position = CString::new(board.notation()).ok()?;
+        let (mut row, mut col): (c_uint, c_uint) = (0, 0);
+        let side_char = side.char().as_bytes()[0] as c_char;
+
+        // SAFETY: a plugin states that choose_move has this signature by exporting it, and every
+        // pointer passed is valid for the duration of the call.
+        let status = unsafe { (self.choose_move)(position.as_ptr(), side_char, board.win_row_length() as c_uint, &mut row, &mut col) };
+        if status != 0 {
+            return None;
+        }
+
+        let (row, col) = (row as usize, col as usize);
+        if board.get(row, col) != Some(Tile::Empty) {
+            log::warn!("plugin {} tried to play the unavailable tile {}, {}", self.path.display(), col, row);
+            return None;
+        }
+        Some((row, col))
+    }
+}
+
+/// The function pointer can't be compared or printed meaningfully, the library path identifies it.
+impl PartialEq for Plugin {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl fmt::Debug for Plugin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Plugin({})", self.path.display())
+    }
+}
+
+/// Loads the library at `path` and finds its `choose_move`. Libraries stay loaded until the
+/// program exits.
+#[cfg(unix)]
+pub fn load(path: &Path) -> Result<&'static Plugin, String> {
+    use std::ffi::CStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let name = CString::new(path.as_os_str().as_bytes()).map_err(|_| "Plugin paths can't contain NUL bytes.".to_string())?;
+    let error = || {
+        // SAFETY: dlerror returns NULL or a NUL terminated message valid until the next dl call.
+        let message = unsafe { libc::dlerror() };
+        match message.is_null() {
+            true  => "unknown error".to_string(),
+            false => unsafe { CStr::from_ptr(message) }.to_string_lossy().into_owned(),
+        }
+    };
+
+    // SAFETY: loading a library runs its initializers, which a plugin is trusted with like any
+    // other code the user chooses to run.
+    let handle = unsafe { libc::dlopen(name.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+    if handle.is_null() {
+        return Err(format!("Couldn't load the plugin {}: {}", path.display(), error()));
+    }
+    // SAFETY: handle is a library that is never closed, and the name is NUL terminated.
+    let symbol = unsafe { libc::dlsym(handle, c"choose_move".as_ptr()) };
+    if symbol.is_null() {
+        return Err(format!("The plugin {} has no choose_move function.", path.display()));
+    }
+
+    // SAFETY: the exported choose_move is documented to have this signature.
+    let choose_move = unsafe { std::mem::transmute::<*mut libc::c_void, ChooseMove>(symbol) };
+    Ok(Box::leak(Box::new(Plugin { path: path.to_path_buf(), choose_move })))
+}
+
+#[cfg(not(unix))]
+pub fn load(path: &Path) -> Result<&'static Plugin, String> {
+    Err(format!("Couldn't load the plugin {}: plugins need a Unix system.", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::load;
+    use std::path::Path;
+
+    #[test]
+    fn load_errors() {
+        let missing = load(Path::new("/nonexistent/libbot.so")).unwrap_err();
+        assert!(missing.starts_with("Couldn't load the plugin /nonexistent/libbot.so"), "{}", missing);
+    }
+}
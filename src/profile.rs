@@ -0,0 +1,275 @@
+use tick_tack_toe::board::{BoardStatus, Tile};
+use tick_tack_toe::rating::{self, DEFAULT_RATING};
+use std::{fmt, fs, io, path::PathBuf};
+
+const PROFILES_FILE: &str = "profiles.txt";
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+impl Outcome {
+    /// The outcome of a finished game for the player of `tile`.
+    pub fn of(status: BoardStatus, tile: Tile) -> Self {
+        match status {
+            BoardStatus::Winner(winner) if winner == tile => Self::Win,
+            BoardStatus::Winner(_) => Self::Loss,
+            BoardStatus::Tie | BoardStatus::Continue => Self::Draw,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Win  => "win",
+            Self::Loss => "loss",
+            Self::Draw => "draw",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Profile {
+    pub name: String,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    /// Positive while on a winning streak, negative while on a losing one.
+    pub streak: i32,
+    pub best_streak: u32,
+    /// Elo rating from games between two profiles.
+    pub rating: i32,
+    /// Games played per `(size, win_length)` setting.
+    pub settings: Vec<((usize, usize), u32)>,
+}
+impl Profile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            streak: 0,
+            best_streak: 0,
+            rating: DEFAULT_RATING,
+            settings: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, outcome: Outcome, size: usize, win_length: usize) {
+        match outcome {
+            Outcome::Win => {
+                self.wins += 1;
+                self.streak = self.streak.max(0) + 1;
+                self.best_streak = self.best_streak.max(self.streak as u32);
+            }
+            Outcome::Loss => {
+                self.losses += 1;
+                self.streak = self.streak.min(0) - 1;
+            }
+            Outcome::Draw => {
+                self.draws += 1;
+                self.streak = 0;
+            }
+        }
+
+        match self.settings.iter_mut().find(|(s, _)| *s == (size, win_length)) {
+            Some((_, count)) => *count += 1,
+            None => self.settings.push(((size, win_length), 1)),
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    /// The share of games won, 0 before the first game.
+    pub fn win_rate(&self) -> f64 {
+        f64::from(self.wins) / f64::from(self.games().max(1))
+    }
+
+    /// The most played `(size, win_length)` setting.
+    pub fn favorite_settings(&self) -> Option<(usize, usize)> {
+        self.settings.iter().max_by_key(|(_, count)| *count).map(|(s, _)| *s)
+    }
+
+    fn to_line(&self) -> String {
+        let settings = self.settings.iter()
+            .map(|((size, win), count)| format!("{}x{}:{}", size, win, count))
+            .collect::<Vec<String>>()
+            .join(",");
+
+        format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.name, self.wins, self.losses, self.draws, self.streak, self.best_streak, settings, self.rating)
+    }
+
+    /// Reads `to_line`'s format, profiles saved before ratings existed have 7 fields.
+    fn from_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 7 && fields.len() != 8 { return None }
+
+        let settings = fields[6].split(',')
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                let (setting, count) = s.split_once(':')?;
+                let (size, win) = setting.split_once('x')?;
+                Some(((size.parse().ok()?, win.parse().ok()?), count.parse().ok()?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            name: fields[0].to_string(),
+            wins: fields[1].parse().ok()?,
+            losses: fields[2].parse().ok()?,
+            draws: fields[3].parse().ok()?,
+            streak: fields[4].parse().ok()?,
+            best_streak: fields[5].parse().ok()?,
+            rating: fields.get(7).map_or(Some(DEFAULT_RATING), |r| r.parse().ok())?,
+            settings,
+        })
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}): {} wins, {} losses, {} draws", self.name, self.rating, self.wins, self.losses, self.draws)?;
+
+        match self.streak {
+            s if s > 0 => write!(f, ", {} win streak", s)?,
+            s if s < 0 => write!(f, ", {} loss streak", -s)?,
+            _ => (),
+        }
+        write!(f, ", best streak {}", self.best_streak)?;
+
+        if let Some((size, win_length)) = self.favorite_settings() {
+            write!(f, ", favorite board {0}x{0} with {1} in a row", size, win_length)?;
+        }
+        Ok(())
+    }
+}
+
+/// Players shown by `leaderboard`.
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// All registered profiles, kept in a tab separated file in the data directory.
+#[derive(Default)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+}
+impl Profiles {
+    pub fn load() -> io::Result<Self> {
+        let text = match fs::read_to_string(path()) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err),
+        };
+
+        let profiles = text.lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| Profile::from_line(line).ok_or(io::ErrorKind::InvalidData))
+            .collect::<Result<Vec<Profile>, _>>()?;
+
+        Ok(Self { profiles })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let text: String = self.profiles.iter().map(|p| p.to_line() + "\n").collect();
+
+        fs::create_dir_all(data_dir())?;
+        fs::write(path(), text)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    pub fn get_or_create(&mut self, name: &str) -> &mut Profile {
+        let i = self.profiles.iter().position(|p| p.name == name).unwrap_or_else(|| {
+            self.profiles.push(Profile::new(name));
+            self.profiles.len() - 1
+        });
+        &mut self.profiles[i]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Profile> {
+        self.profiles.iter()
+    }
+
+    /// The `count` best profiles that have played, by rating and then win rate.
+    pub fn leaderboard(&self, count: usize) -> Vec<&Profile> {
+        let mut ranked: Vec<&Profile> = self.profiles.iter().filter(|p| p.games() > 0).collect();
+        ranked.sort_by(|a, b| b.rating.cmp(&a.rating).then(b.win_rate().total_cmp(&a.win_rate())));
+        ranked.truncate(count);
+        ranked
+    }
+
+    /// Records a finished game for the named players of `[Cross, Nought]`, and rates it when
+    /// both have a profile.
+    pub fn record_game(&mut self, names: [Option<&str>; 2], status: BoardStatus, size: usize, win_length: usize) {
+        for (name, tile) in names.iter().zip([Tile::Cross, Tile::Nought]) {
+            if let Some(name) = name {
+                self.get_or_create(name).record(Outcome::of(status, tile), size, win_length);
+            }
+        }
+
+        let [Some(cross), Some(nought)] = names else { return };
+        let score = match Outcome::of(status, Tile::Cross) {
+            Outcome::Win  => 1.0,
+            Outcome::Draw => 0.5,
+            Outcome::Loss => 0.0,
+        };
+        let (cross_rating, nought_rating) = rating::update(self.get_or_create(cross).rating, self.get_or_create(nought).rating, score);
+        self.get_or_create(cross).rating = cross_rating;
+        self.get_or_create(nought).rating = nought_rating;
+    }
+}
+
+/// Where profiles and other saved state live, `$TIC_TAC_TOE_DIR` or `~/.tic-tac-toe`.
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("TIC_TAC_TOE_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    match std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+        Some(home) => PathBuf::from(home).join(".tic-tac-toe"),
+        None => PathBuf::from(".tic-tac-toe"),
+    }
+}
+
+fn path() -> PathBuf {
+    data_dir().join(PROFILES_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Outcome::*, Profile, Profiles};
+    use tick_tack_toe::board::BoardStatus::Winner;
+
+    #[test]
+    fn record_and_round_trip() {
+        let mut p = Profile::new("Alice");
+        for outcome in [Win, Win, Loss, Draw, Win, Win, Win] {
+            p.record(outcome, 3, 3);
+        }
+        p.record(Loss, 4, 3);
+
+        assert_eq!((p.wins, p.losses, p.draws), (5, 2, 1));
+        assert_eq!(p.streak, -1);
+        assert_eq!(p.best_streak, 3);
+        assert_eq!(p.favorite_settings(), Some((3, 3)));
+
+        assert_eq!(Profile::from_line(&p.to_line()), Some(p));
+        assert_eq!(Profile::from_line("Bob\t1\t2"), None);
+        assert_eq!(Profile::from_line("Bob\t1\t2\t0\t1\t1\t").map(|p| p.rating), Some(1200));
+
+        let mut profiles = Profiles::default();
+        profiles.record_game([Some("Alice"), Some("Bob")], Winner(tick_tack_toe::board::Tile::Cross), 3, 3);
+        profiles.record_game([None, Some("Bob")], Winner(tick_tack_toe::board::Tile::Cross), 3, 3);
+        assert_eq!(profiles.get("Alice").map(|p| (p.wins, p.rating)), Some((1, 1216)));
+        assert_eq!(profiles.get("Bob").map(|p| (p.losses, p.rating)), Some((2, 1184)));
+        profiles.get_or_create("Carol");
+        let names: Vec<&str> = profiles.leaderboard(10).iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Alice", "Bob"]);
+    }
+}
@@ -0,0 +1,33 @@
+//! Elo ratings, the expected score of a player follows from the difference in ratings.
+
+pub const DEFAULT_RATING: i32 = 1200;
+
+/// How far one game can move a rating.
+const K: f64 = 32.0;
+
+/// The score `a` is expected to get against `b`, from 0 (certain loss) to 1 (certain win).
+pub fn expected(a: i32, b: i32) -> f64 {
+    1.0 / (1.0 + 10f64.powf((b - a) as f64 / 400.0))
+}
+
+/// The new ratings of `a` and `b` after a game in which `a` scored `score`: 1 for a win, 0.5 for
+/// a draw and 0 for a loss.
+pub fn update(a: i32, b: i32, score: f64) -> (i32, i32) {
+    let change = (K * (score - expected(a, b))).round() as i32;
+    (a + change, b - change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expected, update};
+
+    #[test]
+    fn elo() {
+        assert_eq!(expected(1200, 1200), 0.5);
+        assert!((expected(1600, 1200) - 0.909).abs() < 0.001);
+
+        assert_eq!(update(1200, 1200, 1.0), (1216, 1184));
+        assert_eq!(update(1200, 1200, 0.5), (1200, 1200));
+        assert_eq!(update(1600, 1200, 0.0), (1571, 1229));
+    }
+}
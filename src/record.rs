@@ -0,0 +1,364 @@
+use crate::board::{Board, BoardStatus, Tile};
+use crate::coords;
+use crate::game::{Game, Move};
+use crate::variant::Variant;
+use crate::{Error, Result};
+use std::fmt;
+use std::time::Duration;
+
+/// A game written down in a PGN like text format, `[Tag "value"]` lines followed by the moves:
+///
+/// ```text
+/// [Size "3"]
+/// [WinLength "3"]
+/// 1. Xb2 Oa1? {The corners lose.} 2. Xc3
+/// ```
+///
+/// `Size`, `WinLength` and `Position` (the starting position, when not empty) describe `start`,
+/// any other tags are kept in `tags`. Finished games have a `Result` of `X`, `O` or `draw` and a
+/// `Termination`, see `set_result`, and games played here a `Date` like `2026.10.14`. Games of
+/// another variant than the standard one name it in a `Variant` tag, e.g. `fog`. Moves can
+/// be annotated with a glyph and a `{comment}`, and the time the move took as in PGN, e.g.
+/// `{[%emt 0:00:02.5]}`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Record {
+    pub tags: Vec<(String, String)>,
+    pub start: Board,
+    pub moves: Vec<Move>,
+    /// One for every move, most of them empty.
+    pub annotations: Vec<Annotation>,
+}
+
+/// The glyphs a move can be marked with, best first.
+pub const GLYPHS: [&str; 6] = ["!!", "!", "!?", "?!", "?", "??"];
+
+/// What an annotator thought of a move.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Annotation {
+    /// One of `GLYPHS`.
+    pub glyph: Option<&'static str>,
+    /// Free text, without `}`.
+    pub comment: Option<String>,
+    /// How long the player thought about the move.
+    pub time: Option<Duration>,
+}
+
+/// How a game came to an end, the value of the `Termination` tag.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Termination {
+    /// The winner completed a line.
+    Line,
+    /// Nobody can complete a line any more.
+    Draw,
+    Resignation,
+    /// The side to move stopped moving.
+    Timeout,
+}
+impl Termination {
+    /// How a game with `status` ended when nobody gave up.
+    pub fn of(status: BoardStatus) -> Self {
+        match status {
+            BoardStatus::Winner(_) => Self::Line,
+            BoardStatus::Tie       => Self::Draw,
+            BoardStatus::Continue  => Self::Timeout,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Line        => "line",
+            Self::Draw        => "draw",
+            Self::Resignation => "resignation",
+            Self::Timeout     => "timeout",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        [Self::Line, Self::Draw, Self::Resignation, Self::Timeout].into_iter().find(|t| t.name() == name)
+    }
+}
+
+/// The `Result` tag of a game with `status`: the winner's mark, `draw`, or `*` while unfinished
+/// as in PGN.
+pub fn result(status: BoardStatus) -> &'static str {
+    match status {
+        BoardStatus::Winner(tile) => tile.char(),
+        BoardStatus::Tie          => "draw",
+        BoardStatus::Continue     => "*",
+    }
+}
+impl Record {
+    pub fn of(game: &Game) -> Self {
+        let tags = match game.variant() {
+            Variant::Standard => Vec::new(),
+            variant => vec![("Variant".to_string(), variant.name().to_string())],
+        };
+        Self {
+            tags,
+            start: game.start().clone(),
+            moves: game.history().to_vec(),
+            annotations: vec![Annotation::default(); game.history().len()],
+        }
+    }
+
+    /// The variant in the `Variant` tag, the standard rules without one.
+    pub fn variant(&self) -> Result<Variant> {
+        match self.tag("Variant") {
+            Some(name) => Variant::named(name).ok_or(Error::InvalidNotation("The Variant tag names no variant.")),
+            None => Ok(Variant::Standard),
+        }
+    }
+
+    /// The numbered moves with their annotations, e.g. `1. Xb2 Oa1? {The corners lose.} 2. Xc3`.
+    pub fn movetext(&self) -> String {
+        let moves: Vec<String> = self.moves.iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let annotation = self.annotations.get(i).cloned().unwrap_or_default();
+                let mut text = format!("{}{}", m.notation(), annotation.glyph.unwrap_or(""));
+                let comment = annotation.comment.map(|comment| comment.replace('}', ""));
+                match (annotation.time, comment) {
+                    (Some(time), Some(comment)) => text.push_str(&format!(" {{[%emt {}] {}}}", clock(time), comment)),
+                    (Some(time), None)          => text.push_str(&format!(" {{[%emt {}]}}", clock(time))),
+                    (None, Some(comment))       => text.push_str(&format!(" {{{}}}", comment)),
+                    (None, None)                => (),
+                }
+                text
+            })
+            .collect();
+        moves.chunks(2)
+            .enumerate()
+            .map(|(i, pair)| format!("{}. {}", i + 1, pair.join(" ")))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => self.tags.push((name.to_string(), value)),
+        }
+    }
+
+    /// Tags the game as over: `status` is the result, e.g. the other side's win after a
+    /// resignation, and `termination` how it came about.
+    pub fn set_result(&mut self, status: BoardStatus, termination: Termination) {
+        self.set_tag("Result", result(status));
+        self.set_tag("Termination", termination.name());
+    }
+
+    /// How the game ended, when it's tagged.
+    pub fn termination(&self) -> Option<Termination> {
+        self.tag("Termination").and_then(Termination::parse)
+    }
+
+    /// Sets the time each move took, `times` in the order of the moves.
+    pub fn set_times(&mut self, times: &[Option<Duration>]) {
+        for (annotation, time) in self.annotations.iter_mut().zip(times) {
+            annotation.time = *time;
+        }
+    }
+
+    /// Replays the moves from the starting position.
+    pub fn to_game(&self) -> Result<Game> {
+        let mut game = Game::with_variant(self.start.clone(), self.variant()?);
+        for m in &self.moves {
+            game.play(*m)?;
+        }
+        Ok(game)
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut tags = Vec::new();
+        let mut movetext = String::new();
+
+        for line in text.lines().map(str::trim) {
+            if line.starts_with('[') {
+                tags.push(parse_tag(line).ok_or(Error::InvalidNotation("Tags look like [Name \"value\"]."))?);
+            } else {
+                movetext.push_str(line);
+                movetext.push(' ');
+            }
+        }
+
+        let take = |tags: &mut Vec<(String, String)>, name: &str| {
+            tags.iter().position(|(n, _)| n == name).map(|i| tags.remove(i).1)
+        };
+        let number = |value: Option<String>, default: Option<usize>| match value {
+            Some(v) => v.parse().map_err(|_| Error::InvalidNotation("Size and WinLength must be numbers.")),
+            None => default.ok_or(Error::InvalidNotation("A record needs a Size tag.")),
+        };
+
+        let size = number(take(&mut tags, "Size"), None)?;
+        let win_length = number(take(&mut tags, "WinLength"), Some(size.min(3)))?;
+        let start = match take(&mut tags, "Position") {
+            Some(position) => Board::from_notation(&position, win_length)?,
+            None => Board::from_notation(&vec![".".repeat(size); size].join("/"), win_length)?,
+        };
+        if start.length() != size {
+            return Err(Error::InvalidNotation("The Position doesn't match the Size."));
+        }
+
+        let (moves, annotations) = parse_movetext(&movetext, size)?;
+        Ok(Self { tags, start, moves, annotations })
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "[Size \"{}\"]", self.start.length())?;
+        writeln!(f, "[WinLength \"{}\"]", self.start.win_row_length())?;
+        if self.start.empty_tiles().len() != self.start.length().pow(2) {
+            writeln!(f, "[Position \"{}\"]", self.start.notation())?;
+        }
+        for (name, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+
+        writeln!(f, "{}", self.movetext())
+    }
+}
+
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(if c == '\\' { chars.next()? } else { c });
+    }
+    Some((name.to_string(), unescaped))
+}
+
+/// The moves and their annotations, skipping move numbers.
+fn parse_movetext(mut text: &str, size: usize) -> Result<(Vec<Move>, Vec<Annotation>)> {
+    let (mut moves, mut annotations) = (Vec::new(), Vec::<Annotation>::new());
+
+    loop {
+        text = text.trim_start();
+        if text.is_empty() {
+            return Ok((moves, annotations));
+        }
+
+        if let Some(rest) = text.strip_prefix('{') {
+            let (comment, rest) = rest.split_once('}').ok_or(Error::InvalidNotation("Comments end with '}'."))?;
+            let annotation = annotations.last_mut().ok_or(Error::InvalidNotation("Comments follow the move they are about."))?;
+            let mut comment = comment.trim_start();
+            if let Some((time, rest)) = comment.strip_prefix("[%emt ").and_then(|time| time.split_once(']')) {
+                annotation.time = Some(parse_clock(time).ok_or(Error::InvalidNotation("Move times look like [%emt 0:00:02.5]."))?);
+                comment = rest;
+            }
+            let comment = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+            annotation.comment = (!comment.is_empty()).then_some(comment);
+            text = rest;
+            continue;
+        }
+
+        let end = text.find(|c: char| c.is_whitespace() || c == '{').unwrap_or(text.len());
+        let (token, rest) = text.split_at(end);
+        text = rest;
+        if token.ends_with('.') {
+            continue;
+        }
+
+        let unmarked = token.trim_end_matches(['!', '?']);
+        let glyph = match &token[unmarked.len()..] {
+            "" => None,
+            glyph => Some(*GLYPHS.iter().find(|g| **g == glyph).ok_or(Error::InvalidNotation("Moves are marked with !!, !, !?, ?!, ? or ??."))?),
+        };
+        moves.push(parse_move(unmarked, size)?);
+        annotations.push(Annotation { glyph, ..Annotation::default() });
+    }
+}
+
+/// A time as `h:mm:ss.s`, the way PGN writes the time a move took.
+pub fn clock(time: Duration) -> String {
+    let tenths = (time.as_secs_f64() * 10.0).round() as u64;
+    let seconds = tenths / 10;
+    format!("{}:{:02}:{:02}.{}", seconds / 3600, seconds / 60 % 60, seconds % 60, tenths % 10)
+}
+
+fn parse_clock(text: &str) -> Option<Duration> {
+    let mut parts = text.trim().rsplit(':');
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+    let hours: u64 = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+    if parts.next().is_some() || !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+    Some(Duration::from_secs((hours * 60 + minutes) * 60) + Duration::from_secs_f64(seconds))
+}
+
+fn parse_move(token: &str, size: usize) -> Result<Move> {
+    const INVALID_MOVE: Error = Error::InvalidNotation("Moves look like Xb2 or Oa1, or Xa1-b2 for a piece moved.");
+
+    let mut chars = token.chars();
+    let tile = chars.next().and_then(Tile::from_char).filter(Tile::is_player).ok_or(INVALID_MOVE)?;
+    let tile_at = |notation| coords::parse_notation(notation, size).map_err(|_| INVALID_MOVE);
+    let (from, to) = match chars.as_str().split_once('-') {
+        Some((from, to)) => (Some(tile_at(from)?), to),
+        None => (None, chars.as_str()),
+    };
+    let (row, col) = tile_at(to)?;
+
+    Ok(Move { tile, row, col, from })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Annotation, Record, Termination};
+    use std::time::Duration;
+    use crate::board::{Board, BoardStatus, Tile::*};
+    use crate::game::Game;
+
+    #[test]
+    fn write_and_parse() {
+        let mut start = Board::new(3, 3);
+        start.set(Nought, 0, 0).unwrap();
+
+        let mut game = Game::new(start);
+        game.apply(Cross, 1, 1).unwrap();
+        game.apply(Nought, 2, 0).unwrap();
+        game.apply(Cross, 0, 2).unwrap();
+
+        let mut record = Record::of(&game);
+        record.set_tag("Cross", "Alice \"A\"");
+
+        let text = record.to_string();
+        assert_eq!(text, "[Size \"3\"]\n[WinLength \"3\"]\n[Position \"O../.../...\"]\n[Cross \"Alice \\\"A\\\"\"]\n1. Xb2 Oa3 2. Xc1\n");
+
+        let parsed = Record::parse(&text).unwrap();
+        assert_eq!(parsed, record);
+        assert_eq!(parsed.to_game().unwrap().board(), game.board());
+
+        record.annotations[1] = Annotation { glyph: Some("?"), comment: Some("The corner was safer.".to_string()), time: None };
+        record.annotations[2].glyph = Some("!!");
+        let text = record.to_string();
+        assert!(text.ends_with("1. Xb2 Oa3? {The corner was safer.} 2. Xc1!!\n"));
+        assert_eq!(Record::parse(&text).unwrap(), record);
+
+        record.set_times(&[Some(Duration::from_millis(2500)), Some(Duration::from_secs(3725)), None]);
+        let text = record.to_string();
+        assert!(text.ends_with("1. Xb2 {[%emt 0:00:02.5]} Oa3? {[%emt 1:02:05.0] The corner was safer.} 2. Xc1!!\n"), "{}", text);
+        assert_eq!(Record::parse(&text).unwrap(), record);
+        assert!(Record::parse("[Size \"3\"]\n1. Xb2 {[%emt soon]}\n").is_err());
+
+        record.set_result(BoardStatus::Winner(Cross), Termination::Resignation);
+        let text = record.to_string();
+        assert!(text.contains("[Result \"X\"]\n[Termination \"resignation\"]\n"), "{}", text);
+        assert_eq!(Record::parse(&text).unwrap().termination(), Some(Termination::Resignation));
+        assert!(Record::parse("[Size \"3\"]\n1. Xb2 {unfinished\n").is_err());
+        assert!(Record::parse("[Size \"3\"]\n1. Xb2?!?\n").is_err());
+
+        assert!(Record::parse("[WinLength \"3\"]\n").is_err());
+        assert!(Record::parse("[Size \"3\"]\n1. Xd1\n").is_err());
+        assert!(Record::parse("[Size \"3\"]\n1. Xa1 Oa1\n").unwrap().to_game().is_err());
+    }
+}
@@ -0,0 +1,921 @@
+use crate::board::{Board, BoardStatus, Tile};
+use crate::coords;
+use crate::openings;
+use crate::game::{Game, Mistake};
+use crate::record::{Annotation, Record};
+use std::time::Duration;
+
+/// The characters a board is drawn with, and the colors.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Theme {
+    pub horizontal: char,
+    pub vertical: char,
+    pub empty: char,
+    pub cross: char,
+    pub nought: char,
+    pub blocked: char,
+    pub palette: Palette,
+    /// The colors the terminal has, which the palette's are brought down to.
+    pub colors: ColorDepth,
+    /// Tiles are drawn this many times taller, and about twice as many times wider, with the
+    /// marks drawn large out of their glyphs. 1 is one character per tile.
+    pub scale: usize,
+    /// Whether the board is drawn on a grid or condensed, see `Layout`.
+    pub layout: Layout,
+}
+impl Theme {
+    pub const ASCII: Theme = Theme {
+        horizontal: '=',
+        vertical: '|',
+        empty: ' ',
+        cross: 'X',
+        nought: 'O',
+        blocked: '#',
+        palette: Palette::PLAIN,
+        colors: ColorDepth::Ansi256,
+        scale: 1,
+        layout: Layout::Auto,
+    };
+    /// Lines drawn with box-drawing characters, for terminals that can show them.
+    pub const BOX: Theme = Theme {
+        horizontal: '─',
+        vertical: '│',
+        blocked: '█',
+        ..Self::ASCII
+    };
+    /// Emoji marks on emoji squares, for chat frontends and terminals that draw emoji well. Each
+    /// tile is two columns wide.
+    pub const EMOJI: Theme = Theme {
+        empty: '⬜',
+        cross: '❌',
+        nought: '⭕',
+        blocked: '⬛',
+        ..Self::BOX
+    };
+    pub const MAX_SCALE: usize = 8;
+
+    /// The theme called `name` on the command line, `ascii`, `box` or `emoji`.
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "ascii" => Some(Self::ASCII),
+            "box"   => Some(Self::BOX),
+            "emoji" => Some(Self::EMOJI),
+            _ => None,
+        }
+    }
+
+    /// Whether the board is drawn in ASCII alone, which every terminal can show.
+    pub fn is_ascii(&self) -> bool {
+        [self.horizontal, self.vertical, self.empty, self.cross, self.nought, self.blocked].iter().all(char::is_ascii)
+            && !matches!(self.layout, Layout::HalfBlock | Layout::Braille)
+    }
+
+    pub fn glyph(&self, tile: Tile) -> char {
+        match tile {
+            Tile::Empty   => self.empty,
+            Tile::Cross   => self.cross,
+            Tile::Nought  => self.nought,
+            Tile::Blocked => self.blocked,
+        }
+    }
+
+    /// The columns every glyph of the board is given, 2 when one of them is as wide as an emoji.
+    pub fn cell_width(&self) -> usize {
+        [self.empty, self.cross, self.nought, self.blocked].into_iter().map(width).max().unwrap_or(1)
+    }
+
+    /// The size of a tile in terminal columns and rows, without the grid.
+    pub fn tile_size(&self) -> (usize, usize) {
+        let (columns, rows) = self.tile_cells();
+        (columns * self.cell_width(), rows)
+    }
+
+    /// The size of a tile in glyphs, each drawn `cell_width` columns wide.
+    fn tile_cells(&self) -> (usize, usize) {
+        let scale = self.scale.max(1);
+        (4 * scale - 3, 2 * scale - 1)
+    }
+
+    /// The rows of characters a tile holding `tile` is drawn as, `glyph` alone in the middle of an
+    /// empty one, a large cross or ring of it for a mark and all of it for a blocked tile. Narrow glyphs on a theme of wide
+    /// ones, such as the letters of teaching marks, are padded to keep the columns lined up.
+    pub fn tile_rows(&self, glyph: char, tile: Tile) -> Vec<String> {
+        let (width, height) = self.tile_cells();
+        let cell = self.cell_width();
+        let inked = format!("{}{}", glyph, " ".repeat(cell.saturating_sub(self::width(glyph))));
+        let blank = " ".repeat(cell);
+        (0..height).map(|row| {
+            (0..width).map(|col| {
+                let ink = match tile {
+                    _ if width == 1 => true,
+                    Tile::Blocked => true,
+                    Tile::Empty  => (row, col) == (height / 2, width / 2),
+                    Tile::Cross  => col == 2 * row || col == width - 1 - 2 * row,
+                    Tile::Nought => match row == 0 || row == height - 1 {
+                        true  => (1..width - 1).contains(&col),
+                        false => col == 0 || col == width - 1,
+                    },
+                };
+                if ink { inked.as_str() } else { blank.as_str() }
+            }).collect()
+        }).collect()
+    }
+}
+
+/// The columns `c` takes up on a terminal: 2 for emoji and East Asian wide characters, 1 for the
+/// rest.
+pub fn width(c: char) -> usize {
+    const WIDE: [(u32, u32); 22] = [
+        (0x1100, 0x115f), (0x231a, 0x231b), (0x23e9, 0x23ec), (0x25fd, 0x25fe), (0x2614, 0x2615),
+        (0x2648, 0x2653), (0x26aa, 0x26ab), (0x26bd, 0x26be), (0x2705, 0x2705), (0x274c, 0x274c),
+        (0x274e, 0x274e), (0x2753, 0x2757), (0x2b1b, 0x2b1c), (0x2b50, 0x2b50), (0x2b55, 0x2b55),
+        (0x2e80, 0xa4cf), (0xac00, 0xd7a3), (0xf900, 0xfaff), (0xfe30, 0xfe4f), (0xff00, 0xff60),
+        (0xffe0, 0xffe6), (0x1f300, 0x1faff),
+    ];
+    let c = c as u32;
+    match WIDE.iter().any(|&(first, last)| (first..=last).contains(&c)) {
+        true  => 2,
+        false => 1,
+    }
+}
+/// How the tiles of a board are laid out on the terminal.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Layout {
+    /// A grid below `COMPACT_FROM` tiles across, compact from there on.
+    Auto,
+    /// Every tile in its own box of grid lines, `Theme::scale` times as large.
+    Grid,
+    /// One character per tile and no grid lines, with rulers every 5 rows and columns, so
+    /// Gomoku-sized boards fit in a normal terminal window.
+    Compact,
+    /// Two rows of tiles to a line, the upper as the top half of a block and the lower as the
+    /// bottom half, told apart by color alone.
+    HalfBlock,
+    /// Two rows of tiles to a line in braille, a cross as two dots across, a nought as four and
+    /// a blocked tile as three.
+    Braille,
+}
+impl Layout {
+    /// Boards this many tiles across and more are drawn compact by `Auto`.
+    pub const COMPACT_FROM: usize = 15;
+
+    /// The layout called `name` on the command line, `auto`, `grid`, `compact`, `half-block` or
+    /// `braille`.
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "auto"       => Some(Self::Auto),
+            "grid"       => Some(Self::Grid),
+            "compact"    => Some(Self::Compact),
+            "half-block" => Some(Self::HalfBlock),
+            "braille"    => Some(Self::Braille),
+            _ => None,
+        }
+    }
+
+    /// The layout a board `length` tiles across is drawn in, `Auto` settled.
+    pub fn for_length(&self, length: usize) -> Self {
+        match self {
+            Self::Auto if length >= Self::COMPACT_FROM => Self::Compact,
+            Self::Auto => Self::Grid,
+            layout => *layout,
+        }
+    }
+}
+
+/// `board` in one of the layouts without a grid, with the `highlight` tiles highlighted and the
+/// `marks` on their empty tiles where there is a character per tile to draw them on.
+pub fn condensed(board: &Board, theme: &Theme, layout: Layout, highlight: &[(usize, usize)], marks: &[((usize, usize), char)]) -> String {
+    let length = board.length();
+    let tile = |row: usize, col: usize| board.get(row, col).unwrap_or(Tile::Empty);
+    // Rows are labelled every 5 and at the top, in the numbers moves are entered with.
+    let label = |row: usize| (row == 0 || (row + coords::origin()).is_multiple_of(5)).then(|| (row + coords::origin()).to_string());
+    let margin = (length.saturating_sub(1) + coords::origin()).to_string().len();
+
+    let (columns, lines) = match layout {
+        Layout::Compact => (theme.cell_width().max(2), length),
+        _               => (1, length.div_ceil(2)),
+    };
+    let mut ruler = vec![' '; 2 * margin + 1 + columns * length];
+    for col in 0..length {
+        let start = margin + 1 + columns * col;
+        if let Some(label) = label(col).filter(|_| ruler[start.saturating_sub(1)] == ' ') {
+            for (i, digit) in label.chars().enumerate() {
+                ruler[start + i] = digit;
+            }
+        }
+    }
+    let mut out: String = ruler.into_iter().collect();
+    out = out.trim_end().to_string();
+    out.push('\n');
+
+    for line in 0..lines {
+        let rows = match layout {
+            Layout::Compact => line..line + 1,
+            _               => 2 * line..(2 * line + 2).min(length),
+        };
+        let labelled = rows.clone().find_map(label).unwrap_or_default();
+        out.push_str(&format!("{:>margin$} ", labelled));
+        for col in 0..length {
+            let (top, bottom) = (tile(rows.start, col), tile(rows.end - 1, col));
+            let bottom = if rows.len() == 2 { bottom } else { Tile::Empty };
+            match layout {
+                Layout::Compact => {
+                    let glyph = match marks.iter().find(|(at, _)| *at == (line, col)) {
+                        Some(&(_, mark)) if top == Tile::Empty => mark,
+                        _ if top == Tile::Empty && theme.empty == ' ' => '.',
+                        _ => theme.glyph(top),
+                    };
+                    out.push_str(&theme.palette.paint(&glyph.to_string(), top, highlight.contains(&(line, col)), theme.colors));
+                    out.push_str(&" ".repeat(columns.saturating_sub(width(glyph))));
+                }
+                Layout::Braille => {
+                    let dots = |tile, upper| match (tile, upper) {
+                        (Tile::Cross, true)    => 0x11,
+                        (Tile::Nought, true)   => 0x1b,
+                        (Tile::Cross, false)   => 0x84,
+                        (Tile::Nought, false)  => 0xe4,
+                        (Tile::Blocked, true)  => 0x0b,
+                        (Tile::Blocked, false) => 0x64,
+                        (Tile::Empty, _)       => 0,
+                    };
+                    out.push(char::from_u32(0x2800 + dots(top, true) + dots(bottom, false)).unwrap_or(' '));
+                }
+                _ => out.push_str(&half_block(top, bottom, theme.colors)),
+            }
+        }
+        out.truncate(out.trim_end_matches(' ').len());
+        out.push('\n');
+    }
+    out
+}
+
+/// A hex `board` as rows of hexagons, each row half a tile further right than the one above so
+/// the tiles that touch on the board touch on the screen, under a ruler of the columns. The
+/// `highlight` tiles are highlighted and the `marks` drawn on their empty tiles.
+pub fn hex(board: &Board, theme: &Theme, highlight: &[(usize, usize)], marks: &[((usize, usize), char)]) -> String {
+    let length = board.length();
+    let half = theme.cell_width();
+    let margin = (length.saturating_sub(1) + coords::origin()).to_string().len();
+
+    let mut ruler = vec![' '; 2 * margin + 1 + 2 * half * length];
+    for col in 0..length {
+        let start = margin + 1 + 2 * half * col;
+        if ruler[start.saturating_sub(1)] == ' ' {
+            for (i, digit) in (col + coords::origin()).to_string().chars().enumerate() {
+                ruler[start + i] = digit;
+            }
+        }
+    }
+    let mut out: String = ruler.into_iter().collect();
+    out = out.trim_end().to_string();
+    out.push('\n');
+
+    for row in 0..length {
+        out.push_str(&format!("{:>margin$} {}", row + coords::origin(), " ".repeat(half * row)));
+        for col in 0..length {
+            let tile = board.get(row, col).unwrap_or(Tile::Empty);
+            let glyph = match marks.iter().find(|(at, _)| *at == (row, col)) {
+                Some(&(_, mark)) if tile == Tile::Empty => mark,
+                _ if tile == Tile::Empty && theme.empty == ' ' => '.',
+                _ => theme.glyph(tile),
+            };
+            out.push_str(&theme.palette.paint(&glyph.to_string(), tile, highlight.contains(&(row, col)), theme.colors));
+            out.push_str(&" ".repeat((2 * half).saturating_sub(width(glyph))));
+        }
+        out.truncate(out.trim_end_matches(' ').len());
+        out.push('\n');
+    }
+    out
+}
+
+/// Tiles hidden in fog of war are drawn as this.
+pub const FOG: char = '?';
+
+/// The board of `game` as `viewer` sees it, the tiles it can't see drawn as `FOG`, or the whole
+/// board without a viewer.
+pub fn board_view(game: &Game, viewer: Option<Tile>, theme: &Theme, highlight: &[(usize, usize)]) -> String {
+    let Some(side) = viewer else { return game.board().render_highlighted(theme, highlight) };
+    let fog: Vec<((usize, usize), char)> = game.hidden(side).into_iter().map(|tile| (tile, FOG)).collect();
+    game.view(side).render_marked(theme, highlight, &fog)
+}
+
+/// Blocked tiles in the half-block layout.
+const BLOCKED_GRAY: &str = "38;2;128;128;128";
+
+/// The upper tile in the top half of a character cell and the lower in the bottom half, with
+/// the blue and orange of the colorblind palette whichever palette is used, as the halves need
+/// a color each, and blocked tiles in gray.
+fn half_block(top: Tile, bottom: Tile, colors: ColorDepth) -> String {
+    let color = |tile: Tile| match tile {
+        Tile::Cross   => Palette::COLORBLIND.cross,
+        Tile::Nought  => Palette::COLORBLIND.nought,
+        Tile::Blocked => BLOCKED_GRAY,
+        Tile::Empty   => "",
+    };
+    let (upper, lower) = (color(top), color(bottom));
+    match (upper, lower) {
+        ("", "") => " ".to_string(),
+        (_, "")  => format!("\x1b[{}m▀\x1b[0m", colors.degrade(upper)),
+        ("", _)  => format!("\x1b[{}m▄\x1b[0m", colors.degrade(lower)),
+        _ => format!("\x1b[{};{}m▀\x1b[0m", colors.degrade(upper), colors.degrade(&lower.replacen("38", "48", 1))),
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::ASCII
+    }
+}
+
+/// The colors of the marks and of highlighted tiles, as ANSI SGR parameters on the terminal, in
+/// 24-bit color where the palette needs more than the 16 basic colors, and CSS colors in SVG and
+/// HTML. None of them tells the sides apart by red and green, and the marks
+/// still differ by glyph where there are no colors at all.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Palette {
+    pub name: &'static str,
+    /// Empty for the terminal's own color.
+    pub cross: &'static str,
+    pub nought: &'static str,
+    /// Replaces the mark's color on highlighted tiles.
+    pub highlight: &'static str,
+    pub css_cross: &'static str,
+    pub css_nought: &'static str,
+    pub css_highlight: &'static str,
+}
+impl Palette {
+    /// No colors, highlights in reverse video.
+    pub const PLAIN: Palette = Palette {
+        name: "plain",
+        cross: "",
+        nought: "",
+        highlight: "7",
+        css_cross: "black",
+        css_nought: "black",
+        css_highlight: "#ffe680",
+    };
+    /// Blue and orange from the Okabe-Ito palette, which stay apart with every common form of
+    /// color blindness and differ in brightness too.
+    pub const COLORBLIND: Palette = Palette {
+        name: "colorblind",
+        cross: "38;2;0;114;178",
+        nought: "38;2;230;159;0",
+        highlight: "7",
+        css_cross: "#0072b2",
+        css_nought: "#e69f00",
+        css_highlight: "#e0e0e0",
+    };
+    /// Bold bright marks, and black on white for highlights.
+    pub const HIGH_CONTRAST: Palette = Palette {
+        name: "high-contrast",
+        cross: "1;97",
+        nought: "1;93",
+        highlight: "1;30;107",
+        css_cross: "black",
+        css_nought: "black",
+        css_highlight: "#ffff00",
+    };
+    pub const ALL: [Palette; 3] = [Self::PLAIN, Self::COLORBLIND, Self::HIGH_CONTRAST];
+
+    pub fn named(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|palette| palette.name == name)
+    }
+
+    /// The same colors for SVG and HTML, but none on the terminal, for terminals without escape
+    /// codes.
+    pub fn without_escapes(self) -> Self {
+        Self { cross: "", nought: "", highlight: "", ..self }
+    }
+
+    /// `text` in the color of `tile`, or in the highlight colors, as near as `colors` gets.
+    pub fn paint(&self, text: &str, tile: Tile, highlighted: bool, colors: ColorDepth) -> String {
+        let color = match (highlighted, tile) {
+            (true, _)            => self.highlight,
+            (false, Tile::Cross)  => self.cross,
+            (false, Tile::Nought) => self.nought,
+            (false, Tile::Empty | Tile::Blocked) => "",
+        };
+        match color {
+            "" => text.to_string(),
+            _  => format!("\x1b[{}m{}\x1b[0m", colors.degrade(color), text),
+        }
+    }
+
+    pub fn css_color(&self, tile: Tile) -> &'static str {
+        match tile {
+            Tile::Nought  => self.css_nought,
+            Tile::Blocked => "gray",
+            _             => self.css_cross,
+        }
+    }
+}
+
+/// How many colors a terminal shows.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ColorDepth {
+    /// Any 24-bit color.
+    TrueColor,
+    /// The 6x6x6 color cube and the gray ramp of xterm, besides the basic colors.
+    Ansi256,
+    /// The 8 basic colors and their bright versions.
+    Ansi16,
+}
+impl ColorDepth {
+    /// The depth called `name` on the command line, `truecolor`, `256` or `16`.
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "truecolor" => Some(Self::TrueColor),
+            "256"       => Some(Self::Ansi256),
+            "16"        => Some(Self::Ansi16),
+            _ => None,
+        }
+    }
+
+    /// The SGR parameters `sgr` with every color the terminal doesn't have replaced by the
+    /// nearest one it has, e.g. `1;38;2;0;114;178` becomes `1;38;5;25` with 256 colors.
+    pub fn degrade(&self, sgr: &str) -> String {
+        let params: Vec<&str> = sgr.split(';').collect();
+        let number = |i: usize| params.get(i).and_then(|p| p.parse::<u8>().ok());
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < params.len() {
+            let background = params[i] == "48";
+            let color = match (params[i], params.get(i + 1).copied()) {
+                ("38" | "48", Some("2")) => number(i + 2).zip(number(i + 3)).zip(number(i + 4))
+                    .map(|((r, g), b)| ((r, g, b), 5)),
+                ("38" | "48", Some("5")) => number(i + 2).map(|n| (ansi256_rgb(n), 3)),
+                _ => None,
+            };
+            match color {
+                Some((rgb, length)) => {
+                    out.push(self.color(rgb, background, &params[i..i + length]));
+                    i += length;
+                }
+                None => {
+                    out.push(params[i].to_string());
+                    i += 1;
+                }
+            }
+        }
+        out.join(";")
+    }
+
+    /// The parameters for `rgb`, which `given` already names when the terminal has it.
+    fn color(&self, (r, g, b): (u8, u8, u8), background: bool, given: &[&str]) -> String {
+        let layer = if background { 48 } else { 38 };
+        match (self, given.len()) {
+            (Self::TrueColor, _) | (Self::Ansi256, 3) => given.join(";"),
+            (Self::Ansi256, _) => format!("{};5;{}", layer, nearest_256((r, g, b))),
+            (Self::Ansi16, _) => {
+                let n = nearest(&ANSI16, (r, g, b)) as u8;
+                let base = if background { 40 } else { 30 };
+                (base + n % 8 + if n >= 8 { 60 } else { 0 }).to_string()
+            }
+        }
+    }
+}
+
+/// The basic colors as xterm shows them, in the order of their SGR codes.
+const ANSI16: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+/// The levels of each channel in the color cube of the 256 colors.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn ansi256_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => ANSI16[n as usize],
+        16..=231 => {
+            let n = n - 16;
+            (CUBE[n as usize / 36], CUBE[n as usize / 6 % 6], CUBE[n as usize % 6])
+        }
+        _ => {
+            let level = 8 + 10 * (n - 232);
+            (level, level, level)
+        }
+    }
+}
+
+/// The color of the cube or the gray ramp nearest to `rgb`.
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let cube = [rgb.0, rgb.1, rgb.2].map(|c| (0..CUBE.len()).min_by_key(|&i| CUBE[i].abs_diff(c)).unwrap_or(0) as u8);
+    let cube = 16 + 36 * cube[0] + 6 * cube[1] + cube[2];
+    let grays: Vec<(u8, u8, u8)> = (232..=255).map(ansi256_rgb).collect();
+    let gray = 232 + nearest(&grays, rgb) as u8;
+    match distance(ansi256_rgb(gray), rgb) < distance(ansi256_rgb(cube), rgb) {
+        true  => gray,
+        false => cube,
+    }
+}
+
+/// The index of the color in `colors` nearest to `rgb`.
+fn nearest(colors: &[(u8, u8, u8)], rgb: (u8, u8, u8)) -> usize {
+    (0..colors.len()).min_by_key(|&i| distance(colors[i], rgb)).unwrap_or(0)
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).unsigned_abs().pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// What the players of each side are called in prompts and results, Cross first. Sides without
+/// a name go by their tile.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Names(pub [Option<String>; 2]);
+impl Names {
+    pub fn get(&self, side: Tile) -> Option<&str> {
+        let index = match side {
+            Tile::Cross  => 0,
+            Tile::Nought => 1,
+            Tile::Empty | Tile::Blocked => return None,
+        };
+        self.0[index].as_deref()
+    }
+
+    /// E.g. `Alice (X)`, or `Cross` without a name.
+    pub fn of(&self, side: Tile) -> String {
+        match self.get(side) {
+            Some(name) => format!("{} ({})", name, side.char()),
+            None       => format!("{:?}", side),
+        }
+    }
+
+    fn any(&self) -> bool {
+        self.0.iter().any(Option::is_some)
+    }
+}
+
+/// The line above the board, e.g. `Move 4: Nought to move, 6 empty squares left`, or once the
+/// game is over `Game over after 7 moves, 2 empty squares left`.
+pub fn turn_indicator(game: &Game, to_move: Tile, names: &Names) -> String {
+    let empty = match game.board().empty_tiles().len() {
+        0 => "no empty squares left".to_string(),
+        1 => "1 empty square left".to_string(),
+        empty => format!("{} empty squares left", empty),
+    };
+    let moves = game.history().len();
+    // Under decay the mark that goes with the next move is worth knowing about.
+    let decays = match game.board().next_to_decay(to_move) {
+        Some((row, col)) => format!(", the mark on {} decays next", coords::format(row, col)),
+        None => String::new(),
+    };
+    let points = match game.board().scored() {
+        Some(scored) => format!(", points X {} O {}", scored.of(Tile::Cross), scored.of(Tile::Nought)),
+        None => String::new(),
+    };
+    match game.status() {
+        BoardStatus::Continue => format!("Move {}: {} to move, {}{}{}", moves + 1, names.of(to_move), empty, decays, points),
+        _ => format!("Game over after {} move{}, {}{}", moves, if moves == 1 { "" } else { "s" }, empty, points),
+    }
+}
+
+/// E.g. `Cross, make move (x, y) or ? for help: `, or `Alice (X) to move, …` with a name.
+pub fn prompt(side: Tile, names: &Names) -> String {
+    match names.get(side) {
+        Some(_) => format!("{} to move, make move (x, y) or ? for help: ", names.of(side)),
+        None    => format!("{:?}, make move (x, y) or ? for help: ", side),
+    }
+}
+
+pub fn move_made(side: Tile, names: &Names) -> String {
+    format!("{} move:", names.of(side))
+}
+
+/// A move as spoken, e.g. "Nought plays row two, column three", counting from one whatever
+/// numbers moves are entered with.
+pub fn spoken_move(side: Tile, row: usize, col: usize) -> String {
+    const NUMBERS: [&str; 26] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven", "twelve", "thirteen",
+        "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen", "twenty", "twenty-one", "twenty-two",
+        "twenty-three", "twenty-four", "twenty-five", "twenty-six",
+    ];
+    let number = |n: usize| NUMBERS.get(n).map_or_else(|| (n + 1).to_string(), |word| word.to_string());
+    format!("{:?} plays row {}, column {}", side, number(row), number(col))
+}
+
+pub fn resignation(side: Tile, names: &Names) -> String {
+    format!("{} resigns, {} has won!", names.of(side), names.of(side.opposite().unwrap_or(Tile::Empty)))
+}
+
+pub fn result(status: BoardStatus) -> String {
+    named_result(status, &Names::default())
+}
+
+/// The result with the players' names, e.g. `Alice (X) beats Random AI (O)!`, the same as
+/// `result` when neither side has one.
+pub fn named_result(status: BoardStatus, names: &Names) -> String {
+    match status {
+        BoardStatus::Winner(tile) if names.any() => {
+            format!("{} beats {}!", names.of(tile), names.of(tile.opposite().unwrap_or(Tile::Empty)))
+        }
+        BoardStatus::Winner(tile) => format!("{:?} has won!", tile),
+        BoardStatus::Tie          => "Tie!".to_string(),
+        BoardStatus::Continue     => "The game goes on.".to_string(),
+    }
+}
+
+/// The result of `game` as one line for scripts, e.g. `result=X moves=7 reason=line`.
+/// `status` is `None` when the game was abandoned.
+pub fn result_line(status: Option<BoardStatus>, game: &Game, resigned: bool) -> String {
+    let full = game.board().empty_tiles().is_empty();
+    let (result, reason) = match status {
+        Some(BoardStatus::Winner(tile)) => (tile.char(), match (resigned, game.board().scoring()) {
+            (true, _)      => "resign",
+            (false, true)  => "points",
+            (false, false) => "line",
+        }),
+        Some(BoardStatus::Tie)          => ("draw", if full { "full" } else { "blocked" }),
+        Some(BoardStatus::Continue) | None => ("none", "quit"),
+    };
+    format!("result={} moves={} reason={}", result, game.history().len(), reason)
+}
+
+/// The end of game summary: the final board with the winning line highlighted, every move with
+/// the time it took and the name of the opening, and how long the game lasted.
+pub fn summary(game: &Game, theme: &Theme, thinking: &[Option<Duration>], duration: Duration) -> String {
+    let line = game.board().winning_line().unwrap_or_default();
+    let mut out = format!("Final position:\n{}Moves:\n", game.board().render_highlighted(theme, &line));
+
+    let opening = openings::classify(game.start(), game.history());
+    for (i, m) in game.history().iter().enumerate() {
+        out.push_str(&format!("{:>4}. {:?} {}", i + 1, m.tile, m.describe()));
+        if let Some(Some(time)) = thinking.get(i) {
+            out.push_str(&format!(" ({:.1}s)", time.as_secs_f64()));
+        }
+        if let Some(opening) = opening.filter(|opening| opening.moves == i + 1) {
+            out.push_str(&format!("  {}", opening.name));
+        }
+        out.push('\n');
+    }
+
+    for side in [Tile::Cross, Tile::Nought] {
+        let times: Vec<Duration> = game.history().iter().zip(thinking)
+            .filter(|(m, _)| m.tile == side)
+            .filter_map(|(_, time)| *time)
+            .collect();
+        let Some(longest) = times.iter().max() else { continue };
+        let total: Duration = times.iter().sum();
+        out.push_str(&format!("{:?} thought for {:.1}s, {:.1}s a move on average and {:.1}s at most.\n",
+            side, total.as_secs_f64(), total.as_secs_f64() / times.len() as f64, longest.as_secs_f64()));
+    }
+    out.push_str(&format!("The game took {:.1}s.", duration.as_secs_f64()));
+    out
+}
+
+/// An annotation as shown in replays, e.g. `Mistake: the corner was safer.`, `None` for moves
+/// without one.
+pub fn annotation(annotation: &Annotation) -> Option<String> {
+    let verdict = annotation.glyph.map(|glyph| match glyph {
+        "!!" => "Brilliant move",
+        "!"  => "Good move",
+        "!?" => "Interesting move",
+        "?!" => "Dubious move",
+        "?"  => "Mistake",
+        _    => "Blunder",
+    });
+
+    match (verdict, &annotation.comment) {
+        (Some(verdict), Some(comment)) => Some(format!("{}: {}", verdict, comment)),
+        (Some(verdict), None)          => Some(format!("{}.", verdict)),
+        (None, comment)                => comment.clone(),
+    }
+}
+
+/// Where `side` went wrong, move `index` of the game, and the move that would have been better.
+pub fn mistake(side: Tile, index: usize, game: &Game, (row, col): (usize, usize)) -> String {
+    let m = game.history()[index];
+    format!("{:?} went wrong on move {} ({}), ({}) was better.",
+        side, index + 1, coords::format(m.row, m.col), coords::format(row, col))
+}
+
+/// A look back at the moves where `side` let a better result slip, each with the board before it
+/// and the better move highlighted, or a line saying there were none.
+pub fn review(side: Tile, game: &Game, mistakes: &[Mistake], theme: &Theme) -> String {
+    if mistakes.is_empty() {
+        return format!("{:?} played every move as well as it could be played.", side);
+    }
+
+    let mut out = format!("Looking back at {:?}'s moves:\n", side);
+    for mistake in mistakes {
+        let m = game.history()[mistake.index];
+        let better = coords::format(mistake.better.0, mistake.better.1);
+        let lesson = match (mistake.before, mistake.after) {
+            (1, 0) => format!("here you had a forced win, but ({}) only draws; the winning move was ({})", coords::format(m.row, m.col), better),
+            (1, _) => format!("here you had a forced win, but ({}) loses; the winning move was ({})", coords::format(m.row, m.col), better),
+            _      => format!("here the game was still a draw, but ({}) loses; ({}) would have held it", coords::format(m.row, m.col), better),
+        };
+        out.push_str(&format!("\nMove {}, {}.\n{}", mistake.index + 1, lesson, mistake.position.render_highlighted(theme, &[mistake.better])));
+    }
+    out
+}
+
+/// The board marked for beginners before `side` moves: `w` wins at once, `b` has to be blocked and
+/// `f` makes a fork, with a line explaining each kind of mark. `None` when nothing is marked.
+pub fn teaching(board: &Board, theme: &Theme, side: Tile) -> Option<String> {
+    let tactics = board.tactics(side);
+    if tactics.is_empty() {
+        return None;
+    }
+
+    let kinds = [
+        (&tactics.wins,   'w', "wins the game".to_string()),
+        (&tactics.blocks, 'b', format!("must be blocked, {:?} wins there next", side.opposite().unwrap_or(Tile::Empty))),
+        (&tactics.forks,  'f', "makes a fork, two threats that can't both be blocked".to_string()),
+    ];
+    let mut marks: Vec<((usize, usize), char)> = Vec::new();
+    let mut legend = String::new();
+    // A tile that is both gets the more urgent mark, winning before blocking before forking.
+    for (tiles, mark, meaning) in kinds {
+        let tiles: Vec<(usize, usize)> = tiles.iter().copied().filter(|&tile| !marks.iter().any(|(marked, _)| *marked == tile)).collect();
+        if tiles.is_empty() {
+            continue;
+        }
+        marks.extend(tiles.iter().map(|&tile| (tile, mark)));
+        let tiles: Vec<String> = tiles.iter().map(|&(row, col)| format!("({})", coords::format(row, col))).collect();
+        legend.push_str(&format!("{}: {} {}\n", mark, meaning, tiles.join(", ")));
+    }
+    Some(board.render_marked(theme, &[], &marks) + &legend)
+}
+
+/// `game` as Markdown for issues and forums: the final position as a table with the winning line
+/// in bold, then the moves and the result.
+pub fn markdown(game: &Game, theme: &Theme) -> String {
+    let board = game.board();
+    let line = board.winning_line().unwrap_or_default();
+    let columns: Vec<String> = (0..board.length()).map(column).collect();
+
+    let mut out = format!("|   | {} |\n|---|{}\n", columns.join(" | "), ":-:|".repeat(board.length()));
+    for row in 0..board.length() {
+        out.push_str(&format!("| {} |", row + 1));
+        for col in 0..board.length() {
+            let glyph = match theme.glyph(board.get(row, col).unwrap_or(Tile::Empty)) {
+                '|' => "\\|".to_string(),
+                glyph => glyph.to_string(),
+            };
+            if line.contains(&(row, col)) {
+                out.push_str(&format!(" **{}** |", glyph));
+            } else {
+                out.push_str(&format!(" {} |", glyph));
+            }
+        }
+        out.push('\n');
+    }
+
+    let moves = Record::of(game).movetext();
+    if !moves.is_empty() {
+        out.push_str(&format!("\n**Moves:** {}\n", moves));
+    }
+    out.push_str(&format!("\n**Result:** {}\n", result(board.board_status())));
+    out
+}
+
+/// `game` as an HTML snippet with its own inline styles, to paste into pages: the final position
+/// as a table with the winning line shaded, then the moves and the result.
+pub fn html(game: &Game, theme: &Theme) -> String {
+    const CELL: &str = "border: 1px solid #222; width: 2em; height: 2em; text-align: center";
+
+    let board = game.board();
+    let line = board.winning_line().unwrap_or_default();
+    let mut out = String::from("<div class=\"tic-tac-toe\">\n<table style=\"border-collapse: collapse\">\n<tr><th></th>");
+    for col in 0..board.length() {
+        out.push_str(&format!("<th>{}</th>", column(col)));
+    }
+    out.push_str("</tr>\n");
+
+    for row in 0..board.length() {
+        out.push_str(&format!("<tr><th>{}</th>", row + 1));
+        for col in 0..board.length() {
+            let glyph = escape_html(&theme.glyph(board.get(row, col).unwrap_or(Tile::Empty)).to_string());
+            let shade = match line.contains(&(row, col)) {
+                true  => format!("; background: {}", theme.palette.css_highlight),
+                false => String::new(),
+            };
+            out.push_str(&format!("<td style=\"{}{}\">{}</td>", CELL, shade, glyph));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+
+    if !game.history().is_empty() {
+        out.push_str("<ol class=\"moves\">\n");
+        for m in game.history() {
+            out.push_str(&format!("<li>{}</li>\n", m.notation()));
+        }
+        out.push_str("</ol>\n");
+    }
+    out.push_str(&format!("<p class=\"result\">{}</p>\n</div>\n", result(board.board_status())));
+    out
+}
+
+fn column(col: usize) -> String {
+    coords::notation(0, col).trim_end_matches('1').to_string()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{html, markdown, named_result, summary, turn_indicator, prompt, resignation, review, spoken_move, teaching, ColorDepth, Names, Theme};
+    use crate::board::{Board, BoardStatus, Tile::*};
+    use crate::game::Game;
+    use std::time::Duration;
+
+    #[test]
+    fn spoken_moves() {
+        assert_eq!(spoken_move(Nought, 1, 2), "Nought plays row two, column three");
+        assert_eq!(spoken_move(Cross, 25, 30), "Cross plays row twenty-six, column 31");
+    }
+
+    #[test]
+    fn color_depths() {
+        let blue = "1;38;2;0;114;178";
+        assert_eq!(ColorDepth::TrueColor.degrade(blue), blue);
+        assert_eq!(ColorDepth::Ansi256.degrade(blue), "1;38;5;25");
+        assert_eq!(ColorDepth::Ansi16.degrade(blue), "1;36");
+        assert_eq!(ColorDepth::Ansi256.degrade("48;2;128;128;128"), "48;5;244");
+        assert_eq!(ColorDepth::Ansi16.degrade("38;5;178;48;2;255;255;255"), "33;107");
+        // Basic colors and malformed ones are left alone.
+        assert_eq!(ColorDepth::Ansi16.degrade("7;93;38;2;1"), "7;93;38;2;1");
+        assert_eq!(ColorDepth::named("256"), Some(ColorDepth::Ansi256));
+    }
+
+    #[test]
+    fn names() {
+        let names = Names([Some("Alice".to_string()), None]);
+        assert_eq!(prompt(Cross, &names), "Alice (X) to move, make move (x, y) or ? for help: ");
+        assert_eq!(prompt(Nought, &names), "Nought, make move (x, y) or ? for help: ");
+        assert_eq!(named_result(BoardStatus::Winner(Cross), &names), "Alice (X) beats Nought!");
+        assert_eq!(resignation(Nought, &names), "Nought resigns, Alice (X) has won!");
+        assert_eq!(named_result(BoardStatus::Winner(Nought), &Names::default()), "Nought has won!");
+
+        let mut game = Game::new(Board::new(3, 3));
+        assert_eq!(turn_indicator(&game, Nought, &names), "Move 1: Nought to move, 9 empty squares left");
+        for (tile, row, col) in [(Cross, 0, 0), (Nought, 1, 0), (Cross, 0, 1), (Nought, 1, 1), (Cross, 0, 2)] {
+            game.apply(tile, row, col).unwrap();
+        }
+        assert_eq!(turn_indicator(&game, Nought, &names), "Game over after 5 moves, 4 empty squares left");
+        let thinking = [1500, 200, 500, 100, 1000].map(|millis| Some(Duration::from_millis(millis)));
+        let summary = summary(&game, &Theme::default(), &thinking, Duration::from_secs(4));
+        assert!(summary.contains("   4. Nought 1, 1 (0.1s)\n"), "{}", summary);
+        assert!(summary.ends_with("Cross thought for 3.0s, 1.0s a move on average and 1.5s at most.\n\
+                                   Nought thought for 0.3s, 0.1s a move on average and 0.2s at most.\n\
+                                   The game took 4.0s."), "{}", summary);
+        game.undo_turn(Cross).unwrap();
+        assert_eq!(turn_indicator(&game, Cross, &names), "Move 5: Alice (X) to move, 5 empty squares left");
+    }
+
+    #[test]
+    fn review_mistakes() {
+        let mut game = Game::new(Board::new(3, 3));
+        for (tile, row, col) in [(Cross, 1, 1), (Nought, 0, 1), (Cross, 0, 0), (Nought, 1, 0)] {
+            game.apply(tile, row, col).unwrap();
+        }
+        let review = review(Nought, &game, &game.mistakes(Nought), &Theme::default());
+        assert!(review.starts_with("Looking back at Nought's moves:\n\nMove 2, here the game was still a draw, but (1, 0) loses;"), "{}", review);
+        assert!(!review.contains("Move 4"), "{}", review);
+        assert_eq!(super::review(Cross, &game, &[], &Theme::default()), "Cross played every move as well as it could be played.");
+    }
+
+    #[test]
+    fn teaching_marks() {
+        let board = Board::from_notation("XX./O../...", 3).unwrap();
+        assert_eq!(teaching(&board, &Theme::default(), Nought).unwrap(), "\
+=======
+|X|X|b|
+=======
+|O| | |
+=======
+| | | |
+b: must be blocked, Cross wins there next (2, 0)
+");
+        assert!(teaching(&Board::new(3, 3), &Theme::default(), Cross).is_none());
+    }
+
+    #[test]
+    fn markdown_and_html() {
+        let mut game = Game::new(Board::new(3, 3));
+        for (tile, row, col) in [(Cross, 0, 0), (Nought, 1, 0), (Cross, 0, 1), (Nought, 1, 1), (Cross, 0, 2)] {
+            game.apply(tile, row, col).unwrap();
+        }
+
+        assert_eq!(markdown(&game, &Theme::default()), "\
+|   | a | b | c |
+|---|:-:|:-:|:-:|
+| 1 | **X** | **X** | **X** |
+| 2 | O | O |   |
+| 3 |   |   |   |
+
+**Moves:** 1. Xa1 Oa2 2. Xb1 Ob2 3. Xc1
+
+**Result:** Cross has won!
+");
+
+        let html = html(&game, &Theme { empty: '<', ..Theme::default() });
+        assert!(html.contains("<tr><th>3</th><td style=\"border: 1px solid #222; width: 2em; height: 2em; text-align: center\">&lt;</td>"));
+        assert!(html.contains("background: #ffe680\">X</td>"));
+        assert!(html.contains("<li>Ob2</li>\n<li>Xc1</li>\n</ol>\n<p class=\"result\">Cross has won!</p>"));
+    }
+}
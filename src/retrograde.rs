@@ -0,0 +1,173 @@
+//! Retrograde analysis: every position reachable on a small board labelled with its exact value,
+//! working back from the finished games instead of searching forward. A check on the forward
+//! search, and a tablebase to look positions up in.
+
+use crate::board::{Board, BoardStatus, Tile};
+use crate::solve::{Outcome, Value};
+use std::collections::{HashMap, VecDeque};
+
+struct Node {
+    board: Board,
+    to_move: Tile,
+    parents: Vec<usize>,
+    /// Children not yet known to be wins for the side to move there.
+    unresolved: usize,
+    value: Option<Value>,
+}
+
+/// The value of every position reachable from one start.
+pub struct Tablebase {
+    positions: Vec<(Board, Tile, Value)>,
+    /// Indices into `positions` by the notation and the side to move.
+    index: HashMap<String, usize>,
+}
+impl Tablebase {
+    /// Labels every position reachable from `start` with `side` to move. The number of positions
+    /// grows very fast with the board, 3x3 has under 6000.
+    pub fn build(start: &Board, side: Tile) -> Self {
+        let (mut nodes, index) = reachable(start, side);
+
+        // Finished games first, then back a move at a time, so each win is found by way of its
+        // quickest finish and each loss by way of its slowest.
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        for (i, node) in nodes.iter_mut().enumerate() {
+            node.value = match node.board.board_status() {
+                BoardStatus::Winner(_) => Some(Value { outcome: Outcome::Loss, distance: 0 }),
+                BoardStatus::Tie       => Some(Value { outcome: Outcome::Draw, distance: 0 }),
+                BoardStatus::Continue  => None,
+            };
+            if node.value.is_some() {
+                queue.push_back(i);
+            }
+        }
+
+        while let Some(child) = queue.pop_front() {
+            let Some(value) = nodes[child].value else { continue };
+            for parent in nodes[child].parents.clone() {
+                let node = &mut nodes[parent];
+                if node.value.is_some() {
+                    continue;
+                }
+                match value.outcome {
+                    Outcome::Loss => node.value = Some(Value { outcome: Outcome::Win, distance: value.distance + 1 }),
+                    Outcome::Win => {
+                        node.unresolved -= 1;
+                        if node.unresolved == 0 {
+                            node.value = Some(Value { outcome: Outcome::Loss, distance: value.distance + 1 });
+                        }
+                    }
+                    Outcome::Draw => continue,
+                }
+                if node.value.is_some() {
+                    queue.push_back(parent);
+                }
+            }
+        }
+
+        // What is neither won nor lost by now can't be forced either way.
+        let draw = Value { outcome: Outcome::Draw, distance: 0 };
+        let positions = nodes.into_iter().map(|node| (node.board, node.to_move, node.value.unwrap_or(draw))).collect();
+        Self { positions, index }
+    }
+
+    /// The value of `board` for `side` to move, when it is reachable from the start.
+    pub fn get(&self, board: &Board, side: Tile) -> Option<Value> {
+        self.index.get(&key(board, side)).map(|&i| self.positions[i].2)
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Every position, with the side to move and its value for that side.
+    pub fn iter(&self) -> impl Iterator<Item = (&Board, Tile, Value)> {
+        self.positions.iter().map(|(board, side, value)| (board, *side, *value))
+    }
+
+    /// Every position where `value`, another way of finding the value, disagrees with the table.
+    /// Distances are only compared when `distances` is set, for searches that only find who wins.
+    pub fn check(&self, distances: bool, mut value: impl FnMut(&Board, Tile) -> Value) -> Vec<Disagreement> {
+        self.iter()
+            .filter_map(|(board, side, expected)| {
+                let found = value(board, side);
+                let agrees = match distances {
+                    true  => found == expected,
+                    false => found.outcome == expected.outcome,
+                };
+                (!agrees).then(|| Disagreement { board: board.clone(), side, expected, found })
+            })
+            .collect()
+    }
+}
+
+/// A position that another search values differently from the tablebase.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Disagreement {
+    pub board: Board,
+    pub side: Tile,
+    pub expected: Value,
+    pub found: Value,
+}
+
+fn key(board: &Board, side: Tile) -> String {
+    format!("{} {}", board.notation(), side.char())
+}
+
+/// Every position reachable from `start`, with links to the positions that lead to each.
+fn reachable(start: &Board, side: Tile) -> (Vec<Node>, HashMap<String, usize>) {
+    let mut nodes = vec![Node { board: start.clone(), to_move: side, parents: Vec::new(), unresolved: 0, value: None }];
+    let mut index = HashMap::from([(key(start, side), 0)]);
+
+    let mut next = 0;
+    while next < nodes.len() {
+        let (board, to_move) = (nodes[next].board.clone(), nodes[next].to_move);
+        let (Some(other), BoardStatus::Continue) = (to_move.opposite(), board.board_status()) else {
+            next += 1;
+            continue;
+        };
+
+        for (row, col) in board.empty_tiles() {
+            let mut child = board.clone();
+            let _ = child.set(to_move, row, col);
+            let i = *index.entry(key(&child, other)).or_insert_with(|| {
+                nodes.push(Node { board: child, to_move: other, parents: Vec::new(), unresolved: 0, value: None });
+                nodes.len() - 1
+            });
+            nodes[i].parents.push(next);
+            nodes[next].unresolved += 1;
+        }
+        next += 1;
+    }
+    (nodes, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tablebase;
+    use crate::board::{Board, Tile::*};
+    use crate::solve::{Outcome, Solver, Value};
+
+    #[test]
+    fn agrees_with_the_search() {
+        let tablebase = Tablebase::build(&Board::new(3, 3), Cross);
+        assert_eq!(tablebase.len(), 5478);
+        assert_eq!(tablebase.get(&Board::new(3, 3), Cross), Some(Value { outcome: Outcome::Draw, distance: 0 }));
+        assert_eq!(tablebase.get(&Board::from_notation("X../O../...", 3).unwrap(), Cross), Some(Value { outcome: Outcome::Win, distance: 5 }));
+        assert_eq!(tablebase.get(&Board::new(3, 3), Nought), None);
+        // The same positions without the ones that only differ by turning the board.
+        let mut distinct: Vec<String> = tablebase.iter().map(|(board, _, _)| board.canonical_form().notation()).collect();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 765);
+
+        let mut solver = Solver::new();
+        assert_eq!(tablebase.check(true, |board, side| solver.value(board, side)), []);
+        let draw = Value { outcome: Outcome::Draw, distance: 0 };
+        let wrong = tablebase.check(false, |_, _| draw);
+        assert!(!wrong.is_empty() && wrong.iter().all(|d| d.expected.outcome != Outcome::Draw));
+    }
+}
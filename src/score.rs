@@ -0,0 +1,146 @@
+use crate::board::{BoardStatus, Tile};
+use std::fmt;
+
+/// Running results over several games.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct Score {
+    pub cross_wins: u32,
+    pub nought_wins: u32,
+    pub draws: u32,
+}
+impl Score {
+    pub fn record(&mut self, status: BoardStatus) {
+        match status {
+            BoardStatus::Winner(Tile::Cross)  => self.cross_wins += 1,
+            BoardStatus::Winner(Tile::Nought) => self.nought_wins += 1,
+            BoardStatus::Tie                  => self.draws += 1,
+            BoardStatus::Winner(Tile::Empty | Tile::Blocked) | BoardStatus::Continue => (),
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.cross_wins + self.nought_wins + self.draws
+    }
+
+    /// The side with more wins, `None` when level.
+    pub fn leader(&self) -> Option<Tile> {
+        match self.cross_wins.cmp(&self.nought_wins) {
+            std::cmp::Ordering::Greater => Some(Tile::Cross),
+            std::cmp::Ordering::Less    => Some(Tile::Nought),
+            std::cmp::Ordering::Equal   => None,
+        }
+    }
+
+    pub fn wins(&self, side: Tile) -> u32 {
+        match side {
+            Tile::Cross  => self.cross_wins,
+            Tile::Nought => self.nought_wins,
+            Tile::Empty | Tile::Blocked => self.draws,
+        }
+    }
+
+    /// The 95% Wilson score interval of the rate at which `side` wins, as `(low, high)`.
+    pub fn win_rate_interval(&self, side: Tile) -> (f64, f64) {
+        const Z: f64 = 1.96;
+
+        let n = self.games() as f64;
+        if n == 0.0 {
+            return (0.0, 1.0);
+        }
+        let p = self.wins(side) as f64 / n;
+
+        let center = (p + Z * Z / (2.0 * n)) / (1.0 + Z * Z / n);
+        let spread = Z / (1.0 + Z * Z / n) * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt();
+        ((center - spread).max(0.0), (center + spread).min(1.0))
+    }
+
+    /// The two sided p-value of a sign test over the decisive games, the chance of a difference in
+    /// wins at least this large between two equally strong players.
+    pub fn p_value(&self) -> f64 {
+        let decisive = (self.cross_wins + self.nought_wins) as f64;
+        if decisive == 0.0 {
+            return 1.0;
+        }
+
+        // Normal approximation of the binomial, with a continuity correction.
+        let difference = (self.cross_wins as f64 - self.nought_wins as f64).abs();
+        let z = ((difference - 1.0).max(0.0)) / decisive.sqrt();
+        erfc(z / std::f64::consts::SQRT_2)
+    }
+}
+
+/// Results split by the side that moved first, an advantage that the totals hide.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct SplitScore {
+    pub cross_first: Score,
+    pub nought_first: Score,
+}
+impl SplitScore {
+    pub fn record(&mut self, first: Tile, status: BoardStatus) {
+        match first {
+            Tile::Nought => self.nought_first.record(status),
+            _            => self.cross_first.record(status),
+        }
+    }
+
+    /// The games moved first by `side`.
+    pub fn first(&self, side: Tile) -> &Score {
+        match side {
+            Tile::Nought => &self.nought_first,
+            _            => &self.cross_first,
+        }
+    }
+
+    pub fn total(&self) -> Score {
+        let (a, b) = (self.cross_first, self.nought_first);
+        Score { cross_wins: a.cross_wins + b.cross_wins, nought_wins: a.nought_wins + b.nought_wins, draws: a.draws + b.draws }
+    }
+}
+
+impl fmt::Display for SplitScore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cross first: {}, Nought first: {}", self.cross_first, self.nought_first)
+    }
+}
+
+/// The complementary error function, to within 1.5e-7 (Abramowitz and Stegun 7.1.26).
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erfc = poly * (-x * x).exp();
+
+    if x >= 0.0 { erfc } else { 2.0 - erfc }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cross {} - Nought {} ({} draw{})", self.cross_wins, self.nought_wins, self.draws, if self.draws == 1 { "" } else { "s" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Score, SplitScore};
+    use crate::board::{BoardStatus::*, Tile::*};
+
+    #[test]
+    fn statistics() {
+        let score = Score { cross_wins: 52, nought_wins: 48, draws: 0 };
+        let (low, high) = score.win_rate_interval(Cross);
+        assert!((low - 0.423).abs() < 0.001 && (high - 0.615).abs() < 0.001, "{} {}", low, high);
+        assert!(score.p_value() > 0.7);
+
+        let score = Score { cross_wins: 70, nought_wins: 30, draws: 20 };
+        assert!(score.p_value() < 0.001);
+        assert_eq!(Score::default().p_value(), 1.0);
+        assert_eq!(Score::default().win_rate_interval(Nought), (0.0, 1.0));
+
+        let mut split = SplitScore::default();
+        split.record(Cross, Winner(Cross));
+        split.record(Nought, Winner(Nought));
+        split.record(Nought, Tie);
+        assert_eq!(split.total(), Score { cross_wins: 1, nought_wins: 1, draws: 1 });
+        assert_eq!(split.first(Nought), &Score { cross_wins: 0, nought_wins: 1, draws: 1 });
+        assert_eq!(split.to_string(), "Cross first: Cross 1 - Nought 0 (0 draws), Nought first: Cross 0 - Nought 1 (1 draw)");
+    }
+}
@@ -0,0 +1,121 @@
+//! Drawing the board on the terminal, either below the previous output or redrawn in place at
+//! the top of the screen, where the last placed mark blinks briefly.
+
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::{io, thread, time::Duration};
+use tick_tack_toe::game::Game;
+use crate::player;
+use tick_tack_toe::board::Tile;
+use tick_tack_toe::render::{self, Theme};
+
+static IN_PLACE: AtomicBool = AtomicBool::new(false);
+static ANIMATE: AtomicBool = AtomicBool::new(true);
+static HEADLESS: AtomicBool = AtomicBool::new(false);
+static RESULT_ONLY: AtomicBool = AtomicBool::new(false);
+static THEME: OnceLock<Theme> = OnceLock::new();
+static VIEWPOINT: Mutex<Viewpoint> = Mutex::new(Viewpoint::Everything);
+
+const BLINKS: usize = 3;
+const BLINK_PAUSE: Duration = Duration::from_millis(120);
+
+pub fn configure(in_place: bool, animate: bool) {
+    IN_PLACE.store(in_place, Ordering::Relaxed);
+    ANIMATE.store(animate, Ordering::Relaxed);
+}
+
+/// Stops drawing the board at all, for games nobody watches.
+pub fn set_headless(headless: bool) {
+    HEADLESS.store(headless, Ordering::Relaxed);
+}
+
+pub fn headless() -> bool {
+    HEADLESS.load(Ordering::Relaxed)
+}
+
+/// Reports results as one parseable line per game, see `render::result_line`.
+pub fn set_result_only(result_only: bool) {
+    RESULT_ONLY.store(result_only, Ordering::Relaxed);
+}
+
+pub fn result_only() -> bool {
+    RESULT_ONLY.load(Ordering::Relaxed)
+}
+
+/// Whose view boards are drawn from, which only hides anything in fog of war.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Viewpoint {
+    Everything,
+    Side(Tile),
+    /// The side to move, for two humans at one screen.
+    ToMove,
+}
+
+pub fn set_viewpoint(viewpoint: Viewpoint) {
+    *VIEWPOINT.lock().unwrap_or_else(|err| err.into_inner()) = viewpoint;
+}
+
+/// The side boards are seen by when `to_move` is to move, `None` for the whole board.
+fn viewer(to_move: Tile) -> Option<Tile> {
+    match *VIEWPOINT.lock().unwrap_or_else(|err| err.into_inner()) {
+        Viewpoint::Everything => None,
+        Viewpoint::Side(side) => Some(side),
+        Viewpoint::ToMove     => Some(to_move),
+    }
+}
+
+/// Draws boards with `theme` from now on, it can only be chosen once.
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// The theme every board on the terminal is drawn with.
+pub fn theme() -> Theme {
+    THEME.get().copied().unwrap_or_default()
+}
+
+/// Shows the board under `header`, if any, with `to_move` to make the next move.
+pub fn show_board(game: &Game, theme: &Theme, header: Option<&str>, to_move: Tile) {
+    draw(game, theme, header, to_move, &[]);
+}
+
+/// Shows the board after the last move in the history, blinking that mark first when redrawing
+/// in place.
+pub fn show_move(game: &Game, theme: &Theme, header: &str) {
+    if headless() {
+        return;
+    }
+    let to_move = game.history().last().and_then(|m| m.tile.opposite()).unwrap_or(Tile::Cross);
+    let animate = ANIMATE.load(Ordering::Relaxed) && IN_PLACE.load(Ordering::Relaxed);
+    // A blinking tile in the fog would give the move away.
+    let hidden = viewer(to_move).map_or(Vec::new(), |side| game.hidden(side));
+    if let Some(m) = game.history().last().filter(|m| animate && !hidden.contains(&(m.row, m.col))) {
+        print!("\x1b[?25l");
+        for _ in 0..BLINKS {
+            draw(game, theme, Some(header), to_move, &[(m.row, m.col)]);
+            thread::sleep(BLINK_PAUSE);
+            draw(game, theme, Some(header), to_move, &[]);
+            thread::sleep(BLINK_PAUSE);
+        }
+        print!("\x1b[?25h");
+    }
+    draw(game, theme, Some(header), to_move, &[]);
+}
+
+/// Draws the board below `header` and the move number, whose turn it is and how much room is
+/// left, so a long game can be followed from any single drawing.
+fn draw(game: &Game, theme: &Theme, header: Option<&str>, to_move: Tile, highlight: &[(usize, usize)]) {
+    if headless() {
+        return;
+    }
+    if IN_PLACE.load(Ordering::Relaxed) {
+        print!("\x1b[H\x1b[2J");
+    }
+    if let Some(header) = header {
+        println!("{}", header);
+    }
+    println!("{}", render::turn_indicator(game, to_move, &player::names()));
+    println!("{}", render::board_view(game, viewer(to_move), theme, highlight));
+    let _ = io::stdout().flush();
+}
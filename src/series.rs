@@ -0,0 +1,114 @@
+//! Matches that change the rules from game to game, listed in a file one game per line with its
+//! variant and, optionally, a board of its own:
+//!
+//! ```text
+//! # variant  board
+//! standard
+//! fog        size=5 win=4
+//! decay:4
+//! hex        size=4
+//! ```
+//!
+//! The match is a best of as many games as there are lines, games without a board of their own
+//! are played on the one of the command line.
+
+use crate::setup::{Setup, MAX_SIZE};
+use tick_tack_toe::variant::Variant;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Round {
+    pub variant: Variant,
+    pub size: Option<usize>,
+    pub win_length: Option<usize>,
+}
+impl Round {
+    /// The size and win length of the round's board, the `base` one unless it has its own. A
+    /// round with a new size but no win length keeps the win length if it fits.
+    pub fn board(&self, (size, win_length): (usize, usize)) -> (usize, usize) {
+        let size = self.size.unwrap_or(size);
+        (size, self.win_length.unwrap_or(win_length.min(size)))
+    }
+
+    /// Checks that the round can be played on the board of `base` or its own.
+    pub fn check(&self, base: (usize, usize)) -> Result<(), String> {
+        let (size, win_length) = self.board(base);
+        if win_length > size {
+            return Err("The win length can't be longer than the board size.".to_string());
+        }
+        self.variant.check(size, win_length)
+    }
+
+    /// Changes `setup` to the rules of this round, on the board of `base` or its own.
+    pub fn apply(&self, setup: &mut Setup, base: (usize, usize)) -> Result<(), String> {
+        self.check(base)?;
+        (setup.size, setup.win_length) = self.board(base);
+        setup.variant = self.variant;
+        setup.validate()
+    }
+
+    /// E.g. `fog on 5x5, 4 in a row`.
+    pub fn describe(&self, setup: &Setup) -> String {
+        format!("{} on {1}x{1}, {2} in a row", self.variant.name(), setup.size, setup.win_length)
+    }
+}
+
+/// The rounds of a series file, skipping blank lines and `#` comments.
+pub fn parse(text: &str) -> Result<Vec<Round>, String> {
+    let mut rounds = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&name, settings)) = words.split_first() else { continue };
+
+        let round = parse_round(name, settings).map_err(|err| format!("Line {}: {}", number + 1, err))?;
+        rounds.push(round);
+    }
+
+    if rounds.is_empty() {
+        return Err("A series needs at least one game.".to_string());
+    }
+    Ok(rounds)
+}
+
+fn parse_round(name: &str, settings: &[&str]) -> Result<Round, String> {
+    let variant = Variant::named(name).ok_or(format!("'{}' isn't a variant, use standard, fog, hex, decay:N, morris:N or scoring.", name))?;
+    let mut round = Round { variant, size: None, win_length: None };
+    for setting in settings {
+        let (key, value) = setting.split_once('=').ok_or(format!("'{}' isn't a setting, use size=N or win=N.", setting))?;
+        let value = value.parse().ok().filter(|&value| value > 0).ok_or(format!("{} needs a positive number.", key))?;
+        match key {
+            "size" if value <= MAX_SIZE => round.size = Some(value),
+            "size" => return Err(format!("Boards go up to {}x{}.", MAX_SIZE, MAX_SIZE)),
+            "win"  => round.win_length = Some(value),
+            _ => return Err(format!("'{}' isn't a setting, use size=N or win=N.", key)),
+        }
+    }
+    Ok(round)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Round};
+    use tick_tack_toe::variant::Variant;
+
+    #[test]
+    fn parse_rounds() {
+        let rounds = parse("# mixed\nstandard\nfog size=5 win=4  # bigger\n\ndecay:4\n").unwrap();
+        assert_eq!(rounds.len(), 3);
+        assert_eq!(rounds[1], Round { variant: Variant::Fog, size: Some(5), win_length: Some(4) });
+        assert_eq!(rounds[2], Round { variant: Variant::Decay(4), size: None, win_length: None });
+
+        assert_eq!(parse("standard\nchess\n"), Err("Line 2: 'chess' isn't a variant, use standard, fog, hex, decay:N, morris:N or scoring.".to_string()));
+        assert_eq!(parse("fog size=big"), Err("Line 1: size needs a positive number.".to_string()));
+        assert!(parse("fog depth=3").is_err());
+        assert!(parse("hex size=27").is_err());
+        assert!(parse("# nothing\n").is_err());
+
+        // Rounds keep what they don't change of the command line's board.
+        assert_eq!(rounds[1].board((3, 3)), (5, 4));
+        assert_eq!(rounds[2].board((4, 3)), (4, 3));
+        let small = Round { variant: Variant::Standard, size: Some(2), win_length: None };
+        assert_eq!(small.board((5, 4)), (2, 2));
+        assert!(Round { win_length: Some(3), ..small }.check((5, 4)).is_err());
+    }
+}
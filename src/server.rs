@@ -0,0 +1,1017 @@
+//! A TCP server hosting many games at once. Clients speak the line delimited JSON-RPC of
+//! `jsonrpc`, with a `gameId` param on every call but `newGame`.
+//! Players join a game with `joinGame` and get a token for their side, which `applyMove` needs.
+//! Games are kept as records in the `games` directory of the data directory, so they survive a
+//! restart under the same IDs.
+//! Players with a profile can instead ask `findMatch` for an opponent of similar rating, and the
+//! result of a matched game updates both ratings.
+//! Finished games are also archived for good, abandoned ones as timed out, and `getRecord` or
+//! `GET /games/<id>` hands out their records so they can be replayed locally.
+//! `watchGame` streams a game's moves to the connection as `gameEvent` notifications, and
+//! browsers can follow the same stream over a WebSocket at `/games/<id>/live`.
+//! Games of the fog-of-war variant show each side only its own view, see `state`, and can't be
+//! watched and hand out no record until they end.
+//! `bestMove` answers the perfect move in any position without hosting a game, for clients that
+//! leave a side to the server, see `engine`.
+//! The same port answers plain HTTP `GET /leaderboard` requests with the best rated players.
+
+use crate::daily;
+use crate::jsonrpc::{self, Line, RpcResult, GAME_ERROR, INVALID_PARAMS, METHOD_NOT_FOUND};
+use rand::Rng;
+use crate::profile::{data_dir, Profiles, LEADERBOARD_SIZE};
+use crate::setup::MAX_PERFECT_AI_SIZE;
+use crate::websocket;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufReader, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use tick_tack_toe::board::{Board, BoardStatus, Tile};
+use tick_tack_toe::game::{Game, GameEvent, Move};
+use tick_tack_toe::json::Json;
+use tick_tack_toe::log;
+use tick_tack_toe::rating::DEFAULT_RATING;
+use tick_tack_toe::record::{Record, Termination};
+use tick_tack_toe::variant::Variant;
+
+const GAMES_DIR: &str = "games";
+/// Where finished games are kept, inside the games directory.
+const ARCHIVE_DIR: &str = "archive";
+
+/// A move was sent without the token of the side to move.
+const UNAUTHORIZED: i64 = -32001;
+/// The connection sent requests faster than `REQUESTS_PER_SECOND`, or has too many games.
+const RATE_LIMITED: i64 = -32002;
+
+/// The record tags the tokens and profile names of the two sides are saved under.
+const TOKEN_TAGS: [&str; 2] = ["CrossToken", "NoughtToken"];
+const NAME_TAGS: [&str; 2]  = ["CrossName", "NoughtName"];
+
+/// Finished games stay around this long so both players can see the result.
+const FINISHED_TTL: Duration = Duration::from_secs(5 * 60);
+/// Games nobody has moved in for this long are dropped.
+const ABANDONED_AFTER: Duration = Duration::from_secs(30 * 60);
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+/// Rejected moves a connection may send before it is dropped.
+const MAX_STRIKES: u32 = 20;
+/// Requests a connection may send per second on average, and in a burst.
+const REQUESTS_PER_SECOND: f64 = 20.0;
+const REQUEST_BURST: f64 = 40.0;
+/// Unfinished games one address may have created at a time.
+const MAX_GAMES_PER_ADDRESS: usize = 10;
+/// Players waiting for a match are paired with opponents within this many rating points, and the
+/// window widens by `WINDOW_GROWTH` points for every second they have waited.
+const INITIAL_WINDOW: f64 = 100.0;
+const WINDOW_GROWTH: f64 = 10.0;
+/// Why a fog-of-war game in progress can't be looked at from outside.
+const FOGGED: (i64, &str) = (GAME_ERROR, "Fog-of-war games can't be watched until they end.");
+/// Players who stop asking `findMatch` for this long have given up waiting.
+const TICKET_TTL: Duration = Duration::from_secs(10);
+
+type Shared = Arc<Mutex<Hosted>>;
+
+struct Hosted {
+    game: Game,
+    to_move: Tile,
+    /// The tokens handed out to the Cross and Nought players, `None` while a seat is free.
+    tokens: [Option<String>; 2],
+    /// The profile names of matched players, whose ratings the result changes.
+    names: [Option<String>; 2],
+    /// Who created the game, unknown for games loaded from disk.
+    creator: Option<IpAddr>,
+    last_active: Instant,
+    /// How long each move of the history took, from the move before it or the start.
+    times: Vec<Option<Duration>>,
+    /// The day the game was created, as the `Date` tag writes it.
+    date: String,
+}
+impl Hosted {
+    fn new(game: Game) -> Self {
+        let to_move = game.history().last().and_then(|m| m.tile.opposite()).unwrap_or(Tile::Cross);
+        let times = vec![None; game.history().len()];
+        Self { game, to_move, tokens: [None, None], names: [None, None], creator: None, last_active: Instant::now(), times, date: daily::record_date() }
+    }
+
+    fn seat(side: Tile) -> usize {
+        usize::from(side == Tile::Nought)
+    }
+
+    /// The side `token` plays, if any.
+    fn side_of(&self, token: Option<&str>) -> Option<Tile> {
+        let seat = self.tokens.iter().position(|t| t.is_some() && t.as_deref() == token)?;
+        Some([Tile::Cross, Tile::Nought][seat])
+    }
+
+    /// Whether the game is in fog of war, which only its players may look into.
+    fn fogged(&self) -> bool {
+        self.game.variant() == Variant::Fog && self.game.status() == BoardStatus::Continue
+    }
+}
+
+/// All hosted games by ID. The map is only locked to look games up, each game has its own lock
+/// so moves in different games don't wait for each other.
+#[derive(Default)]
+pub struct GameManager {
+    games: Mutex<HashMap<u64, Shared>>,
+    next_id: AtomicU64,
+    /// Where games are saved, `None` keeps them in memory only.
+    dir: Option<PathBuf>,
+    /// Locked before `games` when both are needed, as matching players adds their game.
+    lobby: Mutex<Lobby>,
+    /// The profiles matched players are rated in, `None` rates everyone `DEFAULT_RATING` and
+    /// records nothing. They are only saved when the games are.
+    profiles: Option<Mutex<Profiles>>,
+}
+impl GameManager {
+    /// Loads the games saved in `dir` and saves every change there.
+    pub fn persistent(dir: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+
+        let mut games = HashMap::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "txt") { continue }
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse().ok()) else { continue };
+
+            let record = fs::read_to_string(&path).ok().and_then(|text| Record::parse(&text).ok());
+            match record.as_ref().and_then(|r| Some((r, r.to_game().ok()?))) {
+                Some((record, game)) => {
+                    let mut hosted = Hosted::new(game);
+                    hosted.tokens = TOKEN_TAGS.map(|tag| record.tag(tag).map(str::to_string));
+                    hosted.names = NAME_TAGS.map(|tag| record.tag(tag).map(str::to_string));
+                    hosted.times = record.annotations.iter().map(|annotation| annotation.time).collect();
+                    if let Some(date) = record.tag("Date") {
+                        hosted.date = date.to_string();
+                    }
+                    games.insert(id, Arc::new(Mutex::new(hosted)));
+                }
+                None => log::warn!("skipping unreadable saved game {}", path.display()),
+            }
+        }
+
+        log::info!("loaded {} saved games", games.len());
+        Ok(Self {
+            next_id: AtomicU64::new(games.keys().max().copied().unwrap_or(0)),
+            games: Mutex::new(games),
+            dir: Some(dir),
+            ..Self::default()
+        })
+    }
+
+    /// Rates matched players with `profiles` and records their results there.
+    pub fn with_profiles(self, profiles: Profiles) -> Self {
+        Self { profiles: Some(Mutex::new(profiles)), ..self }
+    }
+
+    /// Answers a request from the client at `from`, `None` when it isn't on the network.
+    pub fn call(&self, method: &str, params: &Json, from: Option<IpAddr>) -> RpcResult {
+        match method {
+            "newGame"   => self.new_game(params, from),
+            "joinGame"  => self.join_game(params),
+            "findMatch" => self.find_match(params),
+            "applyMove" => self.apply_move(params),
+            "getGame"   => self.get_game(params),
+            "getRecord" => self.get_record(params),
+            "listGames" => Ok(self.list_games()),
+            "bestMove"  => best_move(params),
+            _ => Err((METHOD_NOT_FOUND, "Method not found.")),
+        }
+    }
+
+    fn new_game(&self, params: &Json, from: Option<IpAddr>) -> RpcResult {
+        // The server's board is the only one that counts, clients can't bring their own.
+        if params.get("board").is_some() || params.get("toMove").is_some() {
+            return Err((INVALID_PARAMS, "Hosted games start empty, 'board' and 'toMove' can't be set."));
+        }
+        if from.is_some() && self.unfinished_games_of(from) >= MAX_GAMES_PER_ADDRESS {
+            return Err((RATE_LIMITED, "Too many unfinished games from this address."));
+        }
+        let board = jsonrpc::new_board(params, 3)?;
+        let variant = match params.get("variant") {
+            Some(name) => name.as_str().and_then(Variant::named).ok_or((INVALID_PARAMS, "Expected a 'variant' of standard, fog, hex, decay:N, morris:N or scoring."))?,
+            None => Variant::Standard,
+        };
+        if variant.check(board.length(), board.win_row_length()).is_err() {
+            return Err((INVALID_PARAMS, "Under decay or in Morris a side must keep a row's worth of marks and leave tiles free."));
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let mut hosted = Hosted::new(Game::with_variant(board, variant));
+        hosted.creator = from;
+        let state = state(id, &hosted, None);
+        self.save(id, &hosted);
+        lock(&self.games).insert(id, Arc::new(Mutex::new(hosted)));
+
+        log::info!("created game {}", id);
+        Ok(state)
+    }
+
+    /// Seats the caller on the first free side and hands out its token.
+    fn join_game(&self, params: &Json) -> RpcResult {
+        let (id, hosted) = self.get(params)?;
+        let mut hosted = lock(&hosted);
+
+        let seat = hosted.tokens.iter().position(Option::is_none).ok_or((GAME_ERROR, "The game is full."))?;
+        let token = format!("{:032x}", rand::thread_rng().gen::<u128>());
+        hosted.tokens[seat] = Some(token.clone());
+        self.save(id, &hosted);
+
+        let side = [Tile::Cross, Tile::Nought][seat];
+        log::info!("{:?} joined game {}", side, id);
+        Ok(Json::object([("gameId", Json::from(id as usize)), ("side", side.char().into()), ("token", token.into())]))
+    }
+
+    fn apply_move(&self, params: &Json) -> RpcResult {
+        let (id, hosted) = self.get(params)?;
+        let mut hosted = lock(&hosted);
+        let (row, col) = jsonrpc::position(params)?;
+        // In Morris a piece is moved with `fromRow` and `fromCol`.
+        let from = match (params.get("fromRow").and_then(Json::as_usize), params.get("fromCol").and_then(Json::as_usize)) {
+            (Some(from_row), Some(from_col)) => Some((from_row, from_col)),
+            _ => None,
+        };
+
+        let side = hosted.to_move;
+        let token = params.get("token").and_then(Json::as_str).ok_or((INVALID_PARAMS, "Expected a string param 'token'."))?;
+        if hosted.tokens[Hosted::seat(side)].as_deref() != Some(token) {
+            return Err((UNAUTHORIZED, "That token doesn't play the side to move."));
+        }
+
+        hosted.game.play(Move { tile: side, row, col, from }).map_err(|err| (GAME_ERROR, err.message()))?;
+        hosted.to_move = side.opposite().unwrap_or(side);
+        let time = hosted.last_active.elapsed();
+        hosted.times.push(Some(time));
+        hosted.last_active = Instant::now();
+        self.save(id, &hosted);
+        if hosted.game.status() != BoardStatus::Continue {
+            self.archive(id, &public_record(&hosted));
+            self.rate(&hosted);
+        }
+        Ok(state(id, &hosted, Some(side)))
+    }
+
+    /// Puts the named player in the lobby, or hands them their seat once they have been matched.
+    /// Clients keep calling it until the answer is a seat instead of `{"waiting": true}`.
+    fn find_match(&self, params: &Json) -> RpcResult {
+        let name = params.get("name")
+            .and_then(Json::as_str)
+            .filter(|name| !name.is_empty() && !name.contains('\t'))
+            .ok_or((INVALID_PARAMS, "Expected a profile name param 'name'."))?;
+        let now = Instant::now();
+        let lobby = &mut *lock(&self.lobby);
+
+        lobby.waiting.retain(|ticket| now.saturating_duration_since(ticket.polled) < TICKET_TTL);
+        match lobby.waiting.iter_mut().find(|ticket| ticket.name == name) {
+            Some(ticket) => ticket.polled = now,
+            None if !lobby.matched.contains_key(name) => {
+                let rating = self.rating(name);
+                lobby.waiting.push(Ticket { name: name.to_string(), rating, since: now, polled: now });
+            }
+            None => (),
+        }
+
+        while let Some((first, second)) = pick(&lobby.waiting, now) {
+            // Whoever waited longer plays Cross, the waiting list is in order of arrival.
+            let nought = lobby.waiting.remove(second);
+            let cross = lobby.waiting.remove(first);
+            for (name, seat) in self.start_match([cross.name, nought.name]) {
+                lobby.matched.insert(name, seat);
+            }
+        }
+
+        Ok(lobby.matched.remove(name).unwrap_or_else(|| Json::object([("waiting", Json::from(true))])))
+    }
+
+    /// Creates a 3x3 game with both seats taken by `names`, returns each name's seat.
+    fn start_match(&self, names: [String; 2]) -> Vec<(String, Json)> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut hosted = Hosted::new(Game::new(Board::new(3, 3)));
+        hosted.tokens = [(); 2].map(|_| Some(format!("{:032x}", rand::thread_rng().gen::<u128>())));
+        hosted.names = names.clone().map(Some);
+        log::info!("matched {} against {} in game {}", names[0], names[1], id);
+
+        let seats: Vec<(String, Json)> = names.into_iter()
+            .zip(&hosted.tokens)
+            .zip([Tile::Cross, Tile::Nought])
+            .map(|((name, token), side)| {
+                let token = token.clone().unwrap_or_default();
+                (name, Json::object([("gameId", Json::from(id as usize)), ("side", side.char().into()), ("token", token.into())]))
+            })
+            .collect();
+        self.save(id, &hosted);
+        lock(&self.games).insert(id, Arc::new(Mutex::new(hosted)));
+        seats
+    }
+
+    fn rating(&self, name: &str) -> i32 {
+        self.profiles.as_ref()
+            .and_then(|profiles| lock(profiles).get(name).map(|profile| profile.rating))
+            .unwrap_or(DEFAULT_RATING)
+    }
+
+    /// The best rated players, as `[{"name", "rating", "games", "winRate"}]`.
+    pub fn leaderboard(&self) -> Json {
+        let Some(profiles) = &self.profiles else { return Json::Array(Vec::new()) };
+
+        let leaders = lock(profiles).leaderboard(LEADERBOARD_SIZE)
+            .into_iter()
+            .map(|profile| Json::object([
+                ("name", profile.name.as_str().into()),
+                ("rating", Json::from(i64::from(profile.rating))),
+                ("games", Json::from(profile.games() as usize)),
+                ("winRate", Json::Number(profile.win_rate())),
+            ]))
+            .collect();
+        Json::Array(leaders)
+    }
+
+    /// Records the result of a finished matched game in the players' profiles.
+    fn rate(&self, hosted: &Hosted) {
+        let (Some(profiles), [Some(_), Some(_)]) = (&self.profiles, &hosted.names) else { return };
+        let board = hosted.game.board();
+
+        let mut profiles = lock(profiles);
+        profiles.record_game(hosted.names.each_ref().map(Option::as_deref), hosted.game.status(), board.length(), board.win_row_length());
+        if self.dir.is_none() { return }
+        if let Err(err) = profiles.save() {
+            log::error!("couldn't save profiles: {}", err);
+        }
+    }
+
+    /// The state of the game, as the side of the `token` param sees it when one is given.
+    fn get_game(&self, params: &Json) -> RpcResult {
+        let (id, hosted) = self.get(params)?;
+        let hosted = lock(&hosted);
+        let viewer = hosted.side_of(params.get("token").and_then(Json::as_str));
+        Ok(state(id, &hosted, viewer))
+    }
+
+    fn get_record(&self, params: &Json) -> RpcResult {
+        let id = game_id(params)?;
+        let record = self.record(id)?;
+        Ok(Json::object([("gameId", Json::from(id as usize)), ("record", record.into())]))
+    }
+
+    /// The record of a hosted or archived game, without the players' tokens.
+    pub fn record(&self, id: u64) -> Result<String, (i64, &'static str)> {
+        const NO_GAME: (i64, &str) = (GAME_ERROR, "No such game.");
+        match lock(&self.games).get(&id).cloned() {
+            Some(hosted) => {
+                let hosted = lock(&hosted);
+                if hosted.fogged() {
+                    return Err(FOGGED);
+                }
+                Ok(public_record(&hosted).to_string())
+            }
+            None => fs::read_to_string(self.archive_path(id).ok_or(NO_GAME)?).map_err(|_| NO_GAME),
+        }
+    }
+
+    fn list_games(&self) -> Json {
+        let games: Vec<(u64, Shared)> = lock(&self.games).iter().map(|(id, g)| (*id, g.clone())).collect();
+
+        let mut games: Vec<Json> = games.into_iter()
+            .map(|(id, hosted)| {
+                let hosted = lock(&hosted);
+                Json::object([("gameId", Json::from(id as usize)), ("status", status_name(hosted.game.status()).into())])
+            })
+            .collect();
+        games.sort_by_key(|g| g.get("gameId").and_then(Json::as_usize));
+        Json::Array(games)
+    }
+
+    fn unfinished_games_of(&self, creator: Option<IpAddr>) -> usize {
+        let games: Vec<Shared> = lock(&self.games).values().cloned().collect();
+        games.iter()
+            .filter(|hosted| {
+                let hosted = lock(hosted);
+                hosted.creator == creator && hosted.game.status() == BoardStatus::Continue
+            })
+            .count()
+    }
+
+    /// The state of game `id` and a stream of its events from then on.
+    pub fn watch(&self, id: u64) -> Result<(Json, mpsc::Receiver<GameEvent>), (i64, &'static str)> {
+        let hosted = lock(&self.games).get(&id).cloned().ok_or((GAME_ERROR, "No such game."))?;
+        let mut hosted = lock(&hosted);
+        if hosted.fogged() {
+            return Err(FOGGED);
+        }
+        Ok((state(id, &hosted, None), hosted.game.subscribe()))
+    }
+
+    fn get(&self, params: &Json) -> Result<(u64, Shared), (i64, &'static str)> {
+        let id = game_id(params)?;
+        let hosted = lock(&self.games).get(&id).cloned().ok_or((GAME_ERROR, "No such game."))?;
+        Ok((id, hosted))
+    }
+
+    /// Drops games that finished `FINISHED_TTL` before `now` or were abandoned, returns how many.
+    pub fn sweep(&self, now: Instant) -> usize {
+        let mut lobby = lock(&self.lobby);
+        let mut games = lock(&self.games);
+        let before = games.len();
+
+        games.retain(|id, hosted| {
+            let hosted = lock(hosted);
+            let idle = now.saturating_duration_since(hosted.last_active);
+            let finished = hosted.game.status() != BoardStatus::Continue;
+
+            let keep = idle < if finished { FINISHED_TTL } else { ABANDONED_AFTER };
+            if !keep && !finished {
+                let mut record = public_record(&hosted);
+                record.set_result(BoardStatus::Continue, Termination::Timeout);
+                self.archive(*id, &record);
+            }
+            match self.path(*id) {
+                Some(path) if !keep => {
+                    if let Err(err) = fs::remove_file(&path) {
+                        log::warn!("couldn't remove {}: {}", path.display(), err);
+                    }
+                }
+                _ => (),
+            }
+            keep
+        });
+        // Seats in games that are gone aren't worth handing out.
+        lobby.matched.retain(|_, seat| seat.get("gameId").and_then(Json::as_usize).is_some_and(|id| games.contains_key(&(id as u64))));
+        before - games.len()
+    }
+
+    fn path(&self, id: u64) -> Option<PathBuf> {
+        Some(self.dir.as_ref()?.join(format!("{}.txt", id)))
+    }
+
+    fn archive_path(&self, id: u64) -> Option<PathBuf> {
+        Some(self.dir.as_ref()?.join(ARCHIVE_DIR).join(format!("{}.txt", id)))
+    }
+
+    fn archive(&self, id: u64, record: &Record) {
+        let Some(path) = self.archive_path(id) else { return };
+
+        let archived = path.parent().map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, record.to_string()));
+        if let Err(err) = archived {
+            log::warn!("couldn't archive game {}: {}", id, err);
+        }
+    }
+
+    /// Writes the game's record, through a temporary file so a crash never leaves half a record.
+    fn save(&self, id: u64, hosted: &Hosted) {
+        let Some(path) = self.path(id) else { return };
+        let temporary = path.with_extension("tmp");
+
+        let mut record = public_record(hosted);
+        for (tag, token) in TOKEN_TAGS.iter().zip(&hosted.tokens) {
+            if let Some(token) = token {
+                record.set_tag(tag, token.as_str());
+            }
+        }
+
+        let saved = fs::write(&temporary, record.to_string()).and_then(|_| fs::rename(&temporary, &path));
+        if let Err(err) = saved {
+            log::warn!("couldn't save game {}: {}", id, err);
+        }
+    }
+}
+
+/// Serves clients on `address` until the process is stopped, a thread per connection.
+pub fn serve(address: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    let manager = Arc::new(GameManager::persistent(data_dir().join(GAMES_DIR))?.with_profiles(Profiles::load()?));
+    println!("Serving games on {}", listener.local_addr()?);
+
+    let sweeper = manager.clone();
+    thread::spawn(move || loop {
+        thread::sleep(SWEEP_INTERVAL);
+        let dropped = sweeper.sweep(Instant::now());
+        if dropped > 0 {
+            log::info!("dropped {} finished or abandoned games", dropped);
+        }
+    });
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                log::warn!("couldn't accept a connection: {}", err);
+                continue;
+            }
+        };
+        let manager = manager.clone();
+        thread::spawn(move || {
+            if let Err(err) = handle_connection(&manager, stream) {
+                log::debug!("connection closed: {}", err);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(manager: &GameManager, stream: TcpStream) -> io::Result<()> {
+    // Shared with the threads forwarding watched games' events.
+    let writer = Arc::new(Mutex::new(stream.try_clone()?));
+    let mut client = Client::new(stream.peer_addr()?);
+    let mut reader = BufReader::new(stream);
+
+    while let Some(line) = jsonrpc::read_line(&mut reader)? {
+        let Line::Text(line) = line else {
+            writeln!(lock(&writer), "{}", jsonrpc::too_long())?;
+            continue;
+        };
+        if line.trim().is_empty() { continue }
+        if line.starts_with("GET ") {
+            // Browsers and curl speak HTTP, answer the one request once its headers are read.
+            let mut headers = Vec::new();
+            while let Some(Line::Text(header)) = jsonrpc::read_line(&mut reader)? {
+                if header.is_empty() { break }
+                headers.push(header);
+            }
+            return answer_http(manager, &line, &headers, &mut lock(&writer));
+        }
+
+        let response = jsonrpc::respond(&line, |method, params| {
+            if !client.allow(Instant::now()) {
+                return Err((RATE_LIMITED, "Too many requests, slow down."));
+            }
+            if method == "watchGame" {
+                return watch_game(manager, params, &writer);
+            }
+            let result = manager.call(method, params, Some(client.peer.ip()));
+            client.check(method, &result);
+            result
+        });
+        if let Some(response) = response {
+            writeln!(lock(&writer), "{}", response)?;
+        }
+        if client.strikes >= MAX_STRIKES {
+            log::warn!("dropping {} after {} rejected moves", client.peer, client.strikes);
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Answers `watchGame` with the game's state, and from then on sends the connection a
+/// `gameEvent` notification for every move and for the end of the game.
+fn watch_game(manager: &GameManager, params: &Json, writer: &Arc<Mutex<TcpStream>>) -> RpcResult {
+    let id = game_id(params)?;
+    let (state, events) = manager.watch(id)?;
+
+    let writer = writer.clone();
+    thread::spawn(move || {
+        for event in events.iter().filter_map(|event| event_json(id, event)) {
+            let notification = Json::object([
+                ("jsonrpc", Json::from("2.0")),
+                ("method", Json::from("gameEvent")),
+                ("params", event),
+            ]);
+            if writeln!(lock(&writer), "{}", notification).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(state)
+}
+
+/// Answers a `GET` request, which for `/games/<id>/live` with a WebSocket handshake means
+/// streaming the game's events until it ends.
+fn answer_http(manager: &GameManager, request: &str, headers: &[String], writer: &mut TcpStream) -> io::Result<()> {
+    let key = headers.iter().find_map(|header| {
+        let (name, value) = header.split_once(':')?;
+        name.trim().eq_ignore_ascii_case("Sec-WebSocket-Key").then_some(value.trim())
+    });
+    let live = request.split_whitespace()
+        .nth(1)
+        .and_then(|path| path.strip_prefix("/games/")?.strip_suffix("/live")?.parse().ok())
+        .and_then(|id| Some((id, manager.watch(id).ok()?)));
+
+    let (Some(key), Some((id, (state, events)))) = (key, live) else {
+        return writer.write_all(http_response(manager, request).as_bytes());
+    };
+    writer.write_all(websocket::handshake_response(key).as_bytes())?;
+    writer.write_all(&websocket::text_frame(&state.to_string()))?;
+    for event in events {
+        if let Some(json) = event_json(id, event) {
+            writer.write_all(&websocket::text_frame(&json.to_string()))?;
+        }
+        if let GameEvent::Ended(_) = event {
+            break;
+        }
+    }
+    writer.write_all(&websocket::CLOSE_FRAME)
+}
+
+/// The whole HTTP response to the `GET` request line `request`.
+fn http_response(manager: &GameManager, request: &str) -> String {
+    const JSON: &str = "application/json";
+    const TEXT: &str = "text/plain; charset=utf-8";
+
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+    let path = path.split('?').next().unwrap_or(path);
+    let record = path.strip_prefix("/games/").and_then(|id| id.parse().ok()).and_then(|id| manager.record(id).ok());
+
+    let (status, content_type, body) = match (path, record) {
+        ("/leaderboard", _) => ("200 OK", JSON, manager.leaderboard().to_string()),
+        (_, Some(record))   => ("200 OK", TEXT, record),
+        _ => ("404 Not Found", JSON, Json::object([("error", Json::from("Not found."))]).to_string()),
+    };
+
+    format!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, content_type, body.len(), body)
+}
+
+/// Limits the rate of a connection's requests, and keeps count of the moves it got rejected.
+/// Clients that keep sending illegal or unauthorized moves are cheating or broken either way.
+struct Client {
+    peer: SocketAddr,
+    strikes: u32,
+    /// Requests that may be sent right now, refilled at `REQUESTS_PER_SECOND`.
+    allowance: f64,
+    refilled: Instant,
+}
+impl Client {
+    fn new(peer: SocketAddr) -> Self {
+        Self { peer, strikes: 0, allowance: REQUEST_BURST, refilled: Instant::now() }
+    }
+
+    /// Whether a request arriving at `now` is within the limit.
+    fn allow(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.allowance = (self.allowance + elapsed * REQUESTS_PER_SECOND).min(REQUEST_BURST);
+        self.refilled = now;
+
+        if self.allowance < 1.0 {
+            return false;
+        }
+        self.allowance -= 1.0;
+        true
+    }
+
+    fn check(&mut self, method: &str, result: &RpcResult) {
+        match result {
+            Err((code, message)) if method == "applyMove" && (*code == GAME_ERROR || *code == UNAUTHORIZED) => {
+                self.strikes += 1;
+                log::warn!("suspicious move from {} ({} so far): {}", self.peer, self.strikes, message);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// A player waiting in the lobby for an opponent.
+struct Ticket {
+    name: String,
+    rating: i32,
+    /// When the player started waiting, which widens their window.
+    since: Instant,
+    /// When the player last asked for a match.
+    polled: Instant,
+}
+
+#[derive(Default)]
+struct Lobby {
+    /// Players waiting for a match, in order of arrival.
+    waiting: Vec<Ticket>,
+    /// The seats of matched players who haven't been told yet.
+    matched: HashMap<String, Json>,
+}
+
+/// The rating difference a player who has waited `waited` accepts.
+fn window(waited: Duration) -> f64 {
+    INITIAL_WINDOW + WINDOW_GROWTH * waited.as_secs_f64()
+}
+
+/// The indices of the two waiting players with the closest ratings that either of them accepts,
+/// the earlier arrival first.
+fn pick(waiting: &[Ticket], now: Instant) -> Option<(usize, usize)> {
+    let accepts = |ticket: &Ticket, difference| difference as f64 <= window(now.saturating_duration_since(ticket.since));
+
+    (0..waiting.len())
+        .flat_map(|first| (first + 1..waiting.len()).map(move |second| (first, second)))
+        .map(|(first, second)| (first, second, (waiting[first].rating - waiting[second].rating).abs()))
+        .filter(|&(first, second, difference)| accepts(&waiting[first], difference) || accepts(&waiting[second], difference))
+        .min_by_key(|&(_, _, difference)| difference)
+        .map(|(first, second, _)| (first, second))
+}
+
+fn game_id(params: &Json) -> Result<u64, (i64, &'static str)> {
+    let id = params.get("gameId").and_then(Json::as_usize).ok_or((INVALID_PARAMS, "Expected an integer param 'gameId'."))?;
+    Ok(id as u64)
+}
+
+/// A watched game's event as the params of a `gameEvent` notification, `None` for status
+/// changes, which the moves and the end already tell.
+fn event_json(id: u64, event: GameEvent) -> Option<Json> {
+    let mut fields = vec![("gameId", Json::from(id as usize))];
+    match event {
+        GameEvent::Moved(m) => fields.extend([
+            ("event", Json::from("move")),
+            ("side", m.tile.char().into()),
+            ("row", m.row.into()),
+            ("col", m.col.into()),
+        ]),
+        GameEvent::Ended(status) => {
+            fields.extend([("event", Json::from("end")), ("status", status_name(status).into())]);
+            if let BoardStatus::Winner(tile) = status {
+                fields.push(("winner", tile.char().into()));
+            }
+        }
+        GameEvent::StatusChanged(_) => return None,
+    }
+    Some(Json::object(fields))
+}
+
+/// The perfect move in the position given as to `newGame` of `serve` on stdin, for clients that leave
+/// a side to this machine. Nothing is hosted.
+fn best_move(params: &Json) -> RpcResult {
+    let (board, side) = jsonrpc::game(params)?;
+    // The search goes to the end of the game, which would hold a connection for far too long on
+    // larger boards.
+    if board.length() > MAX_PERFECT_AI_SIZE {
+        return Err((INVALID_PARAMS, "The engine only searches boards up to 3x3."));
+    }
+    if board.board_status() != BoardStatus::Continue {
+        return Err((GAME_ERROR, "The game is already over."));
+    }
+    jsonrpc::best_move(&board, side)
+}
+
+/// The game's record with the date, the players' names and, once it's over, the result.
+fn public_record(hosted: &Hosted) -> Record {
+    let mut record = Record::of(&hosted.game);
+    record.set_times(&hosted.times);
+    record.set_tag("Date", hosted.date.as_str());
+    for (tag, name) in NAME_TAGS.iter().zip(&hosted.names) {
+        if let Some(name) = name {
+            record.set_tag(tag, name.as_str());
+        }
+    }
+    let status = hosted.game.status();
+    if status != BoardStatus::Continue {
+        record.set_result(status, Termination::of(status));
+    }
+    record
+}
+
+/// The `jsonrpc::state` of a hosted game with its ID, and the names of matched players for
+/// clients to show. In fog of war the board is the one `viewer` sees, with `?` for the tiles it
+/// can't see, and all `?` without a viewer.
+fn state(id: u64, hosted: &Hosted, viewer: Option<Tile>) -> Json {
+    let mut state = jsonrpc::state(hosted.game.board(), hosted.to_move);
+    if let Json::Object(fields) = &mut state {
+        fields.insert(0, ("gameId".to_string(), Json::from(id as usize)));
+        if hosted.fogged() {
+            let length = hosted.game.board().length();
+            let view = viewer.map(|side| (hosted.game.view(side), hosted.game.hidden(side)));
+            let rows = (0..length)
+                .map(|row| {
+                    (0..length)
+                        .map(|col| match &view {
+                            Some((board, hidden)) if !hidden.contains(&(row, col)) => board.get(row, col).unwrap_or(Tile::Empty).char(),
+                            _ => "?",
+                        })
+                        .collect::<String>()
+                        .into()
+                })
+                .collect();
+            if let Some((_, value)) = fields.iter_mut().find(|(name, _)| name == "board") {
+                *value = Json::Array(rows);
+            }
+        }
+        if hosted.game.variant() != Variant::Standard {
+            fields.push(("variant".to_string(), Json::from(hosted.game.variant().name().as_str())));
+        }
+        let names: Vec<(String, Json)> = [Tile::Cross, Tile::Nought].iter().zip(&hosted.names)
+            .filter_map(|(tile, name)| Some((tile.char().to_string(), Json::from(name.as_deref()?))))
+            .collect();
+        if !names.is_empty() {
+            fields.push(("names".to_string(), Json::object(names)));
+        }
+    }
+    state
+}
+
+fn status_name(status: BoardStatus) -> &'static str {
+    match status {
+        BoardStatus::Winner(_) => "winner",
+        BoardStatus::Tie       => "tie",
+        BoardStatus::Continue  => "continue",
+    }
+}
+
+/// Locks `mutex`, a panic in another connection's thread doesn't make its data unusable.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{event_json, http_response, lock, pick, Client, GameManager, Ticket, ABANDONED_AFTER, FINISHED_TTL, MAX_GAMES_PER_ADDRESS, REQUEST_BURST};
+    use super::{GAME_ERROR, INVALID_PARAMS, RATE_LIMITED, UNAUTHORIZED};
+    use crate::profile::Profiles;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use tick_tack_toe::json::Json;
+
+    fn call(manager: &GameManager, method: &str, params: &str) -> String {
+        match manager.call(method, &Json::parse(params).unwrap(), None) {
+            Ok(result) => result.to_string(),
+            Err((_, message)) => message.to_string(),
+        }
+    }
+
+    fn join(manager: &GameManager, id: usize) -> String {
+        let joined = manager.call("joinGame", &Json::parse(&format!(r#"{{"gameId":{}}}"#, id)).unwrap(), None).unwrap();
+        joined.get("token").and_then(Json::as_str).unwrap().to_string()
+    }
+
+    #[test]
+    fn manage_games() {
+        let manager = GameManager::default();
+        assert!(call(&manager, "newGame", "{}").starts_with(r#"{"gameId":1,"#));
+        assert!(call(&manager, "newGame", r#"{"size":1,"winLength":1}"#).starts_with(r#"{"gameId":2,"#));
+
+        let (cross, nought) = (join(&manager, 1), join(&manager, 1));
+        assert_eq!(call(&manager, "joinGame", r#"{"gameId":1}"#), "The game is full.");
+        let spoofed = format!(r#"{{"gameId":1,"row":1,"col":1,"token":"{}"}}"#, nought);
+        assert_eq!(call(&manager, "applyMove", &spoofed), "That token doesn't play the side to move.");
+        assert_eq!(call(&manager, "applyMove", r#"{"gameId":1,"row":1,"col":1}"#), "Expected a string param 'token'.");
+
+        let legal = format!(r#"{{"gameId":1,"row":1,"col":1,"token":"{}"}}"#, cross);
+        assert!(call(&manager, "applyMove", &legal).contains(r#""toMove":"O""#));
+        let occupied = format!(r#"{{"gameId":1,"row":1,"col":1,"token":"{}"}}"#, nought);
+        assert_eq!(call(&manager, "applyMove", &occupied), "Already occupied tile.");
+
+        let winning = format!(r#"{{"gameId":2,"row":0,"col":0,"token":"{}"}}"#, join(&manager, 2));
+        assert!(call(&manager, "applyMove", &winning).contains(r#""status":"winner""#));
+        assert_eq!(call(&manager, "getGame", r#"{"gameId":3}"#), "No such game.");
+        assert_eq!(
+            call(&manager, "listGames", "{}"),
+            r#"[{"gameId":1,"status":"continue"},{"gameId":2,"status":"winner"}]"#
+        );
+
+        let now = Instant::now();
+        assert_eq!(manager.sweep(now), 0);
+        assert_eq!(manager.sweep(now + FINISHED_TTL + Duration::from_secs(1)), 1);
+        assert_eq!(manager.sweep(now + ABANDONED_AFTER + Duration::from_secs(1)), 1);
+    }
+
+    #[test]
+    fn limits() {
+        let mut client = Client::new("127.0.0.1:4000".parse().unwrap());
+        let now = client.refilled;
+        assert_eq!((0..50).filter(|_| client.allow(now)).count(), REQUEST_BURST as usize);
+        assert!(client.allow(now + Duration::from_millis(100)));
+
+        let manager = GameManager::default();
+        let from = Some(client.peer.ip());
+        for _ in 0..MAX_GAMES_PER_ADDRESS {
+            assert!(manager.call("newGame", &Json::Null, from).is_ok());
+        }
+        assert_eq!(manager.call("newGame", &Json::Null, from), Err((RATE_LIMITED, "Too many unfinished games from this address.")));
+        // The limit is checked before the board is made.
+        let huge = Json::parse(r#"{"size":27}"#).unwrap();
+        assert_eq!(manager.call("newGame", &huge, from), Err((RATE_LIMITED, "Too many unfinished games from this address.")));
+        assert!(manager.call("newGame", &Json::Null, None).is_ok());
+
+        client.check("applyMove", &Err((UNAUTHORIZED, "")));
+        client.check("applyMove", &Err((GAME_ERROR, "")));
+        client.check("applyMove", &Err((INVALID_PARAMS, "")));
+        client.check("getGame", &Err((GAME_ERROR, "")));
+        assert_eq!(client.strikes, 2);
+
+        assert!(call(&manager, "newGame", r#"{"board":["XXX","   ","   "]}"#).contains("start empty"));
+
+        assert_eq!(call(&manager, "bestMove", r#"{"board":["XX ","OO ","   "]}"#), r#"{"row":0,"col":2}"#);
+        assert_eq!(call(&manager, "bestMove", r#"{"size":4}"#), "The engine only searches boards up to 3x3.");
+    }
+
+    #[test]
+    fn matchmaking() {
+        let now = Instant::now();
+        let ticket = |rating| Ticket { name: String::new(), rating, since: now, polled: now };
+        assert_eq!(pick(&[ticket(1200), ticket(1500), ticket(1250)], now), Some((0, 2)));
+        assert_eq!(pick(&[ticket(1200), ticket(1500)], now), None);
+        assert_eq!(pick(&[ticket(1200), ticket(1500)], now + Duration::from_secs(25)), Some((0, 1)));
+
+        let manager = GameManager::default().with_profiles(Profiles::default());
+        assert_eq!(call(&manager, "findMatch", r#"{"name":"Alice"}"#), r#"{"waiting":true}"#);
+        assert_eq!(call(&manager, "findMatch", r#"{"name":""}"#), "Expected a profile name param 'name'.");
+        let bob = manager.call("findMatch", &Json::parse(r#"{"name":"Bob"}"#).unwrap(), None).unwrap();
+        let alice = manager.call("findMatch", &Json::parse(r#"{"name":"Alice"}"#).unwrap(), None).unwrap();
+        assert_eq!((alice.get("side"), bob.get("side")), (Some(&Json::from("X")), Some(&Json::from("O"))));
+        assert_eq!(alice.get("gameId"), bob.get("gameId"));
+        assert!(call(&manager, "getGame", r#"{"gameId":1}"#).ends_with(r#""names":{"X":"Alice","O":"Bob"}}"#));
+
+        let token = |seat: &Json| seat.get("token").and_then(Json::as_str).unwrap().to_string();
+        for (seat, (row, col)) in [&alice, &bob, &alice, &bob, &alice].into_iter().zip([(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]) {
+            call(&manager, "applyMove", &format!(r#"{{"gameId":1,"row":{},"col":{},"token":"{}"}}"#, row, col, token(seat)));
+        }
+        let profiles = lock(manager.profiles.as_ref().unwrap());
+        assert_eq!(profiles.get("Alice").map(|p| (p.wins, p.rating)), Some((1, 1216)));
+        assert_eq!(profiles.get("Bob").map(|p| (p.losses, p.rating)), Some((1, 1184)));
+        drop(profiles);
+
+        let response = http_response(&manager, "GET /leaderboard HTTP/1.1");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(r#"[{"name":"Alice","rating":1216,"games":1,"winRate":1},{"name":"Bob","rating":1184,"games":1,"winRate":0}]"#));
+        assert!(http_response(&manager, "GET / HTTP/1.1").starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        // Sweeping while players are matched takes the locks in the same order, or they deadlock.
+        let busy = GameManager::default();
+        let matched = AtomicBool::new(false);
+        thread::scope(|scope| {
+            scope.spawn(|| while !matched.load(Ordering::Relaxed) {
+                busy.sweep(Instant::now());
+            });
+            for i in 0..1000 {
+                call(&busy, "findMatch", &format!(r#"{{"name":"a{}"}}"#, i));
+                call(&busy, "findMatch", &format!(r#"{{"name":"b{}"}}"#, i));
+            }
+            matched.store(true, Ordering::Relaxed);
+        });
+    }
+
+    #[test]
+    fn watch_games() {
+        let manager = GameManager::default();
+        call(&manager, "newGame", r#"{"size":1,"winLength":1}"#);
+        let (state, events) = manager.watch(1).unwrap();
+        assert!(state.to_string().contains(r#""status":"continue""#));
+        assert!(manager.watch(2).is_err());
+
+        call(&manager, "applyMove", &format!(r#"{{"gameId":1,"row":0,"col":0,"token":"{}"}}"#, join(&manager, 1)));
+        let events: Vec<String> = events.try_iter().filter_map(|event| event_json(1, event)).map(|json| json.to_string()).collect();
+        assert_eq!(events, [
+            r#"{"gameId":1,"event":"move","side":"X","row":0,"col":0}"#,
+            r#"{"gameId":1,"event":"end","status":"winner","winner":"X"}"#,
+        ]);
+    }
+
+    #[test]
+    fn fog_of_war() {
+        let manager = GameManager::default();
+        assert!(call(&manager, "newGame", r#"{"variant":"fog"}"#).contains(r#""board":["???","???","???"]"#));
+        assert_eq!(call(&manager, "newGame", r#"{"variant":"chess"}"#), "Expected a 'variant' of standard, fog, hex, decay:N, morris:N or scoring.");
+        let (cross, nought) = (join(&manager, 1), join(&manager, 1));
+
+        let reply = call(&manager, "applyMove", &format!(r#"{{"gameId":1,"row":0,"col":0,"token":"{}"}}"#, cross));
+        assert!(reply.contains(r#""board":["X ?","  ?","???"]"#) && reply.contains(r#""variant":"fog""#), "{}", reply);
+        let reply = call(&manager, "getGame", &format!(r#"{{"gameId":1,"token":"{}"}}"#, nought));
+        assert!(reply.contains(r#""board":["???","???","???"]"#), "{}", reply);
+        assert!(call(&manager, "getGame", r#"{"gameId":1}"#).contains(r#""board":["???","???","???"]"#));
+        assert!(manager.watch(1).is_err());
+        assert_eq!(call(&manager, "getRecord", r#"{"gameId":1}"#), "Fog-of-war games can't be watched until they end.");
+        // Playing onto a hidden mark is turned down like any taken tile.
+        assert_eq!(call(&manager, "applyMove", &format!(r#"{{"gameId":1,"row":0,"col":0,"token":"{}"}}"#, nought)), "Already occupied tile.");
+    }
+
+    #[test]
+    fn persist_games() {
+        let dir = std::env::temp_dir().join(format!("tic-tac-toe-games-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let manager = GameManager::persistent(dir.clone()).unwrap();
+        call(&manager, "newGame", r#"{"size":1,"winLength":1}"#);
+        call(&manager, "newGame", "{}");
+        let cross = join(&manager, 2);
+        call(&manager, "applyMove", &format!(r#"{{"gameId":2,"row":0,"col":1,"token":"{}"}}"#, cross));
+        let nought = join(&manager, 2);
+        call(&manager, "applyMove", &format!(r#"{{"gameId":1,"row":0,"col":0,"token":"{}"}}"#, join(&manager, 1)));
+
+        let restarted = GameManager::persistent(dir.clone()).unwrap();
+        assert!(call(&restarted, "getGame", r#"{"gameId":2}"#).contains(r#""board":[" X ","   ","   "],"toMove":"O""#));
+        let reply = call(&restarted, "applyMove", &format!(r#"{{"gameId":2,"row":0,"col":0,"token":"{}"}}"#, nought));
+        assert!(reply.contains(r#""toMove":"X""#), "{}", reply);
+        assert!(call(&restarted, "newGame", "{}").starts_with(r#"{"gameId":3,"#));
+
+        restarted.sweep(Instant::now() + ABANDONED_AFTER + Duration::from_secs(1));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        let archived = restarted.record(1).unwrap();
+        assert!(archived.contains(r#"[Result "X"]"#) && archived.contains(r#"[Termination "line"]"#), "{}", archived);
+        assert!(archived.contains(r#"[Date ""#) && !archived.contains("Token"), "{}", archived);
+        assert_eq!(call(&restarted, "getRecord", r#"{"gameId":1}"#), Json::object([("gameId", Json::from(1usize)), ("record", archived.as_str().into())]).to_string());
+        assert!(http_response(&restarted, "GET /games/1 HTTP/1.1").ends_with(&archived));
+        // Abandoned games are archived without a result.
+        let abandoned = restarted.record(2).unwrap();
+        assert!(abandoned.contains("[Result \"*\"]\n[Termination \"timeout\"]\n"), "{}", abandoned);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Moves on the last column of the widest board are saved in a record that reads back.
+        let manager = GameManager::persistent(dir.clone()).unwrap();
+        assert_eq!(call(&manager, "newGame", r#"{"size":27}"#), "Boards go up to 26x26.");
+        call(&manager, "newGame", r#"{"size":26}"#);
+        let cross = join(&manager, 1);
+        call(&manager, "applyMove", &format!(r#"{{"gameId":1,"row":25,"col":25,"token":"{}"}}"#, cross));
+        let restarted = GameManager::persistent(dir.clone()).unwrap();
+        let last_row = format!(r#"{}X"],"toMove":"O""#, " ".repeat(25));
+        assert!(call(&restarted, "getGame", r#"{"gameId":1}"#).contains(&last_row));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,276 @@
+use tick_tack_toe::board::{Board, Tile::{self, *}};
+use tick_tack_toe::coords;
+use tick_tack_toe::game::Game;
+use crate::player::Player;
+use crate::profile::Profiles;
+use tick_tack_toe::render::Names;
+use tick_tack_toe::variant::Variant;
+
+/// The perfect AI searches the whole game tree, which is only fast on small boards.
+pub const MAX_PERFECT_AI_SIZE: usize = 3;
+
+/// Columns are named by letters, so boards stop at z.
+pub const MAX_SIZE: usize = 26;
+
+pub struct Setup {
+    pub players: [Player; 2],
+    /// Profile names of the human players, `None` for guests and AIs.
+    pub names: [Option<String>; 2],
+    /// Names to show instead of the profile name or the kind of AI, from the command line.
+    pub display_names: [Option<String>; 2],
+    pub size: usize,
+    pub win_length: usize,
+    pub handicap: Option<Handicap>,
+    /// Blocked tiles on random empty tiles, after the handicap marks.
+    pub obstacles: usize,
+    pub variant: Variant,
+}
+impl Setup {
+    /// What each side is called in prompts and results: the name it was given, its profile name
+    /// or, for AIs, the kind.
+    pub fn shown_names(&self) -> Names {
+        Names([0, 1].map(|i| {
+            self.display_names[i].clone()
+                .or_else(|| self.names[i].clone())
+                .or_else(|| self.players[i].default_name())
+        }))
+    }
+
+    /// A game on an empty board, apart from the handicap marks and the obstacles.
+    pub fn new_game(&self) -> Game {
+        let mut board = Board::new(self.size, self.win_length);
+        if let Some(handicap) = &self.handicap {
+            handicap.place(&mut board);
+        }
+        for _ in 0..self.obstacles {
+            if let Some((row, col)) = board.random_move() {
+                let _ = board.set(Blocked, row, col);
+            }
+        }
+        Game::with_variant(board, self.variant)
+    }
+
+    /// Checks that the variant can be played, that the handicap marks fit on the board without
+    /// winning already, and that the obstacles leave tiles to play on.
+    pub fn validate(&self) -> Result<(), String> {
+        self.variant.check(self.size, self.win_length)?;
+        let marks = self.handicap.as_ref().map_or(0, Handicap::marks);
+        if self.obstacles + marks >= self.size * self.size {
+            return Err(format!("{} obstacles leave no tile to play on.", self.obstacles));
+        }
+        let Some(handicap) = &self.handicap else { return Ok(()) };
+
+        if handicap.marks() >= self.win_length.min(self.size * self.size) {
+            return Err(format!("A handicap needs fewer marks than the win length of {}.", self.win_length));
+        }
+        if let Handicap::Tiles(_, tiles) = handicap {
+            if tiles.iter().any(|&(row, col)| row >= self.size || col >= self.size) {
+                return Err("The handicap tiles must be on the board.".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Marks one side has on the board before the first move, to even out players of different
+/// strength.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Handicap {
+    /// This many marks on random tiles.
+    Random(Tile, usize),
+    Tiles(Tile, Vec<(usize, usize)>),
+}
+impl Handicap {
+    /// Reads `x`, `o:2` for two random marks or `o:a1,c3` for marks on those tiles.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        const USAGE: &str = "--handicap needs a side and the number or tiles of its marks, e.g. o:2 or o:a1,c3.";
+
+        let (side, marks) = value.split_once(':').unwrap_or((value, "1"));
+        let side = match side.to_lowercase().as_str() {
+            "x" => Cross,
+            "o" => Nought,
+            _ => return Err(USAGE.to_string()),
+        };
+
+        match marks.parse::<usize>() {
+            Ok(0) => Err(USAGE.to_string()),
+            Ok(count) => Ok(Self::Random(side, count)),
+            Err(_) => {
+                let mut tiles = marks.split(',')
+                    .map(|tile| coords::parse_notation(tile, MAX_SIZE))
+                    .collect::<Result<Vec<_>, _>>()?;
+                tiles.sort();
+                tiles.dedup();
+                Ok(Self::Tiles(side, tiles))
+            }
+        }
+    }
+
+    pub fn marks(&self) -> usize {
+        match self {
+            Self::Random(_, count) => *count,
+            Self::Tiles(_, tiles) => tiles.len(),
+        }
+    }
+
+    /// Puts the marks on `board`, random ones on empty tiles.
+    pub fn place(&self, board: &mut Board) {
+        match self {
+            Self::Random(side, count) => {
+                for _ in 0..*count {
+                    if let Some((row, col)) = board.random_move() {
+                        let _ = board.set(*side, row, col);
+                    }
+                }
+            }
+            Self::Tiles(side, tiles) => {
+                for &(row, col) in tiles {
+                    let _ = board.set(*side, row, col);
+                }
+            }
+        }
+    }
+}
+
+/// Choices already made on the command line, which `choose_setup` doesn't ask about.
+#[derive(Default, Debug, PartialEq)]
+pub struct Preset {
+    /// Player kinds as in `Player::from_kind`, in `[Cross, Nought]` order.
+    pub players: [Option<&'static str>; 2],
+    /// Display names, see `Setup::display_names`.
+    pub names: [Option<String>; 2],
+    pub size: Option<usize>,
+    pub win_length: Option<usize>,
+    pub handicap: Option<Handicap>,
+    pub obstacles: usize,
+    pub variant: Variant,
+}
+impl Preset {
+    /// The setup, when every choice has been made.
+    pub fn complete(&self) -> Option<Setup> {
+        let players = [Player::from_kind(self.players[0]?, Cross)?, Player::from_kind(self.players[1]?, Nought)?];
+        let (size, win_length) = (self.size?, self.win_length?);
+
+        let handicap = self.handicap.clone();
+        let display_names = self.names.clone();
+        (win_length <= size).then_some(Setup { players, names: [None, None], display_names, size, win_length, handicap, obstacles: self.obstacles, variant: self.variant })
+    }
+
+    /// The setup for games nobody plays in, which need two AIs. The board defaults to 3x3.
+    pub fn unattended(&self) -> Result<Setup, String> {
+        if self.players.iter().any(|kind| matches!(kind, None | Some("human"))) {
+            return Err("Unattended games need two AI players, choose them with --cross and --nought.".to_string());
+        }
+
+        let size = self.size.unwrap_or(3);
+        let preset = Self {
+            players: self.players,
+            names: self.names.clone(),
+            size: Some(size),
+            win_length: Some(self.win_length.unwrap_or(size.min(3))),
+            handicap: self.handicap.clone(),
+            obstacles: self.obstacles,
+            variant: self.variant,
+        };
+        let setup = preset.complete().ok_or("The win length can't be longer than the board size.".to_string())?;
+        setup.validate()?;
+        Ok(setup)
+    }
+}
+
+/// Asks for the player types, board size and win length not in `preset`, empty answers take
+/// the default.
+pub fn choose_setup(preset: &Preset) -> Setup {
+    if let Some(setup) = preset.complete() {
+        return setup;
+    }
+
+    let player = |kind: Option<&str>, tile, default| match kind.and_then(|kind| Player::from_kind(kind, tile)) {
+        Some(player) => (player, None),
+        None => choose_player(tile, default),
+    };
+    let (cross, cross_name)   = player(preset.players[0], Cross, 1);
+    let (nought, nought_name) = player(preset.players[1], Nought, 3);
+
+    let size = preset.size.unwrap_or_else(|| ask_number("Board size", 3, 1, MAX_SIZE));
+    let win_length = match preset.win_length {
+        Some(win_length) if win_length <= size => win_length,
+        _ => ask_number("Win length", 3.min(size), 1, size),
+    };
+
+    let players = [cross, nought];
+    let perfect_ai = players.iter().any(|p| matches!(p, Player::OptimalAi(_)));
+    if perfect_ai && size > MAX_PERFECT_AI_SIZE {
+        println!("Warning: the perfect AI can take a very long time on boards larger than {0}x{0}.", MAX_PERFECT_AI_SIZE);
+    }
+    println!();
+
+    let display_names = preset.names.clone();
+    Setup {
+        players,
+        names: [cross_name, nought_name],
+        display_names,
+        size,
+        win_length,
+        handicap: preset.handicap.clone(),
+        obstacles: preset.obstacles,
+        variant: preset.variant,
+    }
+}
+
+fn choose_player(tile: Tile, default: usize) -> (Player, Option<String>) {
+    let question = format!("Player {:?} (1: human, 2: random AI, 3: perfect AI)", tile);
+
+    match ask_number(&question, default, 1, 3) {
+        1 => (Player::Human(tile), ask_name(tile)),
+        2 => (Player::RandomAi(tile), None),
+        _ => (Player::OptimalAi(tile), None),
+    }
+}
+
+pub fn ask_name(tile: Tile) -> Option<String> {
+    loop {
+        let name = ask(&format!("Name for {:?}, to keep statistics (empty for guest)", tile))?;
+        if name.is_empty() {
+            return None;
+        }
+        if name.contains('\t') {
+            println!("Names can't contain tabs.");
+            continue;
+        }
+
+        match Profiles::load().map(|profiles| profiles.get(&name).cloned()) {
+            Ok(Some(profile)) => println!("Welcome back, {}.", profile),
+            Ok(None) => println!("Registered new player {}.", name),
+            Err(err) => println!("Couldn't read profiles: {}", err),
+        }
+        return Some(name);
+    }
+}
+
+fn ask_number(question: &str, default: usize, min: usize, max: usize) -> usize {
+    loop {
+        let Some(answer) = ask(&format!("{} [{}]", question, default)) else {
+            return default;
+        };
+        if answer.is_empty() {
+            return default;
+        }
+
+        match answer.parse() {
+            Ok(n) if (min..=max).contains(&n) => return n,
+            _ => println!("Answer with a number from {} to {}.", min, max),
+        }
+    }
+}
+
+/// Prints the question and reads a trimmed answer, `None` when input has ended.
+pub fn ask(question: &str) -> Option<String> {
+    println!("{}: ", question);
+
+    let mut buf = String::new();
+    match std::io::stdin().read_line(&mut buf) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(buf.trim().to_string()),
+    }
+}
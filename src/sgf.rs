@@ -0,0 +1,245 @@
+//! Game records in the Smart Game Format (SGF), as used for Go and Gomoku, so games open in
+//! existing SGF viewers. Cross plays black and nought white, tiles are named by a letter for the
+//! column and one for the row, top left `aa`:
+//!
+//! ```text
+//! (;FF[4]GM[4]SZ[3]WL[3];B[bb];W[aa]C[The corners lose.];B[cc])
+//! ```
+//!
+//! `WL`, the win length, isn't a standard property; files without it are taken to be Gomoku and
+//! need five in a row.
+
+use crate::board::{Board, Tile};
+use crate::game::Move;
+use crate::record::{Annotation, Record, Termination};
+use crate::{Error, Result};
+
+/// The record tags written as SGF properties, the rest are left out.
+const TAGS: [(&str, &str); 2] = [("CrossName", "PB"), ("NoughtName", "PW")];
+
+/// SGF move properties for the glyphs of `record::GLYPHS`.
+const GLYPHS: [(&str, &str, &str); 6] = [
+    ("!!", "TE", "2"),
+    ("!",  "TE", "1"),
+    ("!?", "IT", ""),
+    ("?!", "DO", ""),
+    ("?",  "BM", "1"),
+    ("??", "BM", "2"),
+];
+
+const INVALID_SGF: Error = Error::InvalidNotation("SGF games look like (;SZ[3];B[bb];W[aa]).");
+
+type Node = Vec<(String, Vec<String>)>;
+
+pub fn write(record: &Record) -> String {
+    let size = record.start.length();
+    let mut out = format!("(;FF[4]GM[4]CA[UTF-8]AP[tick-tack-toe]SZ[{}]WL[{}]", size, record.start.win_row_length());
+
+    for (tag, property) in TAGS {
+        if let Some(value) = record.tag(tag) {
+            out.push_str(&format!("{}[{}]", property, escape(value)));
+        }
+    }
+    if let Some(date) = record.tag("Date") {
+        out.push_str(&format!("DT[{}]", escape(&date.replace('.', "-"))));
+    }
+    // SGF adds how a game was won after the winner, e.g. B+R for a resignation.
+    let how = match record.termination() {
+        Some(Termination::Resignation) => "R",
+        Some(Termination::Timeout)     => "T",
+        _ => "",
+    };
+    match record.tag("Result") {
+        Some("X")    => out.push_str(&format!("RE[B+{}]", how)),
+        Some("O")    => out.push_str(&format!("RE[W+{}]", how)),
+        Some("draw") => out.push_str("RE[0]"),
+        _ => (),
+    }
+    for (tile, property) in [(Tile::Cross, "AB"), (Tile::Nought, "AW")] {
+        let stones: Vec<String> = (0..size * size)
+            .map(|i| (i / size, i % size))
+            .filter(|&(row, col)| record.start.get(row, col) == Some(tile))
+            .map(|(row, col)| format!("[{}]", point(row, col)))
+            .collect();
+        if !stones.is_empty() {
+            out.push_str(&format!("{}{}", property, stones.concat()));
+        }
+    }
+
+    for (i, m) in record.moves.iter().enumerate() {
+        let color = if m.tile == Tile::Cross { "B" } else { "W" };
+        out.push_str(&format!("\n;{}[{}]", color, point(m.row, m.col)));
+
+        let annotation = record.annotations.get(i).cloned().unwrap_or_default();
+        if let Some((_, property, value)) = GLYPHS.iter().find(|(glyph, ..)| Some(*glyph) == annotation.glyph) {
+            out.push_str(&format!("{}[{}]", property, value));
+        }
+        if let Some(comment) = &annotation.comment {
+            out.push_str(&format!("C[{}]", escape(comment)));
+        }
+    }
+    out.push_str(")\n");
+    out
+}
+
+/// Reads the main line of the first game in `text`, variations are skipped.
+pub fn parse(text: &str) -> Result<Record> {
+    let nodes = main_line(text)?;
+    let root = nodes.first().ok_or(INVALID_SGF)?;
+    let value = |node: &Node, name: &str| {
+        node.iter().find(|(n, _)| n == name).and_then(|(_, values)| values.first().cloned())
+    };
+    let number = |name| value(root, name).map(|v| v.trim().parse::<usize>().map_err(|_| INVALID_SGF)).transpose();
+
+    let size = number("SZ")?.ok_or(Error::InvalidNotation("An SGF game needs its board size, SZ."))?;
+    let win_length = number("WL")?.unwrap_or(size.min(5));
+    if size == 0 || size > 26 || win_length == 0 || win_length > size {
+        return Err(Error::InvalidNotation("The SGF board size or win length doesn't fit."));
+    }
+
+    let mut start = Board::new(size, win_length);
+    for (property, tile) in [("AB", Tile::Cross), ("AW", Tile::Nought)] {
+        for (_, values) in root.iter().filter(|(n, _)| n == property) {
+            for v in values {
+                let (row, col) = parse_point(v, size)?;
+                start.set(tile, row, col)?;
+            }
+        }
+    }
+
+    let mut record = Record { tags: Vec::new(), start, moves: Vec::new(), annotations: Vec::new() };
+    for (tag, property) in TAGS {
+        if let Some(name) = value(root, property) {
+            record.set_tag(tag, name);
+        }
+    }
+    if let Some(date) = value(root, "DT") {
+        record.set_tag("Date", date.replace('-', "."));
+    }
+    let re = value(root, "RE").unwrap_or_default();
+    match re.chars().next() {
+        Some('B') => record.set_tag("Result", "X"),
+        Some('W') => record.set_tag("Result", "O"),
+        Some('0') | Some('D') => record.set_tag("Result", "draw"),
+        _ => (),
+    }
+    match re.get(1..) {
+        Some("+R") | Some("+Resign") => record.set_tag("Termination", Termination::Resignation.name()),
+        Some("+T") | Some("+Time")   => record.set_tag("Termination", Termination::Timeout.name()),
+        _ => (),
+    }
+
+    for node in &nodes {
+        let Some((tile, point)) = value(node, "B").map(|p| (Tile::Cross, p)).or_else(|| value(node, "W").map(|p| (Tile::Nought, p))) else {
+            continue;
+        };
+        let (row, col) = parse_point(&point, size)?;
+        record.moves.push(Move { tile, row, col, from: None });
+
+        let glyph = GLYPHS.iter()
+            .find(|(_, property, number)| value(node, property).is_some_and(|v| number.is_empty() || v == *number))
+            .map(|(glyph, ..)| *glyph);
+        record.annotations.push(Annotation { glyph, comment: value(node, "C"), time: None });
+    }
+    Ok(record)
+}
+
+fn point(row: usize, col: usize) -> String {
+    [col, row].iter().map(|&i| (b'a' + i as u8) as char).collect()
+}
+
+fn parse_point(point: &str, size: usize) -> Result<(usize, usize)> {
+    let index = |c: u8| c.checked_sub(b'a').map(usize::from).filter(|&i| i < size);
+
+    match point.as_bytes() {
+        &[col, row] => Ok((index(row).ok_or(INVALID_SGF)?, index(col).ok_or(INVALID_SGF)?)),
+        _ => Err(Error::InvalidNotation("SGF moves name a tile by two letters, e.g. B[bb].")),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(']', "\\]")
+}
+
+/// The nodes up to the end of the first variation, each a list of properties with their values.
+fn main_line(text: &str) -> Result<Vec<Node>> {
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut chars = text.trim_start().chars().peekable();
+    if chars.next() != Some('(') {
+        return Err(INVALID_SGF);
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => nodes.push(Vec::new()),
+            // A new variation goes on the main line, the end of one is the end of it.
+            '(' => (),
+            ')' => return Ok(nodes),
+            c if c.is_ascii_uppercase() => {
+                let mut name = c.to_string();
+                // Old versions of the format allowed lowercase letters in between, e.g. `AddBlack`.
+                while let Some(c) = chars.next_if(char::is_ascii_alphabetic) {
+                    if c.is_ascii_uppercase() {
+                        name.push(c);
+                    }
+                }
+
+                let mut values = Vec::new();
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+                while chars.next_if_eq(&'[').is_some() {
+                    let mut value = String::new();
+                    loop {
+                        match chars.next().ok_or(INVALID_SGF)? {
+                            '\\' => value.push(chars.next().ok_or(INVALID_SGF)?),
+                            ']' => break,
+                            c => value.push(c),
+                        }
+                    }
+                    values.push(value);
+                    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                        chars.next();
+                    }
+                }
+                nodes.last_mut().ok_or(INVALID_SGF)?.push((name, values));
+            }
+            c if c.is_whitespace() => (),
+            _ => return Err(INVALID_SGF),
+        }
+    }
+    Err(INVALID_SGF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, write};
+    use crate::board::Tile::*;
+    use crate::record::Record;
+
+    #[test]
+    fn write_and_parse() {
+        let text = "[Size \"3\"]\n[Position \"O../.../...\"]\n[CrossName \"Alice\"]\n[Result \"X\"]\n1. Xb2! Oa3? {Too late.} 2. Xc1\n";
+        let record = Record::parse(text).unwrap();
+
+        let sgf = write(&record);
+        assert_eq!(sgf, "(;FF[4]GM[4]CA[UTF-8]AP[tick-tack-toe]SZ[3]WL[3]PB[Alice]RE[B+]AW[aa]\n;B[bb]TE[1]\n;W[ac]BM[1]C[Too late.]\n;B[ca])\n");
+        assert_eq!(parse(&sgf), Ok(record));
+
+        let text = "[Size \"3\"]\n[Date \"2026.10.14\"]\n[Result \"O\"]\n[Termination \"resignation\"]\n1. Xb2\n";
+        let record = Record::parse(text).unwrap();
+        let sgf = write(&record);
+        assert!(sgf.contains("DT[2026-10-14]RE[W+R]"), "{}", sgf);
+        assert_eq!(parse(&sgf), Ok(record));
+
+        // A Gomoku game from elsewhere, with a variation and an escaped comment.
+        let gomoku = parse("(;GM[4]SZ[15] ;B[hh];W[hi] C[a \\] b] (;B[ii])(;B[jj]))").unwrap();
+        assert_eq!((gomoku.start.length(), gomoku.start.win_row_length()), (15, 5));
+        assert_eq!(gomoku.moves.iter().map(|m| (m.tile, m.row, m.col)).collect::<Vec<_>>(), [(Cross, 7, 7), (Nought, 8, 7), (Cross, 8, 8)]);
+        assert_eq!(gomoku.annotations[1].comment.as_deref(), Some("a ] b"));
+
+        assert!(parse("(;B[bb])").is_err());
+        assert!(parse("(;SZ[3];B[dd])").is_err());
+        assert!(parse("(;SZ[3];B[bb]").is_err());
+    }
+}
@@ -0,0 +1,238 @@
+//! Simultaneous exhibitions: one human plays a board against each of several AIs at once,
+//! moving on one board and going on to the next while that AI answers, as in a chess simul.
+//! All the boards are drawn side by side, the one to move on marked, and any of them can be
+//! picked to move on next.
+
+use crate::commands::{self, Action};
+use crate::player::{Player, Turn};
+use crate::screen;
+use std::io::{self, Write};
+use tick_tack_toe::board::{Board, BoardStatus, Tile};
+use tick_tack_toe::coords;
+use tick_tack_toe::game::Game;
+use tick_tack_toe::render;
+use tick_tack_toe::score::Score;
+
+/// Boards are laid out side by side up to this many columns, unless `COLUMNS` says otherwise.
+const DEFAULT_COLUMNS: usize = 80;
+/// Columns between two boards side by side.
+const GAP: usize = 4;
+
+/// One board of the exhibition.
+struct Table {
+    opponent: Player,
+    game: Game,
+    /// `Some` once the game is over, a resigned one included.
+    result: Option<BoardStatus>,
+}
+
+/// What the human typed at the prompt, besides a move.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Selection {
+    Next,
+    Previous,
+    /// The board of this index.
+    Board(usize),
+}
+
+/// Plays a simul of `side` against one board of `size` and `win_length` per AI kind in
+/// `opponents`, until every board is finished or the human quits. Returns the human's score.
+pub fn play(opponents: &[&'static str], side: Tile, size: usize, win_length: usize) -> Result<Score, String> {
+    let other = side.opposite().ok_or("The simul needs a side to play.")?;
+    let mut tables = opponents.iter()
+        .map(|kind| {
+            let opponent = Player::from_kind(kind, other).ok_or(format!("'{}' isn't an AI.", kind))?;
+            Ok(Table { opponent, game: Game::new(Board::new(size, win_length)), result: None })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    // The human moves first as Cross, the AIs open every board otherwise.
+    if side == Tile::Nought {
+        for table in &mut tables {
+            reply(table)?;
+        }
+    }
+
+    let mut selected = 0;
+    let mut message = String::new();
+    while let Some(at) = next_board(&results(&tables), selected, 0) {
+        selected = at;
+        draw(&tables, selected, side, &message);
+        message.clear();
+        println!("Board {}: {:?} to move, or n, p or #N for the next, the previous or board N:", selected + 1, side);
+        let _ = io::stdout().flush();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => (),
+        }
+        if let Some(selection) = parse_selection(&input, tables.len()) {
+            let statuses = results(&tables);
+            let found = match selection {
+                Selection::Next     => next_board(&statuses, selected, 1),
+                Selection::Previous => next_board(&statuses, selected, tables.len() - 1),
+                Selection::Board(i) => statuses[i].is_none().then_some(i),
+            };
+            match found {
+                Some(at) => selected = at,
+                None => message = "That board is finished.".to_string(),
+            }
+            continue;
+        }
+
+        let table = &mut tables[selected];
+        if let Some(command) = commands::find(&input) {
+            match (command.run)(&mut table.game, side) {
+                Action::Prompt => (),
+                Action::Resign => table.result = Some(BoardStatus::Winner(other)),
+                Action::Quit   => break,
+            }
+            continue;
+        }
+        let played = coords::parse(&input, size)
+            .and_then(|(row, col)| table.game.apply(side, row, col).map_err(|err| err.to_string()));
+        if let Err(err) = played {
+            message = err;
+            continue;
+        }
+        table.result = finished(&table.game);
+        if table.result.is_none() {
+            reply(table)?;
+        }
+        // On to the next board while this one's AI is thought to be thinking.
+        selected = next_board(&results(&tables), selected, 1).unwrap_or(selected);
+    }
+
+    draw(&tables, selected, side, "");
+    let mut score = Score::default();
+    for result in results(&tables).into_iter().flatten() {
+        score.record(result);
+    }
+    Ok(score)
+}
+
+/// The AI of `table` answers, and the game ends if that was its last move.
+fn reply(table: &mut Table) -> Result<(), String> {
+    match table.opponent.take_turn(&mut table.game).map_err(|err| err.to_string())? {
+        Turn::Resigned => table.result = Some(BoardStatus::Winner(table.opponent.tile().opposite().unwrap_or(Tile::Empty))),
+        _ => table.result = finished(&table.game),
+    }
+    Ok(())
+}
+
+fn finished(game: &Game) -> Option<BoardStatus> {
+    Some(game.status()).filter(|status| *status != BoardStatus::Continue)
+}
+
+fn results(tables: &[Table]) -> Vec<Option<BoardStatus>> {
+    tables.iter().map(|table| table.result).collect()
+}
+
+/// The first unfinished board from `skip` boards after `from` on, going round to the first
+/// board after the last.
+fn next_board(results: &[Option<BoardStatus>], from: usize, skip: usize) -> Option<usize> {
+    let count = results.len();
+    (0..count).map(|i| (from + skip + i) % count).find(|&i| results[i].is_none())
+}
+
+/// `n` or `next`, `p` or `prev`, or `#2` and `board 2` for the second of `count` boards.
+fn parse_selection(input: &str, count: usize) -> Option<Selection> {
+    let input = input.trim().to_lowercase();
+    let number = match input.as_str() {
+        "n" | "next" => return Some(Selection::Next),
+        "p" | "prev" => return Some(Selection::Previous),
+        _ => input.strip_prefix('#').or_else(|| input.strip_prefix("board "))?,
+    };
+    match number.trim().parse::<usize>() {
+        Ok(n @ 1..) if n <= count => Some(Selection::Board(n - 1)),
+        _ => None,
+    }
+}
+
+/// Every board under a line with its number, its opponent and how it stands, the selected one
+/// marked, as many side by side as fit across the terminal.
+fn draw(tables: &[Table], selected: usize, side: Tile, message: &str) {
+    let theme = screen::theme();
+    let blocks: Vec<Vec<String>> = tables.iter().enumerate().map(|(i, table)| {
+        let state = match table.result {
+            Some(BoardStatus::Winner(winner)) if winner == side => "won".to_string(),
+            Some(BoardStatus::Winner(_)) => "lost".to_string(),
+            Some(_) => "drawn".to_string(),
+            None => format!("move {}", table.game.history().len() + 1),
+        };
+        let marker = if i == selected { '>' } else { ' ' };
+        let mut lines = vec![format!("{}{} {}, {}", marker, i + 1, table.opponent.kind(), state)];
+        let last = table.game.history().last().map(|m| (m.row, m.col));
+        lines.extend(table.game.board().render_highlighted(&theme, &last.into_iter().collect::<Vec<_>>()).lines().map(str::to_string));
+        lines
+    }).collect();
+
+    let columns = std::env::var("COLUMNS").ok().and_then(|columns| columns.parse().ok()).unwrap_or(DEFAULT_COLUMNS);
+    if !screen::headless() {
+        print!("{}", side_by_side(&blocks, columns));
+        if !message.is_empty() {
+            println!("{}", message);
+        }
+    }
+}
+
+/// The `blocks` of lines next to each other, in rows of as many as fit in `columns`.
+fn side_by_side(blocks: &[Vec<String>], columns: usize) -> String {
+    let width = blocks.iter().flatten().map(|line| visible_width(line)).max().unwrap_or(0);
+    let per_row = ((columns + GAP) / (width + GAP)).max(1);
+    let mut out = String::new();
+    for row in blocks.chunks(per_row) {
+        let height = row.iter().map(Vec::len).max().unwrap_or(0);
+        for line in 0..height {
+            let mut text = String::new();
+            for block in row {
+                let part = block.get(line).map_or("", String::as_str);
+                text.push_str(part);
+                text.push_str(&" ".repeat(width - visible_width(part) + GAP));
+            }
+            out.push_str(text.trim_end());
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// The columns `line` takes up on the terminal, without its color escapes.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut escape = false;
+    for c in line.chars() {
+        match (escape, c) {
+            (false, '\x1b') => escape = true,
+            (true, 'm') => escape = false,
+            (true, _) => (),
+            (false, c) => width += render::width(c),
+        }
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_board, parse_selection, side_by_side, Selection};
+    use tick_tack_toe::board::{BoardStatus, Tile::*};
+
+    #[test]
+    fn boards() {
+        let results = [None, Some(BoardStatus::Tie), None, Some(BoardStatus::Winner(Cross))];
+        assert_eq!(next_board(&results, 0, 1), Some(2));
+        assert_eq!(next_board(&results, 2, 1), Some(0));
+        assert_eq!(next_board(&results, 1, 0), Some(2));
+        assert_eq!(next_board(&[Some(BoardStatus::Tie)], 0, 1), None);
+
+        assert_eq!(parse_selection("n\n", 4), Some(Selection::Next));
+        assert_eq!(parse_selection("#3", 4), Some(Selection::Board(2)));
+        assert_eq!(parse_selection("Board 4", 4), Some(Selection::Board(3)));
+        assert_eq!(parse_selection("#5", 4), None);
+        assert_eq!(parse_selection("1 2", 4), None);
+
+        let blocks = [vec!["ab".to_string(), "\x1b[7mX\x1b[0m".to_string()], vec!["cd".to_string()], vec!["ef".to_string()]];
+        assert_eq!(side_by_side(&blocks, 8), "ab    cd\n\x1b[7mX\x1b[0m\n\nef\n\n");
+    }
+}
@@ -0,0 +1,194 @@
+//! Batch play between two AIs, with the results written out for analysis.
+
+use crate::player::{Player, Turn};
+use crate::setup::Setup;
+use std::{fs, io, path::Path, time::Duration, time::Instant};
+use tick_tack_toe::board::{BoardStatus, Tile};
+use tick_tack_toe::json::Json;
+use tick_tack_toe::score::{Score, SplitScore};
+use tick_tack_toe::{Error, Result};
+
+/// One simulated game.
+pub struct GameResult {
+    pub first: Tile,
+    pub status: BoardStatus,
+    pub moves: usize,
+    pub duration: Duration,
+}
+
+/// Plays `games` games without output, alternating who moves first.
+pub fn run(setup: &Setup, games: u32) -> Result<Vec<GameResult>> {
+    (0..games as usize).map(|i| play(setup, i % 2)).collect()
+}
+
+fn play(setup: &Setup, first: usize) -> Result<GameResult> {
+    let started = Instant::now();
+    let mut game = setup.new_game();
+    let mut turn = first;
+
+    let mut status = game.status();
+    while status == BoardStatus::Continue {
+        match setup.players[turn].take_turn(&mut game)? {
+            Turn::Moved => {
+                turn = 1 - turn;
+                status = game.status();
+            }
+            // Only plugins resign, and lose the game by it.
+            Turn::Resigned => status = BoardStatus::Winner(setup.players[1 - turn].tile()),
+            Turn::Quit => return Err(Error::GameOver),
+        }
+    }
+
+    Ok(GameResult {
+        first: setup.players[first].tile(),
+        status,
+        moves: game.history().len(),
+        duration: started.elapsed(),
+    })
+}
+
+/// The results by the side that moved first.
+pub fn split(results: &[GameResult]) -> SplitScore {
+    let mut split = SplitScore::default();
+    for r in results {
+        split.record(r.first, r.status);
+    }
+    split
+}
+
+fn result_name(status: BoardStatus) -> &'static str {
+    match status {
+        BoardStatus::Winner(tile) => tile.char(),
+        BoardStatus::Tie | BoardStatus::Continue => "draw",
+    }
+}
+
+/// The win rates with their confidence intervals, and whether the difference between the players
+/// is more than noise. The games are also counted by who moved first, as moving first is worth more
+/// than most differences between AIs.
+pub fn report(players: &[Player; 2], split: &SplitScore) -> String {
+    let score = split.total();
+    let mut out = String::new();
+    for (player, side) in players.iter().zip([Tile::Cross, Tile::Nought]) {
+        let (low, high) = score.win_rate_interval(side);
+        let rate = score.wins(side) as f64 / score.games().max(1) as f64;
+        out.push_str(&format!("{:?} ({}) wins {:.1}%, 95% confidence interval {:.1}% to {:.1}%\n",
+            side, player.kind(), rate * 100.0, low * 100.0, high * 100.0));
+    }
+    for side in [Tile::Cross, Tile::Nought] {
+        let first = split.first(side);
+        let percent = |n: u32| n as f64 * 100.0 / first.games().max(1) as f64;
+        out.push_str(&format!("With {:?} moving first ({} game{}): Cross wins {:.1}%, Nought wins {:.1}%, {:.1}% drawn\n",
+            side, first.games(), if first.games() == 1 { "" } else { "s" }, percent(first.cross_wins), percent(first.nought_wins), percent(first.draws)));
+    }
+
+    let p = score.p_value();
+    match score.leader() {
+        Some(side) if p < 0.05 => out.push_str(&format!("{:?} is significantly stronger (p = {:.3}).", side, p)),
+        _ => out.push_str(&format!("The difference could be chance (p = {:.3}), play more games to tell.", p)),
+    }
+    out
+}
+
+/// One row per game, with a header.
+pub fn to_csv(results: &[GameResult]) -> String {
+    let mut out = String::from("game,first,result,moves,millis\n");
+    for (i, r) in results.iter().enumerate() {
+        out.push_str(&format!("{},{},{},{},{}\n",
+            i + 1, r.first.char(), result_name(r.status), r.moves, r.duration.as_millis()));
+    }
+    out
+}
+
+/// The aggregate statistics as a header and one row.
+pub fn summary_csv(players: &[Player; 2], split: &SplitScore) -> String {
+    let score = split.total();
+    let (cross_first, nought_first) = (split.cross_first, split.nought_first);
+    let (cross_low, cross_high) = score.win_rate_interval(Tile::Cross);
+    let (nought_low, nought_high) = score.win_rate_interval(Tile::Nought);
+
+    format!("cross,nought,games,cross_wins,nought_wins,draws,cross_low,cross_high,nought_low,nought_high,p_value,\
+        cross_first_cross_wins,cross_first_nought_wins,cross_first_draws,nought_first_cross_wins,nought_first_nought_wins,nought_first_draws\n\
+        {},{},{},{},{},{},{:.4},{:.4},{:.4},{:.4},{:.4},{},{},{},{},{},{}\n",
+        players[0].kind(), players[1].kind(), score.games(), score.cross_wins, score.nought_wins, score.draws,
+        cross_low, cross_high, nought_low, nought_high, score.p_value(),
+        cross_first.cross_wins, cross_first.nought_wins, cross_first.draws, nought_first.cross_wins, nought_first.nought_wins, nought_first.draws)
+}
+
+/// The games and the aggregate statistics, as `{"games": [...], "summary": {...}}`.
+pub fn to_json(players: &[Player; 2], results: &[GameResult]) -> Json {
+    let games = results.iter().enumerate().map(|(i, r)| {
+        Json::object([
+            ("game", Json::from(i + 1)),
+            ("first", r.first.char().into()),
+            ("result", result_name(r.status).into()),
+            ("moves", r.moves.into()),
+            ("millis", Json::from(r.duration.as_millis() as usize)),
+        ])
+    });
+
+    let split = split(results);
+    let score = split.total();
+    let counts = |score: &Score| Json::object([
+        ("crossWins", Json::from(score.cross_wins as usize)),
+        ("noughtWins", Json::from(score.nought_wins as usize)),
+        ("draws", Json::from(score.draws as usize)),
+    ]);
+    let interval = |side| {
+        let (low, high) = score.win_rate_interval(side);
+        Json::Array(vec![Json::Number(low), Json::Number(high)])
+    };
+    let summary = Json::object([
+        ("cross", players[0].kind().into()),
+        ("nought", players[1].kind().into()),
+        ("games", Json::from(score.games() as usize)),
+        ("crossWins", Json::from(score.cross_wins as usize)),
+        ("noughtWins", Json::from(score.nought_wins as usize)),
+        ("draws", Json::from(score.draws as usize)),
+        ("crossWinInterval", interval(Tile::Cross)),
+        ("noughtWinInterval", interval(Tile::Nought)),
+        ("pValue", Json::Number(score.p_value())),
+        ("crossFirst", counts(&split.cross_first)),
+        ("noughtFirst", counts(&split.nought_first)),
+    ]);
+
+    Json::object([("games", Json::Array(games.collect())), ("summary", summary)])
+}
+
+/// Writes the results as JSON when `path` ends in `.json`, otherwise as CSV, with the summary
+/// next to it in `<name>.summary.csv`.
+pub fn write(path: &Path, players: &[Player; 2], results: &[GameResult]) -> io::Result<()> {
+    if path.extension().is_some_and(|ext| ext == "json") {
+        return fs::write(path, to_json(players, results).to_string() + "\n");
+    }
+
+    fs::write(path, to_csv(results))?;
+    fs::write(path.with_extension("summary.csv"), summary_csv(players, &split(results)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{report, split, to_csv, to_json, GameResult};
+    use crate::player::Player;
+    use std::time::Duration;
+    use tick_tack_toe::board::{BoardStatus::*, Tile::*};
+
+    #[test]
+    fn export() {
+        let results = [
+            GameResult { first: Cross, status: Winner(Cross), moves: 5, duration: Duration::from_millis(3) },
+            GameResult { first: Nought, status: Tie, moves: 9, duration: Duration::from_millis(12) },
+        ];
+        assert_eq!(to_csv(&results), "game,first,result,moves,millis\n1,X,X,5,3\n2,O,draw,9,12\n");
+
+        let players = [Player::RandomAi(Cross), Player::OptimalAi(Nought)];
+        assert!(report(&players, &split(&results)).contains("With Cross moving first (1 game): Cross wins 100.0%"));
+        let json = to_json(&players, &results);
+        let summary = json.get("summary").unwrap();
+        assert_eq!(summary.get("crossWins").and_then(|n| n.as_usize()), Some(1));
+        assert_eq!(summary.get("draws").and_then(|n| n.as_usize()), Some(1));
+        assert_eq!(json.get("games").and_then(|g| g.as_array()).map(|g| g.len()), Some(2));
+        let nought_first = summary.get("noughtFirst").unwrap();
+        assert_eq!((nought_first.get("crossWins").and_then(|n| n.as_usize()), nought_first.get("draws").and_then(|n| n.as_usize())), (Some(0), Some(1)));
+    }
+}
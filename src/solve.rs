@@ -0,0 +1,261 @@
+//! Solving positions exactly: the result with best play, how many moves it takes and a proof,
+//! found by an alpha-beta search that remembers the positions it has seen.
+
+use crate::board::{Board, BoardStatus, Tile};
+use crate::game::Move;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The score of a position whose side to move has lost, a win in `n` moves scores `WIN - n`.
+const WIN: i32 = 1000;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// The value of a position for the side to move.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Value {
+    pub outcome: Outcome,
+    /// Moves until the game ends when the winner hurries and the loser holds out.
+    pub distance: usize,
+}
+impl Value {
+    fn of(score: i32) -> Self {
+        let outcome = match score {
+            0 => Outcome::Draw,
+            _ if score > 0 => Outcome::Win,
+            _ => Outcome::Loss,
+        };
+        let distance = match outcome {
+            Outcome::Draw => 0,
+            _ => (WIN - score.abs()) as usize,
+        };
+        Self { outcome, distance }
+    }
+}
+
+/// E.g. `win in 3 moves`, `draw` or `loss in 1 move`.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let outcome = match self.outcome {
+            Outcome::Win  => "win",
+            Outcome::Loss => "loss",
+            Outcome::Draw => return write!(f, "draw"),
+        };
+        write!(f, "{} in {} move{}", outcome, self.distance, if self.distance == 1 { "" } else { "s" })
+    }
+}
+
+/// How a remembered score relates to the real one, as searches with a window stop early.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Bound {
+    Exact,
+    /// At least the score.
+    Lower,
+    /// At most the score.
+    Upper,
+}
+
+/// Remembers every position it has searched, so solving many positions of the same game shares
+/// the work.
+#[derive(Default)]
+pub struct Solver {
+    /// By the notation of the position and the side to move.
+    table: HashMap<String, (i32, Bound)>,
+    /// Positions searched so far.
+    pub positions: u64,
+}
+impl Solver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The value of `board` for `side` to move.
+    pub fn value(&mut self, board: &Board, side: Tile) -> Value {
+        Value::of(self.score(&mut board.clone(), side))
+    }
+
+    /// Best play from `board` to the end of the game, the winner taking the quickest win and the
+    /// loser the longest defence.
+    pub fn principal_variation(&mut self, board: &Board, side: Tile) -> Vec<Move> {
+        let mut board = board.clone();
+        let mut to_move = side;
+        let mut line = Vec::new();
+        while let Some(m) = self.best_move(&mut board, to_move) {
+            let _ = board.play(&m);
+            line.push(m);
+            to_move = to_move.opposite().unwrap_or(to_move);
+        }
+        line
+    }
+
+    /// Why the value of `board` holds: every reply of the losing side, or of the side that can't
+    /// do better than a draw, each answered by a move that keeps the value.
+    pub fn proof(&mut self, board: &Board, side: Tile) -> Proof {
+        let mut board = board.clone();
+        let score = self.score(&mut board, side);
+        let prover = match score < 0 {
+            true  => side.opposite().unwrap_or(side),
+            false => side,
+        };
+        Proof { children: self.prove(&mut board, side, prover) }
+    }
+
+    fn prove(&mut self, board: &mut Board, side: Tile, prover: Tile) -> Vec<(Move, Proof)> {
+        let Some(other) = side.opposite() else { return Vec::new() };
+        let moves: Vec<Move> = match side == prover {
+            true  => self.best_move(board, side).into_iter().collect(),
+            false if board.board_status() == BoardStatus::Continue => {
+                board.empty_tiles().into_iter().map(|(row, col)| Move { tile: side, row, col, from: None }).collect()
+            }
+            false => Vec::new(),
+        };
+
+        moves.into_iter().map(|m| {
+            let _ = board.play(&m);
+            let children = self.prove(board, other, prover);
+            board.clear(m.row, m.col);
+            (m, Proof { children })
+        }).collect()
+    }
+
+    fn best_move(&mut self, board: &mut Board, side: Tile) -> Option<Move> {
+        let other = side.opposite()?;
+        if board.board_status() != BoardStatus::Continue {
+            return None;
+        }
+
+        let mut best: Option<(i32, Move)> = None;
+        for (row, col) in board.empty_tiles() {
+            let _ = board.set(side, row, col);
+            let score = parent_score(self.score(board, other));
+            board.clear(row, col);
+            if best.is_none_or(|(best, _)| score > best) {
+                best = Some((score, Move { tile: side, row, col, from: None }));
+            }
+        }
+        best.map(|(_, m)| m)
+    }
+
+    fn score(&mut self, board: &mut Board, side: Tile) -> i32 {
+        self.search(board, side, -WIN - 1, WIN + 1)
+    }
+
+    /// Negamax with alpha-beta pruning: the score for `side` to move when it is between `alpha`
+    /// and `beta`, otherwise a bound on the side of the window it's on.
+    fn search(&mut self, board: &mut Board, side: Tile, mut alpha: i32, beta: i32) -> i32 {
+        self.positions += 1;
+        match board.board_status() {
+            // The last move won.
+            BoardStatus::Winner(_) => return -WIN,
+            BoardStatus::Tie => return 0,
+            BoardStatus::Continue => (),
+        }
+        let Some(other) = side.opposite() else { return 0 };
+
+        let key = format!("{} {}", board.notation(), side.char());
+        if let Some(&(score, bound)) = self.table.get(&key) {
+            match bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => (),
+            }
+        }
+
+        let start_alpha = alpha;
+        let mut best = -WIN - 1;
+        for (row, col) in board.empty_tiles() {
+            let _ = board.set(side, row, col);
+            let score = parent_score(self.search(board, other, child_bound(-beta), child_bound(-alpha)));
+            board.clear(row, col);
+
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = match best {
+            _ if best <= start_alpha => Bound::Upper,
+            _ if best >= beta => Bound::Lower,
+            _ => Bound::Exact,
+        };
+        self.table.insert(key, (best, bound));
+        best
+    }
+}
+
+/// The score of a position for the side that moved into one scored `score` for the other side,
+/// a move further from the end.
+fn parent_score(score: i32) -> i32 {
+    -(score - score.signum())
+}
+
+/// The bound for the position after a move that `parent_score` turns into the bound `bound`.
+fn child_bound(bound: i32) -> i32 {
+    bound + bound.signum()
+}
+
+/// The moves that show a value holds, each followed by the proof of the position it leads to.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Proof {
+    pub children: Vec<(Move, Proof)>,
+}
+
+/// One line per move of the proving side, with every reply to it indented below.
+impl fmt::Display for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn lines(proof: &Proof, indent: usize, prefix: String, f: &mut fmt::Formatter) -> fmt::Result {
+            for (m, next) in &proof.children {
+                let line = format!("{}{}", prefix, m.notation());
+                match next.children.len() {
+                    // A move of the proving side follows the reply on the same line.
+                    1 => lines(next, indent, line + " ", f)?,
+                    0 => writeln!(f, "{}{}", "  ".repeat(indent), line)?,
+                    _ => {
+                        writeln!(f, "{}{}", "  ".repeat(indent), line)?;
+                        lines(next, indent + 1, String::new(), f)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+        lines(self, 0, String::new(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Outcome, Solver, Value};
+    use crate::board::{Board, Tile::*};
+
+    #[test]
+    fn solve() {
+        let mut solver = Solver::new();
+        assert_eq!(solver.value(&Board::new(3, 3), Cross), Value { outcome: Outcome::Draw, distance: 0 });
+
+        // Whoever moves wins on their row at once.
+        let board = Board::from_notation("XX./OO./...", 3).unwrap();
+        assert_eq!(solver.value(&board, Cross), Value { outcome: Outcome::Win, distance: 1 });
+        assert_eq!(solver.value(&board, Nought), Value { outcome: Outcome::Win, distance: 1 });
+        let board = Board::from_notation("X../.O./..X", 3).unwrap();
+        assert_eq!(solver.value(&board, Nought).outcome, Outcome::Draw);
+        let board = Board::from_notation("X../O../...", 3).unwrap();
+        assert_eq!(solver.value(&board, Cross), Value { outcome: Outcome::Win, distance: 5 });
+        assert_eq!(solver.value(&board, Nought), Value { outcome: Outcome::Draw, distance: 0 });
+        assert_eq!(solver.value(&board, Cross).to_string(), "win in 5 moves");
+        assert_eq!(solver.principal_variation(&board, Cross).len(), 5);
+
+        // After an edge reply Cross wins however Nought defends.
+        let board = Board::from_notation(".O./.X./...", 3).unwrap();
+        let proof = solver.proof(&board, Cross);
+        assert_eq!(proof.children.len(), 1);
+        assert!(proof.to_string().lines().count() > 1, "{}", proof);
+    }
+}
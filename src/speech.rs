@@ -0,0 +1,45 @@
+//! Moves and results spoken aloud for eyes-free play, through whichever speech synthesizer the
+//! system has.
+
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tick_tack_toe::log;
+
+/// Synthesizers and their arguments before the text, tried in order until one works.
+const SYNTHESIZERS: &[(&str, &[&str])] = &[
+    ("say",       &[]),
+    ("espeak-ng", &[]),
+    ("espeak",    &[]),
+    ("spd-say",   &["--wait"]),
+];
+
+/// Whether to speak, set by `--speak` and cleared when no synthesizer works.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Speaks `text` and waits until it has been said, so announcements don't talk over each other.
+pub fn say(text: &str) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    for (synthesizer, args) in SYNTHESIZERS {
+        let status = Command::new(synthesizer)
+            .args(*args)
+            .arg(text)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        match status {
+            Ok(status) if status.success() => return,
+            Ok(status) => log::debug!("{} failed with {}", synthesizer, status),
+            Err(err) => log::debug!("couldn't run {}: {}", synthesizer, err),
+        }
+    }
+
+    log::warn!("no speech synthesizer works, install espeak-ng or spd-say to hear the moves");
+    ENABLED.store(false, Ordering::Relaxed);
+}
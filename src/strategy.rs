@@ -0,0 +1,209 @@
+//! AI strategies registered by name with their settings, so games, tournaments and the arena can
+//! all refer to them by that name.
+
+use crate::engine::Engine;
+use crate::plugin::Plugin;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::sync::{Mutex, OnceLock};
+use tick_tack_toe::board::{Board, BoardStatus, Tile};
+
+/// Settings of a strategy, each only used by some algorithms.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct Config {
+    /// How many moves ahead minimax looks, 2 unless set.
+    pub depth: Option<u32>,
+    /// Random games Monte Carlo plays per candidate move, 100 unless set.
+    pub rollouts: Option<u32>,
+    /// Makes the random choices repeatable.
+    pub seed: Option<u64>,
+}
+impl Config {
+    /// Reads settings such as `depth=3 seed=7`.
+    pub fn parse(settings: &[&str]) -> Result<Self, String> {
+        let mut config = Self::default();
+        for setting in settings {
+            let (key, value) = setting.split_once('=').ok_or(format!("Settings look like depth=3, not '{}'.", setting))?;
+            let invalid = || format!("'{}' needs a whole number.", key);
+            match key {
+                "depth"    => config.depth = Some(value.parse().map_err(|_| invalid())?),
+                "rollouts" => config.rollouts = Some(value.parse().map_err(|_| invalid())?),
+                "seed"     => config.seed = Some(value.parse().map_err(|_| invalid())?),
+                _ => return Err(format!("'{}' isn't a setting, use depth, rollouts or seed.", key)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Algorithm {
+    Random,
+    /// Searches the whole game tree.
+    Perfect,
+    /// Searches `depth` moves ahead and takes positions beyond that as draws.
+    Minimax,
+    /// Plays random games from every candidate move and takes the one that scores best.
+    MonteCarlo,
+    /// Asks a library loaded at runtime, which may resign.
+    Plugin(&'static Plugin),
+    /// Asks the server of `connect-engine`, resigning when it can't be reached.
+    Engine(&'static Engine),
+}
+impl Algorithm {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "random"     => Some(Self::Random),
+            "perfect"    => Some(Self::Perfect),
+            "minimax"    => Some(Self::Minimax),
+            "montecarlo" => Some(Self::MonteCarlo),
+            _ => None,
+        }
+    }
+}
+
+pub struct Strategy {
+    pub name: &'static str,
+    pub algorithm: Algorithm,
+    pub config: Config,
+    rng: Mutex<StdRng>,
+}
+impl Strategy {
+    /// The move `side` makes, `None` on a full board or when a plugin or engine resigns.
+    pub fn choose(&self, board: &Board, side: Tile) -> Option<(usize, usize)> {
+        let mut rng = self.rng.lock().unwrap_or_else(|err| err.into_inner());
+        let rng = &mut *rng;
+        let moves = board.empty_tiles();
+        let other = side.opposite()?;
+
+        let values: Vec<i64> = match self.algorithm {
+            Algorithm::Random  => return moves.choose(rng).copied(),
+            Algorithm::Perfect => return board.best_move(side).map(|m| (m.row, m.col)),
+            Algorithm::Plugin(plugin) => return plugin.choose(board, side),
+            Algorithm::Engine(engine) => return engine.choose(board, side),
+            Algorithm::Minimax => {
+                let depth = self.config.depth.unwrap_or(2).max(1);
+                moves.iter().map(|&(row, col)| {
+                    let mut board = board.clone();
+                    let _ = board.set(side, row, col);
+                    -i64::from(negamax(&board, other, depth - 1))
+                }).collect()
+            }
+            Algorithm::MonteCarlo => {
+                let rollouts = self.config.rollouts.unwrap_or(100).max(1);
+                moves.iter().map(|&(row, col)| {
+                    let mut board = board.clone();
+                    let _ = board.set(side, row, col);
+                    (0..rollouts).map(|_| rollout(board.clone(), side, other, rng)).sum()
+                }).collect()
+            }
+        };
+
+        // Equally good moves are chosen between at random, so games vary.
+        let best = values.iter().max()?;
+        let candidates: Vec<(usize, usize)> = moves.into_iter().zip(&values).filter(|(_, v)| *v == best).map(|(m, _)| m).collect();
+        candidates.choose(rng).copied()
+    }
+}
+
+/// The value of the position for `side` to move, searching `depth` more moves: positive for a win,
+/// higher the sooner it comes, negative for a loss and 0 for draws and positions beyond the horizon.
+fn negamax(board: &Board, side: Tile, depth: u32) -> i32 {
+    match board.board_status() {
+        // The last move won, or in a game scored by rows the board filled up.
+        BoardStatus::Winner(winner) if winner == side => return depth as i32 + 1,
+        BoardStatus::Winner(_) => return -(depth as i32 + 1),
+        BoardStatus::Tie => return 0,
+        BoardStatus::Continue => (),
+    }
+    if depth == 0 {
+        return 0;
+    }
+
+    let Some(other) = side.opposite() else { return 0 };
+    let mut best = i32::MIN;
+    for (row, col) in board.empty_tiles() {
+        // On a copy, as under decay taking the move back would have to bring back a mark.
+        let mut board = board.clone();
+        let _ = board.set(side, row, col);
+        best = best.max(-negamax(&board, other, depth - 1));
+    }
+    best
+}
+
+/// Plays random moves from `to_move` on to the end, scoring 2 when `side` wins and 1 for a draw.
+/// Games under decay that go on for `ROLLOUT_ROUNDS` times the tiles of the board count as draws.
+fn rollout(mut board: Board, side: Tile, mut to_move: Tile, rng: &mut StdRng) -> i64 {
+    const ROLLOUT_ROUNDS: usize = 4;
+    for _ in 0..ROLLOUT_ROUNDS * board.length() * board.length() {
+        match board.board_status() {
+            BoardStatus::Winner(winner) => return if winner == side { 2 } else { 0 },
+            BoardStatus::Tie => return 1,
+            BoardStatus::Continue => (),
+        }
+        let Some(&(row, col)) = board.empty_tiles().choose(rng) else { return 1 };
+        let _ = board.set(to_move, row, col);
+        to_move = to_move.opposite().unwrap_or(to_move);
+    }
+    1
+}
+
+fn registry() -> &'static Mutex<Vec<&'static Strategy>> {
+    static REGISTRY: OnceLock<Mutex<Vec<&'static Strategy>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let builtin = [("minimax AI", Algorithm::Minimax), ("monte carlo AI", Algorithm::MonteCarlo)];
+        Mutex::new(builtin.into_iter().map(|(name, algorithm)| new(name.to_string(), algorithm, Config::default())).collect())
+    })
+}
+
+fn new(name: String, algorithm: Algorithm, config: Config) -> &'static Strategy {
+    let rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    // Strategies live as long as the program, which only ever registers a handful.
+    Box::leak(Box::new(Strategy { name: Box::leak(name.into_boxed_str()), algorithm, config, rng: Mutex::new(rng) }))
+}
+
+/// Adds a strategy under `name`, which must not be taken by a player kind or another strategy.
+pub fn register(name: &str, algorithm: Algorithm, config: Config) -> Result<&'static Strategy, String> {
+    let mut strategies = registry().lock().unwrap_or_else(|err| err.into_inner());
+    if ["human", "random AI", "perfect AI"].contains(&name) || strategies.iter().any(|s| s.name == name) {
+        return Err(format!("There already is a player called '{}'.", name));
+    }
+
+    let strategy = new(name.to_string(), algorithm, config);
+    strategies.push(strategy);
+    Ok(strategy)
+}
+
+pub fn get(name: &str) -> Option<&'static Strategy> {
+    registry().lock().unwrap_or_else(|err| err.into_inner()).iter().find(|s| s.name == name).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get, register, Algorithm, Config};
+    use tick_tack_toe::board::{Board, Tile::*};
+
+    #[test]
+    fn strategies() {
+        assert_eq!(Config::parse(&["depth=3", "seed=7"]), Ok(Config { depth: Some(3), rollouts: None, seed: Some(7) }));
+        assert!(Config::parse(&["depth"]).is_err());
+        assert!(Config::parse(&["speed=3"]).is_err());
+
+        let config = Config { seed: Some(1), ..Config::default() };
+        let minimax = register("test minimax", Algorithm::Minimax, config).unwrap();
+        let monte_carlo = register("test monte carlo", Algorithm::MonteCarlo, config).unwrap();
+        assert!(register("test minimax", Algorithm::Random, config).is_err());
+        assert!(register("human", Algorithm::Random, config).is_err());
+        assert_eq!(get("test minimax").map(|s| s.algorithm), Some(Algorithm::Minimax));
+
+        // Both take a win when there is one, minimax also sees the opponent's coming.
+        let board = Board::from_notation("XX./OO./...", 3).unwrap();
+        assert_eq!(minimax.choose(&board, Cross), Some((0, 2)));
+        assert_eq!(monte_carlo.choose(&board, Cross), Some((0, 2)));
+        assert_eq!(minimax.choose(&Board::from_notation("XX./O../...", 3).unwrap(), Nought), Some((0, 2)));
+    }
+}
@@ -0,0 +1,97 @@
+//! What the terminal can show, found out once at startup. Windows consoles only understand the
+//! escape codes behind colors and redrawing in place once virtual terminal processing is turned
+//! on, and only show characters beyond ASCII in the UTF-8 code page. How many colors there are
+//! is up to the terminal, which tells programs in `COLORTERM` and `TERM`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use tick_tack_toe::render::ColorDepth;
+
+static ESCAPES: AtomicBool = AtomicBool::new(true);
+static UNICODE: AtomicBool = AtomicBool::new(true);
+
+/// Sets the console up for escape codes and UTF-8 where it needs it, and remembers what worked.
+pub fn init() {
+    #[cfg(windows)]
+    {
+        let (escapes, unicode) = windows::set_up();
+        ESCAPES.store(escapes, Ordering::Relaxed);
+        UNICODE.store(unicode, Ordering::Relaxed);
+    }
+}
+
+/// Whether ANSI escape codes for colors and cursor movement are understood.
+pub fn escapes() -> bool {
+    ESCAPES.load(Ordering::Relaxed)
+}
+
+/// Whether characters beyond ASCII, such as box-drawing lines, come out right.
+pub fn unicode() -> bool {
+    UNICODE.load(Ordering::Relaxed)
+}
+
+/// How many colors the terminal shows, from what it announces in the environment.
+pub fn color_depth() -> ColorDepth {
+    let var = |name| std::env::var(name).unwrap_or_default();
+    depth_from(&var("COLORTERM"), &var("TERM"))
+}
+
+fn depth_from(colorterm: &str, term: &str) -> ColorDepth {
+    match (colorterm, term) {
+        ("truecolor" | "24bit", _) => ColorDepth::TrueColor,
+        // Windows consoles have had 24-bit color as long as they have had escape codes.
+        _ if cfg!(windows) => ColorDepth::TrueColor,
+        (_, term) if term.contains("256color") => ColorDepth::Ansi256,
+        _ => ColorDepth::Ansi16,
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::ffi::c_void;
+
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+    const CP_UTF8: u32 = 65001;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(which: u32) -> *mut c_void;
+        fn GetConsoleMode(console: *mut c_void, mode: *mut u32) -> i32;
+        fn SetConsoleMode(console: *mut c_void, mode: u32) -> i32;
+        fn GetConsoleOutputCP() -> u32;
+        fn SetConsoleOutputCP(code_page: u32) -> i32;
+    }
+
+    /// Turns on escape codes and the UTF-8 code page, returning which of them the console has.
+    /// Output that isn't a console, such as a pipe, is passed on as it is.
+    pub fn set_up() -> (bool, bool) {
+        // SAFETY: the calls only take the standard output handle and plain numbers, and mode is
+        // a valid place for GetConsoleMode to write to.
+        unsafe {
+            let console = GetStdHandle(STD_OUTPUT_HANDLE);
+            let mut mode = 0;
+            if GetConsoleMode(console, &mut mode) == 0 {
+                return (true, true);
+            }
+            let escapes = mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                || SetConsoleMode(console, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0;
+            let unicode = GetConsoleOutputCP() == CP_UTF8 || SetConsoleOutputCP(CP_UTF8) != 0;
+            (escapes, unicode)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::depth_from;
+    use tick_tack_toe::render::ColorDepth;
+
+    #[test]
+    #[cfg(not(windows))]
+    fn detect_colors() {
+        assert_eq!(depth_from("truecolor", "xterm-256color"), ColorDepth::TrueColor);
+        assert_eq!(depth_from("", "xterm-256color"), ColorDepth::Ansi256);
+        assert_eq!(depth_from("", "xterm"), ColorDepth::Ansi16);
+        assert_eq!(depth_from("", ""), ColorDepth::Ansi16);
+    }
+}
@@ -0,0 +1,222 @@
+//! Tournaments between AI entrants. Every pairing plays two games, each side moving first once,
+//! and scores a point per win and half a point per draw.
+
+use crate::player::Player;
+use crate::setup::Setup;
+use crate::simulate;
+use tick_tack_toe::board::{BoardStatus, Tile};
+use tick_tack_toe::Result;
+use tick_tack_toe::variant::Variant;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Pairing {
+    /// Everyone plays everyone once.
+    RoundRobin,
+    /// This many rounds, each pairing entrants with equal scores who haven't met yet.
+    Swiss(u32),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entrant {
+    pub name: String,
+    /// As in `Player::from_kind`.
+    pub kind: &'static str,
+    /// In half points, so draws stay whole.
+    pub half_points: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// The half points won in the games this entrant moved first, of `half_points`.
+    pub first_half_points: u32,
+    /// Indices of the entrants already played.
+    pub opponents: Vec<usize>,
+    pub had_bye: bool,
+}
+impl Entrant {
+    /// The entrants for `kinds`, numbered when a kind enters more than once.
+    pub fn all(kinds: &[&'static str]) -> Vec<Self> {
+        kinds.iter()
+            .enumerate()
+            .map(|(i, &kind)| {
+                let name = match kinds.iter().filter(|&&k| k == kind).count() {
+                    1 => kind.to_string(),
+                    _ => format!("{} #{}", kind, kinds[..=i].iter().filter(|&&k| k == kind).count()),
+                };
+                Self { name, kind, half_points: 0, wins: 0, draws: 0, losses: 0, first_half_points: 0, opponents: Vec::new(), had_bye: false }
+            })
+            .collect()
+    }
+
+    pub fn points(&self) -> f64 {
+        f64::from(self.half_points) / 2.0
+    }
+}
+
+/// Plays the tournament on `size` boards, calling `on_round` with the round number and the
+/// entrants after every round.
+pub fn run(
+    kinds: &[&'static str],
+    pairing: Pairing,
+    size: usize,
+    win_length: usize,
+    mut on_round: impl FnMut(usize, &[Entrant]),
+) -> Result<Vec<Entrant>> {
+    let mut entrants = Entrant::all(kinds);
+    let rounds = match pairing {
+        Pairing::RoundRobin => round_robin(entrants.len()),
+        Pairing::Swiss(rounds) => vec![Vec::new(); rounds as usize],
+    };
+
+    for (round, pairs) in rounds.into_iter().enumerate() {
+        let pairs = match pairing {
+            Pairing::RoundRobin => pairs,
+            Pairing::Swiss(_) => {
+                let (pairs, bye) = swiss(&entrants);
+                if let Some(bye) = bye {
+                    // A bye counts as two draws.
+                    entrants[bye].half_points += 2;
+                    entrants[bye].had_bye = true;
+                }
+                pairs
+            }
+        };
+
+        for (a, b) in pairs {
+            play(&mut entrants, a, b, size, win_length)?;
+        }
+        on_round(round + 1, &entrants);
+    }
+    Ok(entrants)
+}
+
+fn play(entrants: &mut [Entrant], a: usize, b: usize, size: usize, win_length: usize) -> Result<()> {
+    let player = |i: usize, tile| Player::from_kind(entrants[i].kind, tile).unwrap_or(Player::RandomAi(tile));
+    let setup = Setup { players: [player(a, Tile::Cross), player(b, Tile::Nought)], names: [None, None], display_names: [None, None], size, win_length, handicap: None, obstacles: 0, variant: Variant::Standard };
+
+    for result in simulate::run(&setup, 2)? {
+        let first = if result.first == Tile::Cross { a } else { b };
+        let (winner, loser) = match result.status {
+            BoardStatus::Winner(Tile::Cross) => (a, b),
+            BoardStatus::Winner(_)           => (b, a),
+            _ => {
+                for i in [a, b] {
+                    entrants[i].half_points += 1;
+                    entrants[i].draws += 1;
+                }
+                entrants[first].first_half_points += 1;
+                continue;
+            }
+        };
+        entrants[winner].half_points += 2;
+        entrants[winner].wins += 1;
+        entrants[loser].losses += 1;
+        if winner == first {
+            entrants[first].first_half_points += 2;
+        }
+    }
+
+    entrants[a].opponents.push(b);
+    entrants[b].opponents.push(a);
+    Ok(())
+}
+
+/// The pairs of every round, by the circle method. With an odd number of entrants one of them
+/// sits out each round.
+pub fn round_robin(entrants: usize) -> Vec<Vec<(usize, usize)>> {
+    // Seat `entrants` is the bye.
+    let seats = entrants + entrants % 2;
+    let mut circle: Vec<usize> = (0..seats).collect();
+
+    (1..seats)
+        .map(|_| {
+            let pairs = (0..seats / 2)
+                .map(|i| (circle[i], circle[seats - 1 - i]))
+                .filter(|&(a, b)| a < entrants && b < entrants)
+                .collect();
+            circle[1..].rotate_right(1);
+            pairs
+        })
+        .collect()
+}
+
+/// The next Swiss round: going down the standings, each entrant meets the highest placed one
+/// they haven't played yet. With an odd number the lowest placed entrant without a bye sits out.
+pub fn swiss(entrants: &[Entrant]) -> (Vec<(usize, usize)>, Option<usize>) {
+    let mut order = ranking(entrants);
+
+    let bye = match order.len() % 2 {
+        1 => {
+            let position = order.iter().rposition(|&i| !entrants[i].had_bye).unwrap_or(order.len() - 1);
+            Some(order.remove(position))
+        }
+        _ => None,
+    };
+
+    let mut pairs = Vec::new();
+    while !order.is_empty() {
+        let a = order.remove(0);
+        // Everyone left may have been played already, then a rematch it is.
+        let position = order.iter().position(|b| !entrants[a].opponents.contains(b)).unwrap_or(0);
+        pairs.push((a, order.remove(position)));
+    }
+    (pairs, bye)
+}
+
+/// Entrant indices from first place down, by points, then by the points of the opponents
+/// they've played, then by entry order.
+pub fn ranking(entrants: &[Entrant]) -> Vec<usize> {
+    let buchholz = |e: &Entrant| e.opponents.iter().map(|&o| entrants[o].half_points).sum::<u32>();
+
+    let mut order: Vec<usize> = (0..entrants.len()).collect();
+    order.sort_by_key(|&i| (std::cmp::Reverse(entrants[i].half_points), std::cmp::Reverse(buchholz(&entrants[i])), i));
+    order
+}
+
+/// The standings table after `round`, with the points split by who moved first.
+pub fn standings(round: usize, entrants: &[Entrant]) -> String {
+    let mut out = format!("Standings after round {}:\n", round);
+    for (place, i) in ranking(entrants).into_iter().enumerate() {
+        let e = &entrants[i];
+        let first = f64::from(e.first_half_points) / 2.0;
+        out.push_str(&format!("{:>4}. {:<16} {:>5.1}  +{} ={} -{}  ({:.1} moving first, {:.1} second)\n",
+            place + 1, e.name, e.points(), e.wins, e.draws, e.losses, first, e.points() - first));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{round_robin, run, swiss, Entrant, Pairing};
+
+    #[test]
+    fn pairings() {
+        let rounds = round_robin(5);
+        assert_eq!(rounds.len(), 5);
+        let mut met: Vec<(usize, usize)> = rounds.iter().flatten().map(|&(a, b)| (a.min(b), a.max(b))).collect();
+        met.sort();
+        met.dedup();
+        assert_eq!(met.len(), 10);
+        assert!(rounds.iter().all(|pairs| pairs.len() == 2));
+
+        let mut entrants = Entrant::all(&["random AI", "perfect AI", "random AI"]);
+        assert_eq!(entrants[2].name, "random AI #2");
+        entrants[1].half_points = 4;
+        entrants[0].opponents.push(1);
+        entrants[1].opponents.push(0);
+        assert_eq!(swiss(&entrants), (vec![(1, 0)], Some(2)));
+        entrants[2].had_bye = true;
+        assert_eq!(swiss(&entrants), (vec![(1, 2)], Some(0)));
+
+        let mut rounds_seen = Vec::new();
+        let entrants = run(&["random AI"; 4], Pairing::Swiss(2), 3, 3, |round, _| rounds_seen.push(round)).unwrap();
+        assert_eq!(rounds_seen, [1, 2]);
+        // Two rounds of two pairings of two games.
+        assert_eq!(entrants.iter().map(|e| e.points()).sum::<f64>(), 8.0);
+        assert!(entrants.iter().all(|e| e.opponents.len() == 2 && e.opponents[0] != e.opponents[1]));
+        assert!(entrants.iter().all(|e| e.first_half_points <= e.half_points));
+
+        // On a 2x2 board with rows of two, whoever moves first wins.
+        let entrants = run(&["perfect AI"; 2], Pairing::RoundRobin, 2, 2, |_, _| ()).unwrap();
+        assert!(entrants.iter().all(|e| e.half_points == 2 && e.first_half_points == 2));
+    }
+}
@@ -0,0 +1,155 @@
+//! The game tree below a position, for tools that explore it: nodes are expanded on demand,
+//! evaluated by the search and followed along the best line.
+
+use crate::board::{Board, BoardStatus, Tile};
+use crate::game::Move;
+
+/// A position and, once expanded, the positions every move leads to.
+#[derive(Clone, Debug)]
+pub struct GameTree {
+    board: Board,
+    to_move: Tile,
+    last_move: Option<Move>,
+    children: Vec<GameTree>,
+    /// For `to_move`, 1 is a forced win and -1 a forced loss, once evaluated.
+    value: Option<i8>,
+}
+impl GameTree {
+    /// The root of the tree below `board`, with `to_move` to make the next move.
+    pub fn new(board: Board, to_move: Tile) -> Self {
+        Self { board, to_move, last_move: None, children: Vec::new(), value: None }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn to_move(&self) -> Tile {
+        self.to_move
+    }
+
+    /// The move that led here from the parent, `None` at the root.
+    pub fn last_move(&self) -> Option<Move> {
+        self.last_move
+    }
+
+    pub fn status(&self) -> BoardStatus {
+        self.board.board_status()
+    }
+
+    /// The children added by `expand`, empty before that and when the game is over.
+    pub fn children(&self) -> &[GameTree] {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut [GameTree] {
+        &mut self.children
+    }
+
+    /// The child that playing `(row, col)` leads to, when expanded.
+    pub fn child(&self, row: usize, col: usize) -> Option<&GameTree> {
+        self.children.iter().find(|child| child.last_move.is_some_and(|m| (m.row, m.col) == (row, col)))
+    }
+
+    pub fn child_mut(&mut self, row: usize, col: usize) -> Option<&mut GameTree> {
+        self.children.iter_mut().find(|child| child.last_move.is_some_and(|m| (m.row, m.col) == (row, col)))
+    }
+
+    /// Adds a child for every move from here, unless the game is over or it was already done.
+    pub fn expand(&mut self) -> &mut [GameTree] {
+        let Some(next) = self.to_move.opposite() else { return &mut self.children };
+        if self.children.is_empty() && self.status() == BoardStatus::Continue {
+            self.children = self.board.empty_tiles().into_iter().map(|(row, col)| {
+                let mut board = self.board.clone();
+                let _ = board.set(self.to_move, row, col);
+                let last_move = Some(Move { tile: self.to_move, row, col, from: None });
+                Self { board, to_move: next, last_move, children: Vec::new(), value: None }
+            }).collect();
+        }
+        &mut self.children
+    }
+
+    /// How many nodes have been added below and including this one.
+    pub fn size(&self) -> usize {
+        1 + self.children.iter().map(GameTree::size).sum::<usize>()
+    }
+
+    /// The value of the position for the side to move, 1 for a forced win, 0 for a draw and -1 for
+    /// a forced loss. Searches the whole subtree the first time, without adding nodes to it, and
+    /// gives the children that have been added their values on the way.
+    pub fn evaluate(&mut self) -> i8 {
+        if let Some(value) = self.value {
+            return value;
+        }
+
+        let value = match self.status() {
+            BoardStatus::Winner(winner) => if winner == self.to_move { 1 } else { -1 },
+            BoardStatus::Tie => 0,
+            BoardStatus::Continue => {
+                let values = self.board.move_values(self.to_move);
+                for child in &mut self.children {
+                    let Some(m) = child.last_move else { continue };
+                    child.value = values.iter().find(|(pos, _)| *pos == (m.row, m.col)).map(|(_, value)| -value);
+                }
+                values.iter().map(|(_, value)| *value).max().unwrap_or(0)
+            }
+        };
+        self.value = Some(value);
+        value
+    }
+
+    /// The value found by `evaluate`, `None` before that.
+    pub fn value(&self) -> Option<i8> {
+        self.value
+    }
+
+    /// The moves of best play from here to the end of the game.
+    pub fn principal_variation(&self) -> PrincipalVariation {
+        PrincipalVariation { board: self.board.clone(), to_move: self.to_move }
+    }
+}
+
+/// The best move in turn for both sides, searching each position as it's reached.
+pub struct PrincipalVariation {
+    board: Board,
+    to_move: Tile,
+}
+impl Iterator for PrincipalVariation {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        if self.board.board_status() != BoardStatus::Continue {
+            return None;
+        }
+        let m = self.board.best_move(self.to_move)?;
+        self.board.play(&m).ok()?;
+        self.to_move = self.to_move.opposite()?;
+        Some(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameTree;
+    use crate::board::{Board, Tile::*};
+    use crate::game::Move;
+
+    #[test]
+    fn game_tree() {
+        let mut tree = GameTree::new(Board::from_notation("XX./OO./...", 3).unwrap(), Cross);
+        assert_eq!(tree.expand().len(), 5);
+        assert_eq!(tree.size(), 6);
+        assert_eq!(tree.child(2, 2).and_then(|child| child.value()), None);
+
+        assert_eq!(tree.evaluate(), 1);
+        assert_eq!(tree.child(0, 2).and_then(|child| child.value()), Some(-1));
+        // Anything else lets Nought win on the middle row.
+        assert_eq!(tree.child_mut(2, 2).map(|child| child.evaluate()), Some(1));
+
+        let line: Vec<Move> = tree.principal_variation().collect();
+        assert_eq!(line, [Move { tile: Cross, row: 0, col: 2, from: None }]);
+        let child = tree.child_mut(2, 2).unwrap();
+        assert_eq!(child.expand().len(), 4);
+        assert_eq!(child.principal_variation().next(), Some(Move { tile: Nought, row: 1, col: 2, from: None }));
+    }
+}
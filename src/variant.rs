@@ -0,0 +1,277 @@
+//! Variants of the rules, chosen when a game starts and kept in the `Variant` tag of its record.
+
+use crate::board::{Board, Shape, Tile};
+use crate::game::Move;
+use rand::seq::SliceRandom;
+
+/// The search under decay and in Morris looks no further ahead than this many moves, and less
+/// when that would mean more than `HORIZON_POSITIONS` positions, as those games have no end to
+/// search to.
+const HORIZON_DEPTH: u32 = 8;
+const HORIZON_POSITIONS: usize = 200_000;
+/// What a point ahead on a full board is worth to the search of a game scored by rows, against a
+/// point ahead where its horizon cuts it short.
+const FINAL_POINT: i32 = 10_000;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// Fog of war: each side only sees its own marks and the tiles next to them, and finds out
+    /// about the other marks by trying to play on them. The fog lifts when the game ends.
+    Fog,
+    /// On a board of hexagons, where rows run three ways instead of four, see `Shape::Hex`.
+    Hex,
+    /// Each side's oldest mark goes when it plays one more than this many, so the board never
+    /// fills up and nobody ties.
+    Decay(usize),
+    /// Three Men's Morris: each side has this many pieces, and once they are all on the board a
+    /// turn moves one of them to an empty tile next to it. A side that can't move loses.
+    Morris(usize),
+    /// Every row completed scores a point and the game goes on, until the board is full and the
+    /// side with the most points wins. For boards larger than the win length.
+    Scoring,
+}
+impl Variant {
+    /// The marks each side keeps under `decay`, and the pieces it has in `morris`, without a
+    /// number.
+    pub const DECAY_MARKS: usize = 3;
+    pub const MORRIS_PIECES: usize = 3;
+
+    /// The variant called `name` on the command line and in records, `decay:4` for decay after
+    /// four marks and `morris:4` for four pieces each.
+    pub fn named(name: &str) -> Option<Self> {
+        let count = |count: &str| count.parse().ok().filter(|&count| count > 0);
+        match name.split_once(':') {
+            Some(("decay", marks)) => count(marks).map(Self::Decay),
+            Some(("morris", pieces)) => count(pieces).map(Self::Morris),
+            Some(_) => None,
+            None => [Self::Standard, Self::Fog, Self::Hex, Self::Decay(Self::DECAY_MARKS), Self::Morris(Self::MORRIS_PIECES), Self::Scoring].into_iter()
+                .find(|variant| variant.name().split(':').next() == Some(name)),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Standard       => "standard".to_string(),
+            Self::Fog            => "fog".to_string(),
+            Self::Hex            => "hex".to_string(),
+            Self::Decay(marks)   => format!("decay:{}", marks),
+            Self::Morris(pieces) => format!("morris:{}", pieces),
+            Self::Scoring        => "scoring".to_string(),
+        }
+    }
+
+    /// The shape of the board the variant is played on.
+    pub fn shape(&self) -> Shape {
+        match self {
+            Self::Hex => Shape::Hex,
+            _ => Shape::Square,
+        }
+    }
+
+    /// The marks a side keeps under decay, `None` when they stay.
+    pub fn decay(&self) -> Option<usize> {
+        match self {
+            Self::Decay(marks) => Some(*marks),
+            _ => None,
+        }
+    }
+
+    /// The pieces each side has in Morris, `None` when it places marks until the board is full.
+    pub fn pieces(&self) -> Option<usize> {
+        match self {
+            Self::Morris(pieces) => Some(*pieces),
+            _ => None,
+        }
+    }
+
+    /// Whether rows score points instead of ending the game.
+    pub fn scoring(&self) -> bool {
+        *self == Self::Scoring
+    }
+
+    /// Checks that the variant can be played on a board of `size` with rows of `win_length`:
+    /// under decay and in Morris a side must keep enough marks for a row, and leave a tile free
+    /// to play on.
+    pub fn check(&self, size: usize, win_length: usize) -> Result<(), String> {
+        match (self.decay(), self.pieces()) {
+            (Some(marks), _) if marks < win_length => Err(format!("Under decay a side needs to keep at least the win length of {} marks.", win_length)),
+            (Some(marks), _) if 2 * marks >= size * size => Err(format!("Decay after {} marks would fill the board.", marks)),
+            (_, Some(pieces)) if pieces < win_length => Err(format!("In Morris a side needs at least the win length of {} pieces.", win_length)),
+            (_, Some(pieces)) if 2 * pieces >= size * size => Err(format!("{} pieces each would leave no tile to move to.", pieces)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The tiles of `board` that `side` can't see in fog of war: all but its own marks, the tiles
+/// next to them, diagonally too, and the blocked tiles, which are no secret.
+pub fn fogged(board: &Board, side: Tile) -> Vec<(usize, usize)> {
+    let length = board.length();
+    let own = |row: usize, col: usize| board.get(row, col) == Some(side);
+    let nearby = |row: usize, col: usize| {
+        (row.saturating_sub(1)..=(row + 1).min(length - 1))
+            .any(|r| (col.saturating_sub(1)..=(col + 1).min(length - 1)).any(|c| own(r, c)))
+    };
+    (0..length)
+        .flat_map(|row| (0..length).map(move |col| (row, col)))
+        .filter(|&(row, col)| !nearby(row, col) && board.get(row, col) != Some(Tile::Blocked))
+        .collect()
+}
+
+/// Every move `side` can make on `board`: a mark on any empty tile, or once all its pieces are
+/// on the board in Morris, a piece moved to an empty tile next to it.
+pub fn moves(board: &Board, side: Tile) -> Vec<Move> {
+    let empty = board.empty_tiles();
+    if !board.must_move(side) {
+        return empty.into_iter().map(|(row, col)| Move { tile: side, row, col, from: None }).collect();
+    }
+    let length = board.length();
+    (0..length)
+        .flat_map(|row| (0..length).map(move |col| (row, col)))
+        .filter(|&(row, col)| board.get(row, col) == Some(side))
+        .flat_map(|from| {
+            board.neighbours(from).into_iter()
+                .filter(|&(row, col)| board.get(row, col) == Some(Tile::Empty))
+                .map(move |(row, col)| Move { tile: side, row, col, from: Some(from) })
+        })
+        .collect()
+}
+
+/// The value of every tile `side` can move to, under decay, in Morris or scored by rows, as far
+/// as the search sees ahead: 1 for a win, -1 for a loss and 0 when neither is in sight. A tile
+/// more than one piece can move to has the value of the best of those moves.
+pub fn horizon_values(board: &Board, side: Tile) -> Vec<((usize, usize), i8)> {
+    let mut values: Vec<((usize, usize), i8)> = Vec::new();
+    for (m, score) in horizon_scores(board, side) {
+        // Being ahead where the search stops isn't a win yet.
+        let seen = !board.scoring() || score.abs() >= FINAL_POINT;
+        let value = if seen { score.signum() as i8 } else { 0 };
+        match values.iter_mut().find(|(tile, _)| *tile == (m.row, m.col)) {
+            Some((_, best)) => *best = value.max(*best),
+            None => values.push(((m.row, m.col), value)),
+        }
+    }
+    values
+}
+
+/// The move the search finds best for `side` under decay, in Morris or scored by rows, the
+/// soonest win, the latest loss or the most points ahead, chosen at random between equally good ones so that games don't go round in
+/// circles.
+pub fn horizon_move(board: &Board, side: Tile) -> Option<Move> {
+    let scores = horizon_scores(board, side);
+    let best = scores.iter().map(|&(_, score)| score).max()?;
+    let candidates: Vec<Move> = scores.into_iter().filter(|&(_, score)| score == best).map(|(m, _)| m).collect();
+    candidates.choose(&mut rand::thread_rng()).copied()
+}
+
+fn horizon_scores(board: &Board, side: Tile) -> Vec<(Move, i32)> {
+    let Some(other) = side.opposite() else { return Vec::new() };
+    let moves = moves(board, side);
+    let depth = (1..=HORIZON_DEPTH).rev()
+        .find(|&depth| moves.len().saturating_pow(depth) <= HORIZON_POSITIONS)
+        .unwrap_or(1);
+    moves.into_iter().map(|m| {
+        let mut next = board.clone();
+        let _ = next.play(&m);
+        (m, -negamax(&next, other, depth - 1, -i32::MAX, i32::MAX))
+    }).collect()
+}
+
+/// The value of `board` for `side` to move, searching `depth` more moves with alpha-beta pruning:
+/// positive for a win, higher the sooner it comes, negative for a loss and 0 beyond the horizon.
+/// A side that can't move loses, as in Morris, and scored by rows the value is the points ahead.
+/// Moves are played on copies, as taking one back would have to bring back the mark it decayed.
+fn negamax(board: &Board, side: Tile, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+    let lost = -(depth as i32 + 1);
+    if board.scoring() {
+        let lead = side.opposite().map_or(0, |other| board.points(side) as i32 - board.points(other) as i32);
+        if board.empty_tiles().is_empty() {
+            return lead * FINAL_POINT;
+        }
+        if depth == 0 {
+            return lead;
+        }
+    } else if board.winning_line().is_some() {
+        // The last move won.
+        return lost;
+    }
+    let Some(other) = side.opposite().filter(|_| depth > 0) else { return 0 };
+
+    let moves = moves(board, side);
+    if moves.is_empty() {
+        return lost;
+    }
+    let mut best = -i32::MAX;
+    for m in moves {
+        let mut next = board.clone();
+        let _ = next.play(&m);
+        let value = -negamax(&next, other, depth - 1, -beta, -alpha);
+        best = best.max(value);
+        alpha = alpha.max(value);
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fogged, horizon_move, horizon_values, moves, Variant};
+    use crate::board::{Board, BoardStatus, Tile::*};
+
+    #[test]
+    fn fog() {
+        assert_eq!(Variant::named("fog"), Some(Variant::Fog));
+        assert_eq!(Variant::named("chess"), None);
+
+        let board = Board::from_notation("X..../...../....O/...../.....", 3).unwrap();
+        let hidden = fogged(&board, Cross);
+        assert_eq!(hidden.len(), 25 - 4);
+        assert!(!hidden.contains(&(1, 1)) && hidden.contains(&(2, 4)) && hidden.contains(&(0, 2)));
+        assert_eq!(fogged(&board, Nought).len(), 25 - 6);
+        assert_eq!(fogged(&Board::new(3, 3), Cross).len(), 9);
+    }
+
+    #[test]
+    fn decay() {
+        assert_eq!(Variant::named("decay"), Some(Variant::Decay(3)));
+        assert_eq!(Variant::named("decay:4").map(|variant| variant.name()), Some("decay:4".to_string()));
+        assert_eq!(Variant::named("decay:0"), None);
+        assert!(Variant::Decay(2).check(3, 3).is_err() && Variant::Decay(5).check(3, 3).is_err());
+
+        let mut board = Board::new(3, 3).with_decay(Some(3));
+        for (row, col) in [(0, 0), (1, 1), (2, 0)] {
+            board.set(Cross, row, col).unwrap();
+        }
+        assert_eq!(board.next_to_decay(Cross), Some((0, 0)));
+        assert_eq!(board.next_to_decay(Nought), None);
+        // The fourth mark takes the first one off, and the row it would have made with it.
+        board.set(Cross, 2, 2).unwrap();
+        assert_eq!(board.get(0, 0), Some(Empty));
+        assert_eq!(board.board_status(), BoardStatus::Continue);
+
+        board.set(Nought, 0, 1).unwrap();
+        board.set(Nought, 0, 2).unwrap();
+        assert_eq!(horizon_move(&board, Cross).map(|m| (m.row, m.col)), Some((2, 1)));
+        assert!(horizon_values(&board, Nought).contains(&((0, 0), 1)));
+    }
+
+    #[test]
+    fn morris() {
+        assert_eq!(Variant::named("morris:4"), Some(Variant::Morris(4)));
+        assert!(Variant::Morris(2).check(3, 3).is_err() && Variant::Morris(5).check(3, 3).is_err());
+
+        let board = Board::from_notation("X.X/XO./OO.", 3).unwrap().with_pieces(Some(3));
+        assert!(board.must_move(Cross) && board.must_move(Nought));
+        let cross = moves(&board, Cross);
+        assert!(cross.iter().all(|m| m.from.is_some()));
+        assert_eq!(cross.len(), 4);
+        // Up from the middle row, diagonally, to finish the top row.
+        let best = horizon_move(&board, Cross).unwrap();
+        assert_eq!((best.from, best.row, best.col), (Some((1, 0)), 0, 1));
+        assert!(moves(&Board::new(3, 3).with_pieces(Some(3)), Cross).iter().all(|m| m.from.is_none()));
+    }
+}
@@ -0,0 +1,139 @@
+//! Watch mode: two AIs play and the board is drawn after every move, with a pause that can be
+//! made shorter or longer while watching, and the game can be paused and gone through a move at
+//! a time. For demos, and for seeing how a change to an AI plays.
+
+use crate::cursor::{self, RawMode};
+use crate::keys::Key;
+use crate::player::{self, Turn};
+use crate::screen;
+use crate::setup::Setup;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+use tick_tack_toe::board::BoardStatus;
+use tick_tack_toe::game::Game;
+use tick_tack_toe::{render, Error};
+
+/// The shortest and longest pause between moves the speed keys go to.
+const FASTEST: Duration = Duration::from_millis(25);
+const SLOWEST: Duration = Duration::from_secs(10);
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Control {
+    /// Pause, or go on when paused.
+    Pause,
+    /// Make the next move now and pause after it.
+    Step,
+    Faster,
+    Slower,
+    Quit,
+}
+
+fn control(key: Key) -> Option<Control> {
+    match key {
+        Key::Char(' ' | 'p') => Some(Control::Pause),
+        Key::Char('n' | '.') | Key::Right | Key::Enter => Some(Control::Step),
+        Key::Char('+' | '=') | Key::Up => Some(Control::Faster),
+        Key::Char('-') | Key::Down => Some(Control::Slower),
+        Key::Char('q') | Key::Escape => Some(Control::Quit),
+        _ => None,
+    }
+}
+
+/// The pause between moves after `control` at `delay`: faster halves it and slower doubles it.
+fn adjust(delay: Duration, control: Control) -> Duration {
+    match control {
+        Control::Faster => (delay / 2).max(FASTEST),
+        Control::Slower => (delay * 2).clamp(FASTEST, SLOWEST),
+        _ => delay,
+    }
+}
+
+/// Plays `game` between the AIs of `setup`, the one at `first` moving first, pausing `delay`
+/// before every move. Returns how the game ended, `None` when watching was quit.
+pub fn run(setup: &Setup, game: &mut Game, first: usize, mut delay: Duration) -> tick_tack_toe::Result<Option<BoardStatus>> {
+    let names = setup.shown_names();
+    player::set_names(names.clone());
+    let theme = screen::theme();
+    // Without a terminal to read keys from, e.g. with the input piped, the game plays on its own.
+    let keys = RawMode::enter().ok();
+    let mut paused = false;
+    let mut turn = first;
+    screen::show_board(game, &theme, None, setup.players[first].tile());
+
+    loop {
+        if keys.is_some() {
+            print_controls(delay, paused);
+        }
+        let deadline = Instant::now() + delay;
+        loop {
+            let timeout = (!paused).then(|| deadline.saturating_duration_since(Instant::now()));
+            if timeout == Some(Duration::ZERO) {
+                break;
+            }
+            let key = match keys {
+                Some(_) => cursor::key_within(timeout).unwrap_or(Some(Key::Escape)),
+                None => {
+                    thread::sleep(timeout.unwrap_or_default());
+                    None
+                }
+            };
+            let Some(key) = key else { break };
+            match control(key) {
+                Some(Control::Pause) => paused = !paused,
+                Some(Control::Step) => {
+                    paused = true;
+                    break;
+                }
+                Some(Control::Quit) => return Ok(None),
+                Some(speed) => delay = adjust(delay, speed),
+                None => continue,
+            }
+            print_controls(delay, paused);
+        }
+
+        let p = &setup.players[turn];
+        match p.take_turn(game)? {
+            Turn::Moved => (),
+            Turn::Resigned => return Ok(Some(BoardStatus::Winner(p.tile().opposite().ok_or(Error::NotASide)?))),
+            Turn::Quit => return Ok(None),
+        }
+        screen::show_move(game, &theme, &render::move_made(p.tile(), &names));
+        let status = game.status();
+        if status != BoardStatus::Continue {
+            return Ok(Some(status));
+        }
+        turn = 1 - turn;
+    }
+}
+
+fn print_controls(delay: Duration, paused: bool) {
+    match paused {
+        true  => println!("Paused: space goes on, n makes the next move, q quits."),
+        false => println!("{} ms a move: space pauses, n steps, + and - change the speed, q quits.", delay.as_millis()),
+    }
+    let _ = io::stdout().flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adjust, control, Control, FASTEST, SLOWEST};
+    use crate::keys::Key;
+    use std::time::Duration;
+
+    #[test]
+    fn controls() {
+        assert_eq!(control(Key::Char(' ')), Some(Control::Pause));
+        assert_eq!(control(Key::Right), Some(Control::Step));
+        assert_eq!(control(Key::Char('x')), None);
+
+        let delay = Duration::from_millis(800);
+        assert_eq!(adjust(delay, Control::Faster), Duration::from_millis(400));
+        assert_eq!(adjust(delay, Control::Slower), Duration::from_millis(1600));
+        assert_eq!(adjust(delay, Control::Pause), delay);
+        // The speed keys stop at the fastest and slowest pauses, --delay 0 included.
+        assert_eq!(adjust(Duration::ZERO, Control::Slower), FASTEST);
+        assert_eq!(adjust(FASTEST, Control::Faster), FASTEST);
+        assert_eq!(adjust(SLOWEST, Control::Slower), SLOWEST);
+    }
+}